@@ -0,0 +1,200 @@
+// Criterion counterpart to the `main.rs` harness: one benchmark group per
+// phase, each parameterized over (n, t) and reporting throughput in
+// signers (or ciphertexts) per second. Signing always uses the first t+1
+// ids as SS, like the harness.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use curve25519_dalek::scalar::Scalar;
+use std::hint::black_box;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use threshold_signature::commitment::{
+    aggregate_commitments, aggregate_openings, commit_z, derive_h_from_g, verify_aggregate,
+};
+use threshold_signature::keygen::{kgen_for_ids, setup};
+use threshold_signature::protocol::{combine, sig1, verify};
+use threshold_signature::shamir::{lagrange_coeff, LagrangeTable};
+use threshold_signature::timed::{setup_timed, timed_decrypt, timed_encrypt, TimedParams};
+use threshold_signature::tracing::{admitter_issue_token, setup_admitter, trace_decrypt, trace_encrypt};
+use threshold_signature::testutil::{Session, MSG};
+use threshold_signature::types::{CommitmentMessage, OpeningMessage, PartialSignature, SignerState};
+
+const SIZES: [(usize, usize); 4] = [(8, 4), (16, 8), (32, 16), (64, 32)];
+
+// Each round is timed on the inputs of one `testutil::Session`. Sig2 runs
+// from the session's Sig2 states, which carry the Sig1 state (a_i, rho_i,
+// B_i) it reads.
+fn round1(s: &Session) -> Vec<(CommitmentMessage, SignerState)> {
+    s.ss.iter().map(|&i| sig1(&s.par, i, s.sk(i))).collect()
+}
+
+fn round2(s: &Session) -> Vec<(OpeningMessage, SignerState)> {
+    let mut signing = s.signing();
+    s.ss.iter().zip(&s.states).map(|(&i, st)| {
+        signing.sig2(i, &s.commitments, s.pk(i), s.sk(i), st).unwrap()
+    }).collect()
+}
+
+fn round3(s: &Session) -> Vec<PartialSignature> {
+    let mut signing = s.signing();
+    s.ss.iter().zip(&s.states).map(|(&i, st)| {
+        signing.sig3(i, s.sk(i), st, &s.commitments, &s.openings).unwrap()
+    }).collect()
+}
+
+fn bench_kgen(c: &mut Criterion) {
+    let mut g = c.benchmark_group("kgen");
+    for (n, t) in SIZES {
+        let par = setup(n, t).unwrap();
+        let ids: Vec<u32> = (1..=n as u32).collect();
+        g.throughput(Throughput::Elements(n as u64));
+        g.bench_with_input(BenchmarkId::from_parameter(format!("n={n},t={t}")), &par, |b, par| {
+            b.iter(|| kgen_for_ids(par, &ids).unwrap())
+        });
+    }
+    g.finish();
+}
+
+fn bench_rounds(c: &mut Criterion) {
+    let sessions: Vec<_> = SIZES.iter().map(|&(n, t)| (n, t, Session::new(n, t))).collect();
+
+    let mut g = c.benchmark_group("sig1");
+    for (n, t, s) in &sessions {
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| b.iter(|| round1(s)));
+    }
+    g.finish();
+
+    let mut g = c.benchmark_group("sig2");
+    for (n, t, s) in &sessions {
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| b.iter(|| round2(s)));
+    }
+    g.finish();
+
+    let mut g = c.benchmark_group("sig3");
+    g.sample_size(10);
+    for (n, t, s) in &sessions {
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| b.iter(|| round3(s)));
+    }
+    g.finish();
+
+    let mut g = c.benchmark_group("combine");
+    for (n, t, s) in &sessions {
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| {
+            b.iter(|| combine(&s.ss, &s.openings, &s.partials).unwrap())
+        });
+    }
+    g.finish();
+
+    let mut g = c.benchmark_group("verify");
+    for (n, t, s) in &sessions {
+        let sig = s.signature();
+        g.throughput(Throughput::Elements(1));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| {
+            b.iter(|| assert!(verify(&s.par, &s.pk_joint, &s.ss, black_box(MSG), &sig)))
+        });
+    }
+    g.finish();
+
+    let mut g = c.benchmark_group("verify_aggregate");
+    for (n, t, s) in &sessions {
+        let sig = s.signature();
+        let h = derive_h_from_g(&s.par.g);
+        let (cms, ops): (Vec<_>, Vec<_>) = s.ss.iter().zip(&s.partials)
+            .map(|(&i, ps)| commit_z(i, &s.par.g, &h, &Scalar::from_bytes_mod_order(ps.z_i)))
+            .unzip();
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::from_parameter(format!("n={n},t={t}")), |b| {
+            b.iter(|| {
+                let r = aggregate_openings(&ops);
+                let c_agg = aggregate_commitments(&cms).unwrap();
+                assert!(verify_aggregate(&s.par.g, &h, &c_agg, &sig.z, &r));
+            })
+        });
+    }
+    g.finish();
+}
+
+// Every L_{i,SS} of a k = 32 signing set: one `LagrangeTable` (shared
+// numerator, one batch inversion) against a `lagrange_coeff` call, with
+// its own inversion, per signer.
+fn bench_lagrange(c: &mut Criterion) {
+    let ss: Vec<u32> = (1..=32).collect();
+    let mut g = c.benchmark_group("lagrange");
+    g.throughput(Throughput::Elements(ss.len() as u64));
+    g.bench_function(BenchmarkId::new("table", "k=32"), |b| {
+        b.iter(|| {
+            let table = LagrangeTable::new(black_box(&ss)).unwrap();
+            ss.iter().map(|&i| table.get(i).unwrap()).sum::<Scalar>()
+        })
+    });
+    g.bench_function(BenchmarkId::new("per_call", "k=32"), |b| {
+        b.iter(|| ss.iter().map(|&i| lagrange_coeff(i, black_box(&ss)).unwrap()).sum::<Scalar>())
+    });
+    g.finish();
+}
+
+// 2048-bit N as in the harness; generating it dominates the suite's setup.
+fn timed_params() -> TimedParams {
+    setup_timed(2048, 12, &mut rand::rng()).expect("timed parameters").params
+}
+
+fn bench_timed(c: &mut Criterion) {
+    let timed = timed_params();
+    let mut g = c.benchmark_group("timed");
+    for (n, t) in SIZES {
+        let s = Session::new(n, t);
+        let cts: Vec<_> = s.partials.iter()
+            .map(|ps| timed_encrypt(&timed, &ps.z_i, b"timed-z").unwrap())
+            .collect();
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::new("encrypt", format!("n={n},t={t}")), |b| {
+            b.iter(|| {
+                for ps in &s.partials {
+                    timed_encrypt(&timed, &ps.z_i, b"timed-z").unwrap();
+                }
+            })
+        });
+        g.bench_function(BenchmarkId::new("decrypt", format!("n={n},t={t}")), |b| {
+            b.iter(|| {
+                for ct in &cts {
+                    timed_decrypt(&timed, ct, b"timed-z").unwrap();
+                }
+            })
+        });
+    }
+    g.finish();
+}
+
+fn bench_tracing(c: &mut Criterion) {
+    let admitter = setup_admitter();
+    let expiry = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let tok = admitter_issue_token(&admitter, MSG, expiry);
+    let mut g = c.benchmark_group("tracing");
+    for (n, t) in SIZES {
+        let s = Session::new(n, t);
+        let cts: Vec<_> = s.partials.iter().map(|ps| trace_encrypt(&tok, &ps.z_i, b"trace-z")).collect();
+        g.throughput(Throughput::Elements(s.ss.len() as u64));
+        g.bench_function(BenchmarkId::new("encrypt", format!("n={n},t={t}")), |b| {
+            b.iter(|| {
+                for ps in &s.partials {
+                    trace_encrypt(&tok, &ps.z_i, b"trace-z");
+                }
+            })
+        });
+        g.bench_function(BenchmarkId::new("decrypt", format!("n={n},t={t}")), |b| {
+            b.iter(|| {
+                for ct in &cts {
+                    trace_decrypt(&tok, ct, b"trace-z").unwrap();
+                }
+            })
+        });
+    }
+    g.finish();
+}
+
+criterion_group!(benches, bench_kgen, bench_rounds, bench_lagrange, bench_timed, bench_tracing);
+criterion_main!(benches);
@@ -0,0 +1,20 @@
+// Malformed nonce reveals must come back as a DecodeError (or a serde
+// error), never a panic, and anything accepted must re-encode to the same
+// bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use threshold_signature::types::NonceReveal;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(reveal) = NonceReveal::from_bytes(data) {
+        assert_eq!(reveal.to_bytes()[..], *data);
+        assert!(reveal.validate_encoding().is_ok());
+    }
+    if let Ok(reveal) = serde_json::from_slice::<NonceReveal>(data) {
+        if reveal.validate_encoding().is_ok() {
+            assert_eq!(NonceReveal::from_bytes(&reveal.to_bytes()).unwrap().to_bytes(), reveal.to_bytes());
+        }
+    }
+});
@@ -0,0 +1,19 @@
+// Malformed openings must come back as a DecodeError (or a serde error),
+// never a panic, and anything accepted must re-encode to the same bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use threshold_signature::types::OpeningMessage;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(om) = OpeningMessage::from_bytes(data) {
+        assert_eq!(om.to_bytes()[..], *data);
+        assert!(om.validate().is_ok());
+    }
+    if let Ok(om) = serde_json::from_slice::<OpeningMessage>(data) {
+        if om.validate_encoding().is_ok() {
+            assert_eq!(OpeningMessage::from_bytes(&om.to_bytes()).unwrap().to_bytes(), om.to_bytes());
+        }
+    }
+});
@@ -0,0 +1,273 @@
+// src/at_rest.rs
+//
+// Password-sealed secret key shares for at-rest storage (feature "at-rest").
+//
+// key   = Argon2id(password, salt) with the cost parameters stored alongside
+// box   = AES-256-GCM(key, nonce, s || r || u), AAD = "Gargos::SealedShare"
+//
+// The sealed form is serde-serializable and self-describing (salt, nonce and
+// KDF costs travel with the ciphertext), so no external KMS is needed.
+//
+// KeyPackage bundles a sealed share with the public material its signer
+// needs; generate_to_dir / load_package persist a whole key set as files:
+//
+// dir/verification_key.bin   VerificationKey::to_bytes
+// dir/signer_<i>.pkg         KeyPackage::to_bytes
+
+use std::fs;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Version};
+use rand::CryptoRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::Error;
+use crate::hash::{decode_point, enc_point};
+use crate::keygen::{kgen, setup};
+use crate::types::{PublicKeyShare, SecretKeyShare, VerificationKey};
+
+const AAD: &[u8] = b"Gargos::SealedShare";
+
+// Upper bounds on the Argon2id costs `unseal` accepts, so a crafted box
+// cannot make it allocate or spin without limit: 1 GiB, 16 passes, 16
+// lanes. The defaults `seal` uses are far below.
+const MAX_M_COST: u32 = 1 << 20;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SealedShare {
+    pub salt: [u8; 16],
+    pub nonce: [u8; 12],
+    // Argon2id costs: memory (KiB), iterations, lanes
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+    pub ct: Vec<u8>, // AES-GCM(s || r || u)
+}
+
+fn share_cipher(password: &str, salt: &[u8; 16], m_cost: u32, t_cost: u32, p_cost: u32) -> Result<Aes256Gcm, Error> {
+    let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|_| Error::MalformedEncoding)?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key[..])
+        .map_err(|_| Error::MalformedEncoding)?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..])))
+}
+
+impl SecretKeyShare {
+    /// Seal this share under `password` with the default Argon2id costs.
+    /// Fails with Error::SealFailed if key derivation or encryption does.
+    pub fn seal(&self, password: &str, rng: &mut impl CryptoRng) -> Result<SealedShare, Error> {
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 12];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let (m_cost, t_cost, p_cost) = (
+            argon2::Params::DEFAULT_M_COST,
+            argon2::Params::DEFAULT_T_COST,
+            argon2::Params::DEFAULT_P_COST,
+        );
+        let cipher = share_cipher(password, &salt, m_cost, t_cost, p_cost).map_err(|_| Error::SealFailed)?;
+
+        let pt = self.to_bytes();
+        let ct = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &pt[..], aad: AAD })
+            .map_err(|_| Error::SealFailed)?;
+
+        Ok(SealedShare { salt, nonce, m_cost, t_cost, p_cost, ct })
+    }
+}
+
+impl SealedShare {
+    /// Fails with Error::UnsealFailed on a wrong password or tampered box,
+    /// and with a decoding error if the stored costs or scalars are invalid
+    /// or the costs exceed the MAX_*_COST bounds.
+    pub fn unseal(&self, password: &str) -> Result<SecretKeyShare, Error> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(Error::MalformedEncoding);
+        }
+        let cipher = share_cipher(password, &self.salt, self.m_cost, self.t_cost, self.p_cost)?;
+        let pt = Zeroizing::new(
+            cipher
+                .decrypt(Nonce::from_slice(&self.nonce), Payload { msg: &self.ct, aad: AAD })
+                .map_err(|_| Error::UnsealFailed)?,
+        );
+        let bytes: &[u8; SecretKeyShare::ENCODED_LEN] =
+            pt[..].try_into().map_err(|_| Error::MalformedEncoding)?;
+        SecretKeyShare::from_bytes(bytes)
+    }
+}
+
+impl SealedShare {
+    /// salt || nonce || m_cost || t_cost || p_cost || len(ct) || ct, with
+    /// the integers as u32 little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + 12 + 16 + self.ct.len());
+        out.extend_from_slice(&self.salt);
+        out.extend_from_slice(&self.nonce);
+        for x in [self.m_cost, self.t_cost, self.p_cost, self.ct.len() as u32] {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        out.extend_from_slice(&self.ct);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<SealedShare, Error> {
+        let mut rest = bytes;
+        let share = read_sealed(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(Error::MalformedEncoding);
+        }
+        Ok(share)
+    }
+}
+
+/// One signer's persisted key material: its id, the verification key, every
+/// signer's pk_i (Sig3 checks the openings against them) and its own share,
+/// sealed under a password.
+#[derive(Clone, Debug)]
+pub struct KeyPackage {
+    pub i: u32,
+    pub vk: VerificationKey,
+    pub pk_shares: Vec<(u32, PublicKeyShare)>,
+    pub share: SealedShare,
+}
+
+impl KeyPackage {
+    /// i || VerificationKey || count || (id || pk_i)* || SealedShare, with
+    /// i, count and ids as u32 little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.i.to_le_bytes());
+        out.extend_from_slice(&self.vk.to_bytes());
+        out.extend_from_slice(&(self.pk_shares.len() as u32).to_le_bytes());
+        for (j, pk) in &self.pk_shares {
+            out.extend_from_slice(&j.to_le_bytes());
+            out.extend_from_slice(&enc_point(&pk.pk_i));
+        }
+        out.extend_from_slice(&self.share.to_bytes());
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<KeyPackage, Error> {
+        let mut rest = bytes;
+        let i = read_u32(&mut rest)?;
+        let vk = VerificationKey::from_bytes(take(&mut rest)?)?;
+        let count = read_u32(&mut rest)?;
+        let mut pk_shares = Vec::new();
+        for _ in 0..count {
+            let j = read_u32(&mut rest)?;
+            pk_shares.push((j, PublicKeyShare { pk_i: decode_point(take(&mut rest)?)? }));
+        }
+        let share = read_sealed(&mut rest)?;
+        if !rest.is_empty() {
+            return Err(Error::MalformedEncoding);
+        }
+        Ok(KeyPackage { i, vk, pk_shares, share })
+    }
+}
+
+// Split a fixed-size field off the front of `rest`.
+fn take<'a, const N: usize>(rest: &mut &'a [u8]) -> Result<&'a [u8; N], Error> {
+    if rest.len() < N {
+        return Err(Error::MalformedEncoding);
+    }
+    let (head, tail) = rest.split_at(N);
+    *rest = tail;
+    Ok(head.try_into().expect("split at N"))
+}
+
+fn read_u32(rest: &mut &[u8]) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(*take(rest)?))
+}
+
+fn read_sealed(rest: &mut &[u8]) -> Result<SealedShare, Error> {
+    let salt = *take(rest)?;
+    let nonce = *take(rest)?;
+    let m_cost = read_u32(rest)?;
+    let t_cost = read_u32(rest)?;
+    let p_cost = read_u32(rest)?;
+    let len = read_u32(rest)? as usize;
+    if rest.len() < len {
+        return Err(Error::MalformedEncoding);
+    }
+    let (ct, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(SealedShare { salt, nonce, m_cost, t_cost, p_cost, ct: ct.to_vec() })
+}
+
+/// Deal a fresh (n, t) key set and write it to `dir` (created if missing):
+/// the shared verification key and one package per signer, signer i's
+/// share sealed under `passwords[i - 1]`. Fails with
+/// Error::SignerCountMismatch unless there is exactly one password per
+/// signer. Returns the verification key.
+pub fn generate_to_dir(n: usize, t: usize, dir: &Path, passwords: &[&str]) -> Result<VerificationKey, Error> {
+    if passwords.len() != n {
+        return Err(Error::SignerCountMismatch { expected: n, got: passwords.len() });
+    }
+    let par = setup(n, t)?;
+    let (pk_joint, pks, sks, _) = kgen(&par)?;
+    let vk = VerificationKey { params_public: par, pk_joint };
+    let pk_shares: Vec<(u32, PublicKeyShare)> = (1..=n as u32).zip(pks).collect();
+
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join("verification_key.bin"), vk.to_bytes())?;
+
+    let mut rng = rand::rng();
+    for ((&(i, _), sk), password) in pk_shares.iter().zip(&sks).zip(passwords) {
+        let pkg = KeyPackage {
+            i,
+            vk: vk.clone(),
+            pk_shares: pk_shares.clone(),
+            share: sk.seal(password, &mut rng)?,
+        };
+        fs::write(dir.join(format!("signer_{}.pkg", i)), pkg.to_bytes())?;
+    }
+    Ok(vk)
+}
+
+/// Read a package written by `generate_to_dir` and unseal its share.
+pub fn load_package(path: &Path, password: &str) -> Result<(KeyPackage, SecretKeyShare), Error> {
+    let bytes = fs::read(path)?;
+    let pkg = KeyPackage::from_bytes(&bytes)?;
+    let sk = pkg.share.unseal(password)?;
+    Ok((pkg, sk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Session;
+
+    #[test]
+    fn sealed_share_round_trips() {
+        let sk = Session::new(3, 1).sk(1).clone();
+        let sealed = sk.seal("correct horse", &mut rand::rng()).unwrap();
+        let back = sealed.unseal("correct horse").unwrap();
+        assert_eq!((back.s, back.r, back.u), (sk.s, sk.r, sk.u));
+    }
+
+    #[test]
+    fn unseal_rejects_a_wrong_password_or_tampered_box() {
+        let sealed = Session::new(3, 1).sk(1).seal("correct horse", &mut rand::rng()).unwrap();
+        assert_eq!(sealed.unseal("battery staple").err(), Some(Error::UnsealFailed));
+
+        let mut tampered = sealed.clone();
+        tampered.ct[0] ^= 1;
+        assert_eq!(tampered.unseal("correct horse").err(), Some(Error::UnsealFailed));
+    }
+
+    #[test]
+    fn unseal_rejects_excessive_costs() {
+        let sealed = Session::new(3, 1).sk(1).seal("correct horse", &mut rand::rng()).unwrap();
+        for (m, t, p) in [(MAX_M_COST + 1, 1, 1), (1 << 10, MAX_T_COST + 1, 1), (1 << 10, 1, MAX_P_COST + 1)] {
+            let bad = SealedShare { m_cost: m, t_cost: t, p_cost: p, ..sealed.clone() };
+            assert_eq!(bad.unseal("correct horse").err(), Some(Error::MalformedEncoding));
+        }
+    }
+}
@@ -0,0 +1,240 @@
+// src/audit.rs
+//
+// Archivable signing transcripts.
+//
+// SignedSession captures everything a completed session put on the wire
+// (commitments, openings, final signature) plus a fingerprint of the
+// public parameters, so the session can be re-verified offline from the
+// archive and the public keys alone:
+// 1) params fingerprint matches
+// 2) openings cover exactly SS, each mu_j == Hcom(j, rho_j, B_j)
+// 3) every NIZK verifies under (G0, G1) derived from (m, mu_vec)
+// 4) A_hat recomputed from the openings equals the signature's A_hat
+// 5) the signature verifies under pk_joint
+
+use std::fmt;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::traits::Identity;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::error::Error;
+use crate::hash::{decode_point, decode_scalar, enc_point, enc_scalar, g0, g1, hcom};
+use crate::nizk::sig_verify;
+use crate::protocol::verify;
+use crate::shamir::LagrangeTable;
+use crate::types::{OpeningMessage, Params, Signature};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AuditError {
+    ParamsMismatch,
+    /// The openings do not cover exactly the signing set.
+    SigningSetMismatch,
+    MissingCommitment(u32),
+    CommitmentMismatch(u32),
+    MissingPublicKeyShare(u32),
+    PointDecodeFailed(u32),
+    ProofInvalid(u32),
+    /// A_hat recomputed from the openings differs from the signature's.
+    CombineMismatch,
+    MalformedSignature(Error),
+    InvalidSignature,
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::ParamsMismatch => write!(f, "parameters fingerprint mismatch"),
+            AuditError::SigningSetMismatch => write!(f, "openings do not match the signing set"),
+            AuditError::MissingCommitment(i) => write!(f, "no commitment from signer {}", i),
+            AuditError::CommitmentMismatch(i) => write!(f, "commitment mismatch for signer {}", i),
+            AuditError::MissingPublicKeyShare(i) => write!(f, "no public key share for signer {}", i),
+            AuditError::PointDecodeFailed(i) => write!(f, "undecodable point from signer {}", i),
+            AuditError::ProofInvalid(i) => write!(f, "invalid proof from signer {}", i),
+            AuditError::CombineMismatch => write!(f, "A_hat does not match the openings"),
+            AuditError::MalformedSignature(e) => write!(f, "malformed signature: {}", e),
+            AuditError::InvalidSignature => write!(f, "signature does not verify"),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedSession {
+    pub params_fingerprint: [u8; 32],
+    pub message: Vec<u8>,
+    pub ss: Vec<u32>,
+    pub commitments: Vec<(u32, [u8; 32])>,
+    pub openings: Vec<OpeningMessage>,
+    pub sig_a_hat: [u8; 32], // A_hat compressed
+    pub sig_z: [u8; 32],     // z scalar bytes
+}
+
+impl SignedSession {
+    pub fn new(
+        par: &Params,
+        message: &[u8],
+        ss: &[u32],
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+        sig: &Signature,
+    ) -> SignedSession {
+        SignedSession {
+            params_fingerprint: par.fingerprint(),
+            message: message.to_vec(),
+            ss: ss.to_vec(),
+            commitments: commitments.to_vec(),
+            openings: openings.to_vec(),
+            sig_a_hat: enc_point(&sig.A_hat),
+            sig_z: enc_scalar(&sig.z),
+        }
+    }
+
+    pub fn signature(&self) -> Result<Signature, Error> {
+        Ok(Signature { A_hat: decode_point(&self.sig_a_hat)?, z: decode_scalar(&self.sig_z)? })
+    }
+
+    /// Re-run the complete verification of the archived session.
+    pub fn verify(
+        &self,
+        par: &Params,
+        pk_joint: &RistrettoPoint,
+        pk_shares: &[(u32, RistrettoPoint)],
+    ) -> Result<(), AuditError> {
+        if self.params_fingerprint != par.fingerprint() {
+            return Err(AuditError::ParamsMismatch);
+        }
+        let sig = self.signature().map_err(AuditError::MalformedSignature)?;
+
+        let mut ss = self.ss.clone();
+        ss.sort_unstable();
+        let mut ids: Vec<u32> = self.openings.iter().map(|om| om.i).collect();
+        ids.sort_unstable();
+        if ss != ids {
+            return Err(AuditError::SigningSetMismatch);
+        }
+        let table = LagrangeTable::new(&self.ss).ok_or(AuditError::SigningSetMismatch)?;
+
+        let mut mu_vec = self.commitments.clone();
+        mu_vec.sort_by_key(|(i, _)| *i);
+        let g0p = g0(&self.message, &mu_vec);
+        let g1p = g1(&self.message, &mu_vec);
+
+        let mut a_hat = RistrettoPoint::identity();
+        for om in &self.openings {
+            let j = om.i;
+            let bj = decode_point(&om.b_point).map_err(|_| AuditError::PointDecodeFailed(j))?;
+            let aj = decode_point(&om.a_point).map_err(|_| AuditError::PointDecodeFailed(j))?;
+
+            let muj = mu_vec
+                .iter()
+                .find(|(id, _)| *id == j)
+                .map(|x| x.1)
+                .ok_or(AuditError::MissingCommitment(j))?;
+            if !bool::from(muj.ct_eq(&hcom(j, &om.rho_i, &bj))) {
+                return Err(AuditError::CommitmentMismatch(j));
+            }
+
+            let pkj = pk_shares
+                .iter()
+                .find(|(id, _)| *id == j)
+                .map(|x| x.1)
+                .ok_or(AuditError::MissingPublicKeyShare(j))?;
+            if !sig_verify(par, j, &pkj, &aj, &bj, &g0p, &g1p, &om.rho_i, &om.proof) {
+                return Err(AuditError::ProofInvalid(j));
+            }
+
+            a_hat += aj * table.get(j).ok_or(AuditError::SigningSetMismatch)?;
+        }
+
+        if a_hat != sig.A_hat {
+            return Err(AuditError::CombineMismatch);
+        }
+        if !verify(par, pk_joint, &self.ss, &self.message, &sig) {
+            return Err(AuditError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keygen::setup;
+    use crate::testutil::{Session, MSG};
+
+    fn archive(s: &Session) -> SignedSession {
+        SignedSession::new(&s.par, MSG, &s.ss, &s.commitments, &s.openings, &s.signature())
+    }
+
+    #[test]
+    fn archived_session_reverifies_after_a_json_round_trip() {
+        let s = Session::new(4, 2);
+        let json = serde_json::to_string(&archive(&s)).unwrap();
+        let back: SignedSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.verify(&s.par, &s.pk_joint, &s.pk_map), Ok(()));
+    }
+
+    #[test]
+    fn audit_names_what_was_tampered_with() {
+        let s = Session::new(4, 2);
+
+        assert_eq!(
+            archive(&s).verify(&setup(5, 2).unwrap(), &s.pk_joint, &s.pk_map),
+            Err(AuditError::ParamsMismatch)
+        );
+
+        let mut a = archive(&s);
+        a.openings.pop();
+        assert_eq!(a.verify(&s.par, &s.pk_joint, &s.pk_map), Err(AuditError::SigningSetMismatch));
+
+        let mut a = archive(&s);
+        a.openings[1].rho_i[0] ^= 1;
+        assert_eq!(a.verify(&s.par, &s.pk_joint, &s.pk_map), Err(AuditError::CommitmentMismatch(s.ss[1])));
+
+        let mut a = archive(&s);
+        a.openings[2].proof = s.openings[0].proof.clone();
+        assert_eq!(a.verify(&s.par, &s.pk_joint, &s.pk_map), Err(AuditError::ProofInvalid(s.ss[2])));
+
+        let mut a = archive(&s);
+        a.message = b"other".to_vec();
+        assert!(a.verify(&s.par, &s.pk_joint, &s.pk_map).is_err());
+
+        let pk_map: Vec<_> = s.pk_map.iter().copied().filter(|(j, _)| *j != s.ss[0]).collect();
+        assert_eq!(archive(&s).verify(&s.par, &s.pk_joint, &pk_map), Err(AuditError::MissingPublicKeyShare(s.ss[0])));
+    }
+
+    #[test]
+    fn archive_holds_no_secret_scalar() {
+        let s = Session::new(4, 2);
+        let json = serde_json::to_string(&archive(&s)).unwrap();
+        let mut secrets: Vec<[u8; 32]> = s.states.iter().map(|st| enc_scalar(&st.a_i)).collect();
+        for &i in &s.ss {
+            let sk = s.sk(i);
+            secrets.extend([enc_scalar(&sk.s), enc_scalar(&sk.r), enc_scalar(&sk.u)]);
+        }
+        for secret in secrets {
+            assert!(!json.contains(&serde_json::to_string(&secret).unwrap()));
+        }
+    }
+
+    #[test]
+    fn audit_rejects_a_signature_from_another_session() {
+        let mut s = Session::new(4, 2);
+        let mut a = archive(&s);
+        s.run(MSG);
+        let sig = s.signature();
+        a.sig_a_hat = enc_point(&sig.A_hat);
+        a.sig_z = enc_scalar(&sig.z);
+        assert_eq!(a.verify(&s.par, &s.pk_joint, &s.pk_map), Err(AuditError::CombineMismatch));
+
+        let mut a = archive(&s);
+        a.sig_z = [0xff; 32];
+        assert_eq!(
+            a.verify(&s.par, &s.pk_joint, &s.pk_map),
+            Err(AuditError::MalformedSignature(Error::NonCanonicalScalar))
+        );
+    }
+}
@@ -0,0 +1,322 @@
+// src/combiner.rs
+//
+// Designated-combiner workflow.
+//
+// - CombinerKey: keypair of the only party allowed to assemble signatures
+// - SealedPartial: a PartialSignature encrypted to the combiner (ECIES:
+//   ephemeral Ristretto DH -> HKDF-SHA256 -> AES-256-GCM)
+// - CombinerAuthorization: signer i's Schnorr proof of knowledge of its
+//   share opening (s, r, u) over pk_i, bound to (session_id, combiner_pk, m)
+//
+// combine_authorized only assembles a signature when every signer in SS
+// authorized this combiner for this session and message, and every share
+// opens under the combiner's secret key.
+
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::Error;
+use crate::hash::{decode_point, decode_scalar, enc_point, enc_scalar, hauth};
+use crate::protocol::{combine, one_per_signer};
+use crate::randutil::random_scalar;
+use crate::types::{OpeningMessage, Params, PartialSignature, SecretKeyShare, Signature};
+
+/// sk is wiped on drop and left out of Debug output.
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct CombinerKey {
+    pub sk: Scalar,
+    #[zeroize(skip)]
+    pub pk: RistrettoPoint,
+}
+
+impl fmt::Debug for CombinerKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CombinerKey")
+            .field("sk", &"<redacted>")
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct SealedPartial {
+    pub i: u32,
+    pub eph: [u8; 32],   // ephemeral public key, compressed
+    pub nonce: [u8; 12],
+    pub ct: Vec<u8>,     // AES-GCM(z_i || A_hat digest), AAD = session_id || i
+}
+
+/// Proof π := (R, zs, zr, zu) of knowledge of (s, r, u) with
+/// pk_i = g*s + h*r + v*u, challenge e = HAuth(R, pk_i, i, sid, pk_C, m).
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CombinerAuthorization {
+    pub i: u32,
+    pub r: [u8; 32],
+    pub zs: [u8; 32],
+    pub zr: [u8; 32],
+    pub zu: [u8; 32],
+}
+
+impl CombinerKey {
+    pub fn generate() -> CombinerKey {
+        let sk = random_scalar();
+        let pk = RistrettoPoint::mul_base(&sk);
+        CombinerKey { sk, pk }
+    }
+
+    /// Decrypt a sealed partial signature addressed to this combiner.
+    pub fn open_partial(&self, session_id: &[u8; 32], sealed: &SealedPartial) -> Option<PartialSignature> {
+        let eph = decode_point(&sealed.eph).ok()?;
+        let cipher = ecies_cipher(&(eph * self.sk), &sealed.eph);
+        let pt = cipher
+            .decrypt(
+                Nonce::from_slice(&sealed.nonce),
+                Payload { msg: &sealed.ct, aad: &sealed_aad(session_id, sealed.i) },
+            )
+            .ok()?;
+        if pt.len() != 64 {
+            return None;
+        }
+        let z_i: [u8; 32] = pt[..32].try_into().ok()?;
+        let a_hat_digest: [u8; 32] = pt[32..].try_into().ok()?;
+        Some(PartialSignature { i: sealed.i, z_i, a_hat_digest })
+    }
+}
+
+fn sealed_aad(session_id: &[u8; 32], i: u32) -> Vec<u8> {
+    let mut aad = session_id.to_vec();
+    aad.extend_from_slice(&i.to_le_bytes());
+    aad
+}
+
+fn ecies_cipher(shared: &RistrettoPoint, eph: &[u8; 32]) -> Aes256Gcm {
+    let hk = Hkdf::<Sha256>::new(Some(eph), &enc_point(shared));
+    let mut key = [0u8; 32];
+    hk.expand(b"Gargos::ECIES", &mut key).expect("32 bytes is a valid HKDF length");
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
+
+/// Encrypt a partial signature to the combiner's public key.
+pub fn seal_partial(combiner_pk: &RistrettoPoint, session_id: &[u8; 32], ps: &PartialSignature) -> SealedPartial {
+    let e = random_scalar();
+    let eph = enc_point(&RistrettoPoint::mul_base(&e));
+    let cipher = ecies_cipher(&(combiner_pk * e), &eph);
+
+    let nonce: [u8; 12] = rand::random();
+    let mut pt = [0u8; 64];
+    pt[..32].copy_from_slice(&ps.z_i);
+    pt[32..].copy_from_slice(&ps.a_hat_digest);
+    let ct = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload { msg: &pt, aad: &sealed_aad(session_id, ps.i) },
+        )
+        .expect("AES-GCM encryption of 64 bytes cannot fail");
+
+    SealedPartial { i: ps.i, eph, nonce, ct }
+}
+
+/// Signer i authorizes `combiner_pk` to assemble the signature on `message`
+/// in session `session_id`.
+pub fn authorize_combiner(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    sk_i: &SecretKeyShare,
+    combiner_pk: &RistrettoPoint,
+    session_id: &[u8; 32],
+    message: &[u8],
+) -> CombinerAuthorization {
+    let s_hat = random_scalar();
+    let r_hat = random_scalar();
+    let u_hat = random_scalar();
+    let r = par.mul_g(&s_hat) + par.h * r_hat + par.v * u_hat;
+
+    let e = hauth(&r, pk_i, i, session_id, combiner_pk, message);
+
+    CombinerAuthorization {
+        i,
+        r: enc_point(&r),
+        zs: enc_scalar(&(s_hat + sk_i.s * e)),
+        zr: enc_scalar(&(r_hat + sk_i.r * e)),
+        zu: enc_scalar(&(u_hat + sk_i.u * e)),
+    }
+}
+
+/// Check g^zs h^zr v^zu == R * pk_i^e.
+pub fn verify_authorization(
+    par: &Params,
+    pk_i: &RistrettoPoint,
+    auth: &CombinerAuthorization,
+    combiner_pk: &RistrettoPoint,
+    session_id: &[u8; 32],
+    message: &[u8],
+) -> bool {
+    let r = match decode_point(&auth.r) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+    let (zs, zr, zu) = match (decode_scalar(&auth.zs), decode_scalar(&auth.zr), decode_scalar(&auth.zu)) {
+        (Ok(zs), Ok(zr), Ok(zu)) => (zs, zr, zu),
+        _ => return false,
+    };
+
+    let e = hauth(&r, pk_i, auth.i, session_id, combiner_pk, message);
+    par.mul_g(&zs) + par.h * zr + par.v * zu == r + (*pk_i) * e
+}
+
+/// Combine, run by the designated combiner:
+/// - every i ∈ SS must present a valid authorization for (sid, pk_C, m)
+/// - there must be exactly one sealed share per i ∈ SS and none from
+///   outside SS (else Error::BadPartials names the offending ids)
+/// - every sealed share must open under the combiner's secret key
+#[allow(clippy::too_many_arguments)]
+pub fn combine_authorized(
+    ck: &CombinerKey,
+    par: &Params,
+    pk_shares: &[(u32, RistrettoPoint)],
+    session_id: &[u8; 32],
+    message: &[u8],
+    ss: &[u32],
+    openings: &[OpeningMessage],
+    sealed: &[SealedPartial],
+    auths: &[CombinerAuthorization],
+) -> Result<Signature, Error> {
+    for &i in ss {
+        let pk_i = pk_shares
+            .iter()
+            .find(|(id, _)| *id == i)
+            .map(|x| x.1)
+            .ok_or(Error::UnauthorizedCombiner(i))?;
+        let ok = auths
+            .iter()
+            .filter(|a| a.i == i)
+            .any(|a| verify_authorization(par, &pk_i, a, &ck.pk, session_id, message));
+        if !ok {
+            return Err(Error::UnauthorizedCombiner(i));
+        }
+    }
+
+    let sealed = one_per_signer(ss, sealed, |sp| sp.i).map_err(Error::BadPartials)?;
+    let mut sigshares = Vec::with_capacity(sealed.len());
+    for sp in sealed {
+        let ps = ck
+            .open_partial(session_id, sp)
+            .ok_or(Error::ShareDecryptionFailed(sp.i))?;
+        sigshares.push(ps);
+    }
+
+    combine(ss, openings, &sigshares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::verify;
+    use crate::testutil::{Session, MSG};
+
+    const SID: [u8; 32] = [7; 32];
+
+    fn authorize_all(s: &Session, ck: &CombinerKey, message: &[u8]) -> Vec<CombinerAuthorization> {
+        s.ss.iter()
+            .map(|&i| authorize_combiner(&s.par, i, &s.pk(i).pk_i, s.sk(i), &ck.pk, &SID, message))
+            .collect()
+    }
+
+    fn seal_all(s: &Session, ck: &CombinerKey) -> Vec<SealedPartial> {
+        s.partials.iter().map(|ps| seal_partial(&ck.pk, &SID, ps)).collect()
+    }
+
+    #[test]
+    fn combiner_key_is_wiped_on_drop_and_redacted() {
+        let ck = CombinerKey::generate();
+        assert_ne!(ck.sk, Scalar::ZERO);
+        assert!(!format!("{:?}", ck).contains(&format!("{:?}", ck.sk)));
+        // SAFETY: Scalar is a 32-byte array; the pointer comes from after_drop.
+        let left = crate::testutil::after_drop(ck, |p| unsafe {
+            core::ptr::read(core::ptr::addr_of!((*p).sk) as *const [u8; 32])
+        });
+        assert_eq!(left, [0u8; 32]);
+    }
+
+    #[test]
+    fn authorized_combiner_assembles_a_valid_signature() {
+        let s = Session::new(4, 2);
+        let ck = CombinerKey::generate();
+        let sig = combine_authorized(&ck, &s.par, &s.pk_map, &SID, MSG, &s.ss, &s.openings,
+                                     &seal_all(&s, &ck), &authorize_all(&s, &ck, MSG))
+            .unwrap();
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+    }
+
+    #[test]
+    fn missing_or_misbound_authorization_is_rejected() {
+        let s = Session::new(4, 2);
+        let ck = CombinerKey::generate();
+        let sealed = seal_all(&s, &ck);
+
+        let mut auths = authorize_all(&s, &ck, MSG);
+        auths.remove(1);
+        assert_eq!(
+            combine_authorized(&ck, &s.par, &s.pk_map, &SID, MSG, &s.ss, &s.openings, &sealed, &auths).err(),
+            Some(Error::UnauthorizedCombiner(s.ss[1]))
+        );
+
+        // Authorized for a different message.
+        let auths = authorize_all(&s, &ck, b"other");
+        assert_eq!(
+            combine_authorized(&ck, &s.par, &s.pk_map, &SID, MSG, &s.ss, &s.openings, &sealed, &auths).err(),
+            Some(Error::UnauthorizedCombiner(s.ss[0]))
+        );
+
+        // Authorized for a different combiner.
+        let other = CombinerKey::generate();
+        let auths = authorize_all(&s, &other, MSG);
+        assert!(!verify_authorization(&s.par, &s.pk(1).pk_i, &auths[0], &ck.pk, &SID, MSG));
+    }
+
+    #[test]
+    fn partials_sealed_to_another_combiner_do_not_open() {
+        let s = Session::new(4, 2);
+        let ck = CombinerKey::generate();
+        let other = CombinerKey::generate();
+        let sealed: Vec<_> = s.partials.iter().map(|ps| seal_partial(&other.pk, &SID, ps)).collect();
+        assert_eq!(
+            combine_authorized(&ck, &s.par, &s.pk_map, &SID, MSG, &s.ss, &s.openings, &sealed,
+                               &authorize_all(&s, &ck, MSG))
+                .err(),
+            Some(Error::ShareDecryptionFailed(s.ss[0]))
+        );
+        // A sealed partial is bound to its session id.
+        assert!(ck.open_partial(&[8; 32], &seal_partial(&ck.pk, &SID, &s.partials[0])).is_none());
+    }
+
+    #[test]
+    fn one_sealed_partial_per_signer_is_opened() {
+        let s = Session::new(4, 2);
+        let ck = CombinerKey::generate();
+        let auths = authorize_all(&s, &ck, MSG);
+        let combine = |sealed: &[SealedPartial]| {
+            combine_authorized(&ck, &s.par, &s.pk_map, &SID, MSG, &s.ss, &s.openings, sealed, &auths).err()
+        };
+
+        let mut sealed = seal_all(&s, &ck);
+        sealed.push(seal_partial(&ck.pk, &SID, &s.partials[1]));
+        assert_eq!(combine(&sealed), Some(Error::BadPartials(vec![s.ss[1]])));
+
+        let mut sealed = seal_all(&s, &ck);
+        sealed.push(seal_partial(&ck.pk, &SID, &PartialSignature { i: 4, ..s.partials[0].clone() }));
+        assert_eq!(combine(&sealed), Some(Error::BadPartials(vec![4])));
+
+        let mut sealed = seal_all(&s, &ck);
+        sealed.remove(2);
+        assert_eq!(combine(&sealed), Some(Error::BadPartials(vec![s.ss[2]])));
+    }
+}
@@ -8,12 +8,17 @@
 // - Combiner can aggregate commitments and (optionally) provide aggregate opening r = Σ r_i
 // - Anyone can verify aggregate correctness: C ?= g*z + h*r
 
+use alloc::vec::Vec;
+
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::Identity;
-use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+use crate::hash::{enc_point, hash_to_point, hash_to_scalar};
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct CommitmentMsg {
     pub i: u32,
     pub c_i: [u8; 32], // compressed RistrettoPoint
@@ -25,24 +30,18 @@ pub struct CommitmentOpening {
     pub r_i: Scalar,
 }
 
-/// Deterministically derive a secondary generator h from g (prototype-grade).
-/// This MUST be domain-separated from all other hashes in the system.
+/// Deterministically derive a secondary generator h from g:
+/// h = from_uniform_bytes(SHA-512("VC::derive_h_from_g" || enc(g))), the
+/// same domain-separated oracle hash.rs uses. h is a hash output, so no one
+/// knows log_g(h) and C = g*z + h*r is binding.
+///
+/// Test vector: for g = the Ristretto basepoint, enc(h) is
+/// 267f023d095c92a83706c0d89720520708cf7c9cd16d7a736abcf3454161e01b.
 pub fn derive_h_from_g(g: &RistrettoPoint) -> RistrettoPoint {
-    let mut h = Sha256::new();
-    h.update(b"VC::derive_h_from_g");
-    h.update(g.compress().as_bytes());
-    let digest = h.finalize();
-
-    // Map 32-byte digest to 64-byte "uniform" input for from_uniform_bytes.
-    let mut wide = [0u8; 64];
-    wide[..32].copy_from_slice(&digest);
-    // second half is another hash for cheap expansion
-    let digest2 = Sha256::digest(&digest);
-    wide[32..].copy_from_slice(&digest2);
-
-    RistrettoPoint::from_uniform_bytes(&wide)
+    hash_to_point(b"VC::derive_h_from_g", &enc_point(g))
 }
 
+#[cfg(feature = "std")]
 fn random_scalar() -> Scalar {
     // sample 64 bytes and reduce mod ℓ
     let buf: [u8; 64] = rand::random();
@@ -51,13 +50,26 @@ fn random_scalar() -> Scalar {
 
 /// Commit to share z_i: C_i = g*z_i + h*r_i.
 /// Returns (public message, local opening).
+#[cfg(feature = "std")]
 pub fn commit_z(
     i: u32,
     g: &RistrettoPoint,
     h: &RistrettoPoint,
     z_i: &Scalar,
 ) -> (CommitmentMsg, CommitmentOpening) {
-    let r_i = random_scalar();
+    commit_z_with_blinding(i, g, h, z_i, random_scalar())
+}
+
+/// `commit_z` with the blinding r_i supplied by the caller, so the same
+/// (z_i, r_i) always gives the same commitment (e.g. re-deriving it after
+/// a restart). r_i must still be secret and uniformly random.
+pub fn commit_z_with_blinding(
+    i: u32,
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    z_i: &Scalar,
+    r_i: Scalar,
+) -> (CommitmentMsg, CommitmentOpening) {
     let c_point = g * (*z_i) + h * r_i;
 
     (
@@ -69,18 +81,38 @@ pub fn commit_z(
     )
 }
 
-/// Aggregate public commitments: C = Σ C_i.
-pub fn aggregate_commitments(coms: &[CommitmentMsg]) -> [u8; 32] {
+/// Aggregate public commitments: C = Σ C_i. None if some C_i does not
+/// decode (commitments come from peers).
+pub fn aggregate_commitments(coms: &[CommitmentMsg]) -> Option<[u8; 32]> {
     let mut c_sum = RistrettoPoint::identity();
 
     for c in coms {
-        let cp = CompressedRistretto(c.c_i)
-            .decompress()
-            .expect("bad commitment point");
-        c_sum += cp;
+        c_sum += CompressedRistretto(c.c_i).decompress()?;
     }
 
-    c_sum.compress().to_bytes()
+    Some(c_sum.compress().to_bytes())
+}
+
+/// Re-randomize a commitment before forwarding (breaks linkability):
+/// C' = C + h*delta, which still commits to the same z_i under blinding
+/// r_i + delta. Returns (C', delta); the combiner adds delta to the
+/// aggregate opening so verify_aggregate still holds. None if C does not
+/// decode.
+pub fn rerandomize_commitment(
+    c: &CommitmentMsg,
+    h: &RistrettoPoint,
+    delta: Scalar,
+) -> Option<(CommitmentMsg, Scalar)> {
+    let cp = CompressedRistretto(c.c_i).decompress()?;
+    let c2 = cp + h * delta;
+
+    Some((
+        CommitmentMsg {
+            i: c.i,
+            c_i: c2.compress().to_bytes(),
+        },
+        delta,
+    ))
 }
 
 /// Aggregate openings (if the protocol reveals/provides them): r = Σ r_i.
@@ -95,7 +127,7 @@ pub fn aggregate_openings(ops: &[CommitmentOpening]) -> [u8; 32] {
 }
 
 /// Verify aggregate commitment against final z:
-/// check C == g*z + h*r.
+/// check C == g*z + h*r. False if C does not decode.
 pub fn verify_aggregate(
     g: &RistrettoPoint,
     h: &RistrettoPoint,
@@ -103,11 +135,212 @@ pub fn verify_aggregate(
     z: &Scalar,
     r_agg_bytes: &[u8; 32],
 ) -> bool {
-    let c_agg = CompressedRistretto(*c_agg_bytes)
-        .decompress()
-        .expect("bad agg commitment");
+    let Some(c_agg) = CompressedRistretto(*c_agg_bytes).decompress() else {
+        return false;
+    };
     let r_agg = Scalar::from_bytes_mod_order(*r_agg_bytes);
 
     let expected = g * (*z) + h * r_agg;
     c_agg == expected
 }
+
+/// `verify_aggregate` for many (C, z, r) at once: for random δ_k, check
+/// Σ δ_k C_k == g*(Σ δ_k z_k) + h*(Σ δ_k r_k) with one multi-exponentiation.
+/// Any inconsistent tuple makes this fail except with probability ~1/ℓ.
+/// Returns false if some C_k does not decode, or if the batch is empty.
+#[cfg(feature = "std")]
+pub fn verify_aggregate_batch(
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    batch: &[([u8; 32], Scalar, [u8; 32])],
+) -> bool {
+    if batch.is_empty() {
+        return false;
+    }
+    let mut scalars = Vec::with_capacity(batch.len() + 2);
+    let mut points = Vec::with_capacity(batch.len() + 2);
+    let (mut z_sum, mut r_sum) = (Scalar::ZERO, Scalar::ZERO);
+    for (c_agg, z, r_agg) in batch {
+        let Some(c) = CompressedRistretto(*c_agg).decompress() else {
+            return false;
+        };
+        let delta = random_scalar();
+        z_sum += delta * z;
+        r_sum += delta * Scalar::from_bytes_mod_order(*r_agg);
+        scalars.push(delta);
+        points.push(c);
+    }
+    scalars.extend([-z_sum, -r_sum]);
+    points.extend([*g, *h]);
+    RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity()
+}
+
+/// Proof that the committer knows r with C = g*z + h*r for a public z,
+/// without revealing r: a Schnorr proof for D = C - g*z = h*r.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct OpeningProof {
+    pub t: [u8; 32], // T = h*k
+    pub s: [u8; 32], // s = k + e*r
+}
+
+fn opening_challenge(
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    c: &[u8; 32],
+    z: &Scalar,
+    t: &[u8; 32],
+) -> Scalar {
+    let mut buf = Vec::with_capacity(5 * 32);
+    buf.extend_from_slice(&enc_point(g));
+    buf.extend_from_slice(&enc_point(h));
+    buf.extend_from_slice(c);
+    buf.extend_from_slice(z.as_bytes());
+    buf.extend_from_slice(t);
+    hash_to_scalar(b"VC::opening", &buf)
+}
+
+/// Prove knowledge of the opening r of C (compressed) to z.
+#[cfg(feature = "std")]
+pub fn prove_opening(
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    c: &[u8; 32],
+    z: &Scalar,
+    r: &Scalar,
+) -> OpeningProof {
+    let k = random_scalar();
+    let t = (h * k).compress().to_bytes();
+    let e = opening_challenge(g, h, c, z, &t);
+    OpeningProof { t, s: (k + e * r).to_bytes() }
+}
+
+/// Check h*s == T + (C - g*z)*e. False on undecodable points or a
+/// non-canonical s.
+pub fn verify_opening(
+    g: &RistrettoPoint,
+    h: &RistrettoPoint,
+    c: &[u8; 32],
+    z: &Scalar,
+    proof: &OpeningProof,
+) -> bool {
+    let (Some(c_point), Some(t_point)) = (
+        CompressedRistretto(*c).decompress(),
+        CompressedRistretto(proof.t).decompress(),
+    ) else {
+        return false;
+    };
+    let Some(s) = Option::<Scalar>::from(Scalar::from_canonical_bytes(proof.s)) else {
+        return false;
+    };
+    let e = opening_challenge(g, h, c, z, &proof.t);
+    h * s == t_point + (c_point - g * z) * e
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::traits::IsIdentity;
+
+    #[test]
+    fn derived_h_matches_the_test_vector_and_is_not_g() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let expected = "267f023d095c92a83706c0d89720520708cf7c9cd16d7a736abcf3454161e01b";
+        let hex: String = enc_point(&h).iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, expected);
+        assert_ne!(h, g);
+        assert!(!h.is_identity());
+
+        // A different g gives a different h, and h is not simply a multiple
+        // of g by a small known factor.
+        let g2 = g * Scalar::from(2u64);
+        assert_ne!(derive_h_from_g(&g2), h);
+        for k in 0..16u64 {
+            assert_ne!(h, g * Scalar::from(k));
+        }
+    }
+
+    #[test]
+    fn rerandomized_commitment_opens_to_the_same_z() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let z = random_scalar();
+        let (cm, op) = commit_z(1, &g, &h, &z);
+
+        let delta = random_scalar();
+        let (cm2, added) = rerandomize_commitment(&cm, &h, delta).unwrap();
+        assert_eq!(added, delta);
+        assert_eq!(cm2.i, cm.i);
+        assert_ne!(cm2.c_i, cm.c_i);
+        assert!(verify_aggregate(&g, &h, &cm2.c_i, &z, &(op.r_i + delta).to_bytes()));
+        assert!(!verify_aggregate(&g, &h, &cm2.c_i, &z, &op.r_i.to_bytes()));
+
+        // In an aggregate, the combiner adds delta to Σ r_i.
+        let z2 = random_scalar();
+        let (other, op2) = commit_z(2, &g, &h, &z2);
+        let c_agg = aggregate_commitments(&[cm2, other]).unwrap();
+        let r_agg = Scalar::from_bytes_mod_order(aggregate_openings(&[op, op2])) + delta;
+        assert!(verify_aggregate(&g, &h, &c_agg, &(z + z2), &r_agg.to_bytes()));
+    }
+
+    #[test]
+    fn undecodable_commitment_is_rejected() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let bad = CommitmentMsg { i: 1, c_i: [0xff; 32] };
+        assert!(rerandomize_commitment(&bad, &h, Scalar::ONE).is_none());
+        let (good, _) = commit_z(2, &g, &h, &Scalar::ONE);
+        assert!(aggregate_commitments(core::slice::from_ref(&good)).is_some());
+        assert_eq!(aggregate_commitments(&[good, bad.clone()]), None);
+        assert!(!verify_aggregate(&g, &h, &bad.c_i, &Scalar::ONE, &[0u8; 32]));
+    }
+
+    #[test]
+    fn batch_verify_accepts_valid_aggregates_and_rejects_a_mismatch() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let batch: Vec<([u8; 32], Scalar, [u8; 32])> = (1..=4)
+            .map(|i| {
+                let z = random_scalar();
+                let (cm, op) = commit_z(i, &g, &h, &z);
+                (cm.c_i, z, op.r_i.to_bytes())
+            })
+            .collect();
+        assert!(verify_aggregate_batch(&g, &h, &batch));
+
+        for k in 0..batch.len() {
+            let mut bad = batch.clone();
+            bad[k].1 += Scalar::ONE;
+            assert!(!verify_aggregate_batch(&g, &h, &bad));
+        }
+        assert!(!verify_aggregate_batch(&g, &h, &[]));
+    }
+
+    #[test]
+    fn same_blinding_gives_the_same_commitment() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let (z, r) = (random_scalar(), random_scalar());
+        let (cm, op) = commit_z_with_blinding(3, &g, &h, &z, r);
+        assert_eq!(commit_z_with_blinding(3, &g, &h, &z, r).0, cm);
+        assert_eq!(op.r_i, r);
+        assert!(verify_aggregate(&g, &h, &cm.c_i, &z, &r.to_bytes()));
+        assert_ne!(commit_z_with_blinding(3, &g, &h, &z, r + Scalar::ONE).0, cm);
+    }
+
+    #[test]
+    fn opening_proof_accepts_the_committed_z_only() {
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let h = derive_h_from_g(&g);
+        let z = random_scalar();
+        let (cm, op) = commit_z(1, &g, &h, &z);
+        let proof = prove_opening(&g, &h, &cm.c_i, &z, &op.r_i);
+        assert!(verify_opening(&g, &h, &cm.c_i, &z, &proof));
+
+        assert!(!verify_opening(&g, &h, &cm.c_i, &(z + Scalar::ONE), &proof));
+        // Nor can the prover make one for a z it did not commit to.
+        let wrong = prove_opening(&g, &h, &cm.c_i, &(z + Scalar::ONE), &op.r_i);
+        assert!(!verify_opening(&g, &h, &cm.c_i, &(z + Scalar::ONE), &wrong));
+    }
+}
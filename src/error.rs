@@ -0,0 +1,159 @@
+// src/error.rs
+//
+// Crate-wide error type for the fallible (Result-returning) APIs.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::keygen::SetupError;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Fewer than t+1 signers can exchange messages with each other.
+    QuorumUnavailable { available: usize, required: usize },
+    /// A signer rejected the round messages it received (Sig3 aborted).
+    /// Names the signer whose message was at fault when that is known
+    /// (see `SignError::signer`), otherwise the signer that aborted.
+    SignerAborted(u32),
+    /// Combine could not assemble a signature from the given shares.
+    CombineFailed,
+    /// The assembled signature does not verify under the joint key.
+    InvalidSignature,
+    /// 32 bytes that are not the canonical encoding of any Ristretto point
+    /// (field element >= p, top bit set, or negative).
+    NonCanonicalPoint,
+    /// A canonical field encoding that is not a valid Ristretto point.
+    PointDecodeFailed,
+    /// 32 bytes that do not encode a scalar reduced mod ℓ.
+    NonCanonicalScalar,
+    /// A structurally invalid encoding (bad length, out-of-range field).
+    MalformedEncoding,
+    /// Signer did not (validly) authorize this combiner for the session.
+    UnauthorizedCombiner(u32),
+    /// A sealed partial signature could not be opened by the combiner.
+    ShareDecryptionFailed(u32),
+    /// Signer id 0 (reserved for the secret) or a duplicate id.
+    InvalidSignerId(u32),
+    /// Number of signer ids (or per-signer inputs) differs from n.
+    SignerCountMismatch { expected: usize, got: usize },
+    /// kgen sampled s(x) with all higher coefficients zero (RNG failure).
+    DegeneratePolynomial,
+    /// A DKG dealer's commitments or share failed verification.
+    InvalidDealing(u32),
+    /// combine_resilient ran out of retries; lists the excluded signers.
+    RetriesExhausted { excluded: Vec<u32> },
+    /// Partial signatures from these signers failed verification.
+    BadPartials(Vec<u32>),
+    /// A sealed share did not open: wrong password or tampered ciphertext.
+    UnsealFailed,
+    /// A share could not be sealed: key derivation or encryption failed.
+    SealFailed,
+    /// pk_joint does not match the dealer's coefficient commitments.
+    InconsistentJointKey,
+    /// These signers derived their challenge from a different A_hat than
+    /// the combiner's (they saw a different set of openings).
+    ChallengeDivergence(Vec<u32>),
+    /// combine got a different number of signers, openings and partials.
+    InconsistentInputLengths { ss: usize, openings: usize, partials: usize },
+    /// Reading or writing key material failed.
+    Io(IoErrorKind),
+    /// Interpolation got fewer shares than the threshold requires.
+    NotEnoughShares { got: usize, required: usize },
+    /// `setup` rejected (n, t).
+    Setup(SetupError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::QuorumUnavailable { available, required } => write!(
+                f,
+                "quorum unavailable: {} reachable signers, {} required",
+                available, required
+            ),
+            Error::SignerAborted(i) => write!(f, "signer {} aborted the signing round", i),
+            Error::CombineFailed => write!(f, "failed to combine partial signatures"),
+            Error::InvalidSignature => write!(f, "combined signature does not verify"),
+            Error::NonCanonicalPoint => write!(f, "non-canonical point encoding"),
+            Error::PointDecodeFailed => write!(f, "point decompression failed"),
+            Error::NonCanonicalScalar => write!(f, "non-canonical scalar encoding"),
+            Error::MalformedEncoding => write!(f, "malformed encoding"),
+            Error::UnauthorizedCombiner(i) => {
+                write!(f, "signer {} did not authorize this combiner", i)
+            }
+            Error::InvalidSignerId(i) => write!(f, "invalid or duplicate signer id {}", i),
+            Error::SignerCountMismatch { expected, got } => {
+                write!(f, "expected {} signer ids, got {}", expected, got)
+            }
+            Error::DegeneratePolynomial => {
+                write!(f, "degenerate secret polynomial (RNG failure?)")
+            }
+            Error::InvalidDealing(k) => write!(f, "invalid DKG dealing from party {}", k),
+            Error::RetriesExhausted { excluded } => {
+                write!(f, "retries exhausted after excluding signers {:?}", excluded)
+            }
+            Error::BadPartials(ids) => write!(f, "bad partial signatures from signers {:?}", ids),
+            Error::UnsealFailed => write!(f, "sealed share did not open"),
+            Error::SealFailed => write!(f, "share could not be sealed"),
+            Error::InconsistentJointKey => write!(f, "joint key inconsistent with commitments"),
+            Error::ChallengeDivergence(ids) => {
+                write!(f, "signers {:?} signed under a different A_hat", ids)
+            }
+            Error::InconsistentInputLengths { ss, openings, partials } => write!(
+                f,
+                "{} signers but {} openings and {} partial signatures",
+                ss, openings, partials
+            ),
+            Error::Io(kind) => write!(f, "i/o error: {}", kind),
+            Error::NotEnoughShares { got, required } => {
+                write!(f, "{} shares given, {} required", got, required)
+            }
+            Error::ShareDecryptionFailed(i) => {
+                write!(f, "failed to open sealed partial signature of signer {}", i)
+            }
+            Error::Setup(e) => write!(f, "invalid parameters: {}", e),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+/// The cause of an `Error::Io`. `std::io::ErrorKind` does not exist
+/// without std, so the kinds key-file handling cares about are mirrored
+/// here and everything else lands in `Other`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IoErrorKind {
+    NotFound,
+    PermissionDenied,
+    AlreadyExists,
+    Other,
+}
+
+impl fmt::Display for IoErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoErrorKind::NotFound => write!(f, "entity not found"),
+            IoErrorKind::PermissionDenied => write!(f, "permission denied"),
+            IoErrorKind::AlreadyExists => write!(f, "entity already exists"),
+            IoErrorKind::Other => write!(f, "other error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(match e.kind() {
+            std::io::ErrorKind::NotFound => IoErrorKind::NotFound,
+            std::io::ErrorKind::PermissionDenied => IoErrorKind::PermissionDenied,
+            std::io::ErrorKind::AlreadyExists => IoErrorKind::AlreadyExists,
+            _ => IoErrorKind::Other,
+        })
+    }
+}
+
+impl From<SetupError> for Error {
+    fn from(e: SetupError) -> Self {
+        Error::Setup(e)
+    }
+}
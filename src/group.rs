@@ -1,5 +1,21 @@
+use alloc::vec::Vec;
+use core::fmt::Debug;
+
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE};
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, MultiscalarMul, VartimeMultiscalarMul};
+use rand::CryptoRng;
+use subtle::CtOption;
+
+use crate::hash::{enc_point, hash_to_point, hash_to_scalar};
+use crate::randutil::random_scalar_with_rng;
+
+// These wrappers are exact aliases for the dalek operators that the rest
+// of the crate builds on; they must not change semantics:
+//   point_add(a, b) == a + b,  point_sub(a, b) == a - b,
+//   point_mul(b, k) == b * k,  scalar_from_u64(x) == Scalar::from(x),
+// and in particular point_add(point_sub(a, b), b) == a.
 
 pub fn scalar_from_u64(x: u64) -> Scalar {
     Scalar::from(x)
@@ -16,3 +32,263 @@ pub fn point_add(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
 pub fn point_sub(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
     a - b
 }
+
+/// Decode a canonical scalar (< ℓ) without branching on the input: the
+/// range check is folded into the returned CtOption's choice, so callers
+/// on constant-time paths can combine validity flags before deciding.
+pub fn scalar_from_canonical_ct(bytes: &[u8; 32]) -> CtOption<Scalar> {
+    Scalar::from_canonical_bytes(*bytes)
+}
+
+/// A prime-order group with the operations the Gargos NIZK and the final
+/// Schnorr check need. Only those are generic: `nizk::sig_prove_in`,
+/// `nizk::sig_verify_in`, `protocol::verify_in` and the `hash::*_in`
+/// oracles. Params, key generation, the signing rounds and the wire types
+/// are Ristretto-only; `Ristretto` is the instance they use.
+///
+/// Hashes take a domain tag and data and must behave as random oracles into
+/// the group / scalar field. `encode_point` must be canonical, since its
+/// output is what gets hashed into challenges.
+pub trait Group {
+    type Point: Copy + PartialEq + Debug;
+    type Scalar: Copy + PartialEq + Debug;
+
+    fn basepoint() -> Self::Point;
+    fn identity() -> Self::Point;
+    fn add(a: &Self::Point, b: &Self::Point) -> Self::Point;
+    fn mul(p: &Self::Point, k: &Self::Scalar) -> Self::Point;
+
+    /// `basepoint() * k`. Groups with a precomputed table for their
+    /// standard generator override this; the default is a plain `mul`.
+    fn mul_basepoint(k: &Self::Scalar) -> Self::Point {
+        Self::mul(&Self::basepoint(), k)
+    }
+
+    fn scalar_add(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+    fn scalar_mul(a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+    fn scalar_neg(a: &Self::Scalar) -> Self::Scalar;
+    fn scalar_one() -> Self::Scalar;
+    fn random_scalar(rng: &mut impl CryptoRng) -> Self::Scalar;
+    /// Reduce 64 uniform bytes to a (near-)uniform scalar.
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> Self::Scalar;
+
+    fn encode_point(p: &Self::Point) -> Vec<u8>;
+    fn hash_to_point(domain: &[u8], data: &[u8]) -> Self::Point;
+    fn hash_to_scalar(domain: &[u8], data: &[u8]) -> Self::Scalar;
+
+    /// Σ k_i P_i, constant-time in the scalars. The default is the plain
+    /// sum of `mul`s; groups with a faster multi-exponentiation override it.
+    fn multiscalar_mul(scalars: &[Self::Scalar], points: &[Self::Point]) -> Self::Point {
+        scalars
+            .iter()
+            .zip(points)
+            .fold(Self::identity(), |acc, (k, p)| Self::add(&acc, &Self::mul(p, k)))
+    }
+
+    /// `multiscalar_mul` for public inputs only.
+    fn vartime_multiscalar_mul(scalars: &[Self::Scalar], points: &[Self::Point]) -> Self::Point {
+        Self::multiscalar_mul(scalars, points)
+    }
+}
+
+/// The Ristretto255 group of curve25519-dalek, with the crate's own
+/// hash-to-point/scalar oracles (domain-separated SHA-512).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ristretto;
+
+impl Group for Ristretto {
+    type Point = RistrettoPoint;
+    type Scalar = Scalar;
+
+    fn basepoint() -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn identity() -> RistrettoPoint {
+        RistrettoPoint::identity()
+    }
+
+    fn add(a: &RistrettoPoint, b: &RistrettoPoint) -> RistrettoPoint {
+        point_add(a, b)
+    }
+
+    fn mul(p: &RistrettoPoint, k: &Scalar) -> RistrettoPoint {
+        point_mul(p, k)
+    }
+
+    /// Uses dalek's static `RISTRETTO_BASEPOINT_TABLE` (radix-16 affine
+    /// Niels points, about 30 KB of read-only data shared by every caller),
+    /// several times faster than the generic ladder.
+    fn mul_basepoint(k: &Scalar) -> RistrettoPoint {
+        RISTRETTO_BASEPOINT_TABLE * k
+    }
+
+    fn scalar_add(a: &Scalar, b: &Scalar) -> Scalar {
+        a + b
+    }
+
+    fn scalar_mul(a: &Scalar, b: &Scalar) -> Scalar {
+        a * b
+    }
+
+    fn scalar_neg(a: &Scalar) -> Scalar {
+        -a
+    }
+
+    fn scalar_one() -> Scalar {
+        Scalar::ONE
+    }
+
+    fn random_scalar(rng: &mut impl CryptoRng) -> Scalar {
+        random_scalar_with_rng(rng)
+    }
+
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> Scalar {
+        Scalar::from_bytes_mod_order_wide(bytes)
+    }
+
+    fn encode_point(p: &RistrettoPoint) -> Vec<u8> {
+        enc_point(p).to_vec()
+    }
+
+    fn hash_to_point(domain: &[u8], data: &[u8]) -> RistrettoPoint {
+        hash_to_point(domain, data)
+    }
+
+    fn hash_to_scalar(domain: &[u8], data: &[u8]) -> Scalar {
+        hash_to_scalar(domain, data)
+    }
+
+    fn multiscalar_mul(scalars: &[Scalar], points: &[RistrettoPoint]) -> RistrettoPoint {
+        RistrettoPoint::multiscalar_mul(scalars, points)
+    }
+
+    fn vartime_multiscalar_mul(scalars: &[Scalar], points: &[RistrettoPoint]) -> RistrettoPoint {
+        RistrettoPoint::vartime_multiscalar_mul(scalars, points)
+    }
+}
+
+/// The three public generators (g, h, v) of `types::Params` in group G.
+#[derive(Clone, Copy, Debug)]
+pub struct Bases<G: Group> {
+    pub g: G::Point,
+    pub h: G::Point,
+    pub v: G::Point,
+}
+
+impl<G: Group> Bases<G> {
+    /// g * k, through `G::mul_basepoint` when g is the group's standard
+    /// generator (as `keygen::setup` picks it) and a plain `mul` otherwise.
+    /// Parameters with a custom g get no table: building one per Params
+    /// would cost as much memory as the static table and several dozen
+    /// multiplications up front, which only pays off for long-lived signers.
+    pub fn mul_g(&self, k: &G::Scalar) -> G::Point {
+        if self.g == G::basepoint() {
+            G::mul_basepoint(k)
+        } else {
+            G::mul(&self.g, k)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::randutil::random_scalar_with_rng;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::traits::Identity;
+
+    #[test]
+    fn wrappers_match_the_dalek_operators() {
+        use rand::RngCore;
+
+        let mut rng = rand::rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+        for _ in 0..64 {
+            let a = g * random_scalar_with_rng(&mut rng);
+            let b = g * random_scalar_with_rng(&mut rng);
+            let k = random_scalar_with_rng(&mut rng);
+            let x = rng.next_u64();
+
+            assert_eq!(point_add(&a, &b), a + b);
+            assert_eq!(point_sub(&a, &b), a - b);
+            assert_eq!(point_mul(&b, &k), b * k);
+            assert_eq!(scalar_from_u64(x), Scalar::from(x));
+            assert_eq!(point_add(&point_sub(&a, &b), &b), a);
+            assert_eq!(point_sub(&point_add(&a, &b), &b), a);
+        }
+        for x in [0, 1, u64::MAX] {
+            assert_eq!(scalar_from_u64(x), Scalar::from(x));
+        }
+        assert_eq!(point_mul(&g, &Scalar::ZERO), RistrettoPoint::identity());
+        assert_eq!(point_sub(&g, &g), RistrettoPoint::identity());
+    }
+
+    // ℓ = 2^252 + 27742317777372353535851937790883648493, little-endian.
+    const L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+    ];
+
+    #[test]
+    fn canonical_scalar_accepts_exactly_below_l() {
+        let mut l_minus_1 = L;
+        l_minus_1[0] -= 1;
+        assert_eq!(Option::from(scalar_from_canonical_ct(&l_minus_1)), Some(-Scalar::ONE));
+        assert_eq!(Option::from(scalar_from_canonical_ct(&[0u8; 32])), Some(Scalar::ZERO));
+
+        assert!(bool::from(scalar_from_canonical_ct(&L).is_none()));
+        let mut l_plus_1 = L;
+        l_plus_1[0] += 1;
+        assert!(bool::from(scalar_from_canonical_ct(&l_plus_1).is_none()));
+        assert!(bool::from(scalar_from_canonical_ct(&[0xff; 32]).is_none()));
+    }
+
+    #[test]
+    fn canonical_scalar_agrees_with_the_branching_check() {
+        use rand::RngCore;
+
+        // The branching reference: canonical iff reducing mod ℓ is a no-op.
+        let reference = |b: &[u8; 32]| {
+            let reduced = Scalar::from_bytes_mod_order(*b);
+            (reduced.to_bytes() == *b).then_some(reduced)
+        };
+        let mut rng = rand::rng();
+        let mut inputs: Vec<[u8; 32]> = (0..256)
+            .map(|k| {
+                let mut b = [0u8; 32];
+                rng.fill_bytes(&mut b);
+                // Half of them in [0, 2^253), where ℓ sits.
+                if k % 2 == 0 {
+                    b[31] &= 0x1f;
+                }
+                b
+            })
+            .collect();
+        for d in 0u8..4 {
+            let (mut below, mut above) = (L, L);
+            below[0] -= d;
+            above[0] += d;
+            inputs.extend([below, above]);
+        }
+        for b in &inputs {
+            assert_eq!(Option::from(scalar_from_canonical_ct(b)), reference(b));
+        }
+    }
+
+    #[test]
+    fn table_multiplication_matches_the_naive_one() {
+        let mut rng = rand::rng();
+        let g = RISTRETTO_BASEPOINT_POINT;
+        let custom = g * random_scalar_with_rng(&mut rng);
+        let standard = Bases::<Ristretto> { g, h: custom, v: custom };
+        let other = Bases::<Ristretto> { g: custom, h: g, v: g };
+        let mut ks: Vec<Scalar> = (0..32).map(|_| random_scalar_with_rng(&mut rng)).collect();
+        ks.extend([Scalar::ZERO, Scalar::ONE, -Scalar::ONE]);
+        for k in &ks {
+            assert_eq!(Ristretto::mul_basepoint(k), g * k);
+            assert_eq!(standard.mul_g(k), g * k);
+            assert_eq!(other.mul_g(k), custom * k);
+        }
+    }
+}
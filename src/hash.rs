@@ -1,45 +1,70 @@
+use alloc::vec::Vec;
+
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use sha2::{Digest, Sha512};
+use merlin::Transcript;
+use sha2::{Digest, Sha512, Sha512_256};
 
+use crate::error::Error;
+use crate::group::{scalar_from_canonical_ct, Group, Ristretto};
 use crate::randutil::{hash_to_point as uhash_to_point, hash_to_scalar as uhash_to_scalar};
 
-/// ===== Random Oracles (paper's Hall) =====
-/// Domain-separated SHA-512, then map to:
-/// - Points via RistrettoPoint::from_uniform_bytes (through randutil::hash_to_point)
-/// - Scalars via Scalar::from_bytes_mod_order_wide (through randutil::hash_to_scalar)
+// ===== Random Oracles (paper's Hall) =====
+// Domain-separated SHA-512, then map to:
+// - Points via RistrettoPoint::from_uniform_bytes (through randutil::hash_to_point)
+// - Scalars via Scalar::from_bytes_mod_order_wide (through randutil::hash_to_scalar)
+//
+// The two output sizes are distinct types. Digest32 is a commitment value
+// (Hcom's mu) and can only be read out as bytes; Digest64 is the only
+// input the point/scalar mappers accept. Digest32 uses SHA-512/256 (its own
+// IV), so it is not a prefix of the Digest64 for the same input.
 
-fn hash_32(domain: &[u8], data: &[u8]) -> [u8; 32] {
-    let mut h = Sha512::new();
+/// 32-byte oracle output: a binding commitment, not a uniform mapper input.
+struct Digest32([u8; 32]);
+
+/// 64-byte oracle output: uniform input for hash-to-point/scalar.
+struct Digest64([u8; 64]);
+
+impl Digest32 {
+    fn into_bytes(self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl Digest64 {
+    fn to_point(&self) -> RistrettoPoint {
+        uhash_to_point(&self.0)
+    }
+
+    fn to_scalar(&self) -> Scalar {
+        uhash_to_scalar(&self.0)
+    }
+}
+
+/// Hash(domain || data) -> 32 bytes (SHA-512/256)
+fn hash_32(domain: &[u8], data: &[u8]) -> Digest32 {
+    let mut h = Sha512_256::new();
     h.update(domain);
     h.update(data);
-    let out = h.finalize();
-    let mut r = [0u8; 32];
-    r.copy_from_slice(&out[..32]);
-    r
+    Digest32(h.finalize().into())
 }
 
 /// Hash(domain || data) -> 64 bytes (SHA-512)
-fn hash_64(domain: &[u8], data: &[u8]) -> [u8; 64] {
+fn hash_64(domain: &[u8], data: &[u8]) -> Digest64 {
     let mut h = Sha512::new();
     h.update(domain);
     h.update(data);
-    let out = h.finalize();
-    let mut r = [0u8; 64];
-    r.copy_from_slice(&out[..64]);
-    r
+    Digest64(h.finalize().into())
 }
 
 /// Domain-separated hash-to-point
-fn hash_to_point(domain: &[u8], data: &[u8]) -> RistrettoPoint {
-    let wide = hash_64(domain, data);
-    uhash_to_point(&wide)
+pub(crate) fn hash_to_point(domain: &[u8], data: &[u8]) -> RistrettoPoint {
+    hash_64(domain, data).to_point()
 }
 
 /// Domain-separated hash-to-scalar
-fn hash_to_scalar(domain: &[u8], data: &[u8]) -> Scalar {
-    let wide = hash_64(domain, data);
-    uhash_to_scalar(&wide)
+pub(crate) fn hash_to_scalar(domain: &[u8], data: &[u8]) -> Scalar {
+    hash_64(domain, data).to_scalar()
 }
 
 /// Serialize helper (compressed ristretto)
@@ -55,23 +80,88 @@ pub fn enc_scalar(s: &Scalar) -> [u8; 32] {
     s.to_bytes()
 }
 
+/// Inverse of `enc_point`; `None` for anything `decompress` rejects. Use
+/// `decode_point` to learn why.
+pub fn dec_point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+    CompressedRistretto(*bytes).decompress()
+}
+
+/// Inverse of `enc_scalar`, reducing mod ℓ: every 32-byte string decodes,
+/// and encodings that differ by a multiple of ℓ decode to the same scalar.
+pub fn dec_scalar(bytes: &[u8; 32]) -> Scalar {
+    Scalar::from_bytes_mod_order(*bytes)
+}
+
+/// Inverse of `enc_scalar` that only accepts its output: `None` unless
+/// `bytes` is already reduced mod ℓ.
+pub fn dec_scalar_canonical(bytes: &[u8; 32]) -> Option<Scalar> {
+    scalar_from_canonical_ct(bytes).into()
+}
+
+/// Ristretto only accepts the canonical encoding of a point: the field
+/// element s must be < p = 2^255 - 19 (so the top bit is clear) and
+/// non-negative (even).  `decompress` enforces this too, but folds it into
+/// the same `None` as "not on the curve"; this lets callers tell them apart.
+pub(crate) fn is_canonical_point_encoding(bytes: &[u8; 32]) -> bool {
+    if bytes[31] & 0x80 != 0 || bytes[0] & 1 != 0 {
+        return false;
+    }
+    // s >= p  <=>  s ∈ [2^255 - 19, 2^255 - 1]
+    let ge_p = bytes[31] == 0x7f && bytes[1..31].iter().all(|&b| b == 0xff) && bytes[0] >= 0xed;
+    !ge_p
+}
+
+/// Decode a compressed point, reporting why a rejected encoding failed.
+pub fn decode_point(bytes: &[u8; 32]) -> Result<RistrettoPoint, Error> {
+    if !is_canonical_point_encoding(bytes) {
+        return Err(Error::NonCanonicalPoint);
+    }
+    CompressedRistretto(*bytes)
+        .decompress()
+        .ok_or(Error::PointDecodeFailed)
+}
+
+/// Decode a scalar, rejecting encodings that are not reduced mod ℓ.
+pub fn decode_scalar(bytes: &[u8; 32]) -> Result<Scalar, Error> {
+    dec_scalar_canonical(bytes).ok_or(Error::NonCanonicalScalar)
+}
+
+/// Domain tag of Hcom. v1 was the first 32 bytes of SHA-512 under "Hcom";
+/// v2 is SHA-512/256, so the tag changes with the function and no v1 mu
+/// is mistaken for a v2 one.
+pub const HCOM_DOMAIN: &[u8] = b"Gargos::Hcom::v2";
+
+/// Domain tag of HAhat, versioned with Hcom for the same reason.
+pub const HAHAT_DOMAIN: &[u8] = b"Gargos::HAhat::v2";
+
 /// Hcom(i, rho, B) -> mu  (paper: Hcom : {0,1}^λ × G -> R)
+/// mu is a commitment to B; it is never mapped to a point or scalar.
 pub fn hcom(i: u32, rho: &[u8; 32], b: &RistrettoPoint) -> [u8; 32] {
     let mut buf = Vec::new();
     buf.extend_from_slice(b"Gargos::Hcom");
     buf.extend_from_slice(&i.to_le_bytes());
     buf.extend_from_slice(rho);
     buf.extend_from_slice(&enc_point(b));
-    hash_32(b"Hcom", &buf)
+    hash_32(HCOM_DOMAIN, &buf).into_bytes()
 }
 
 /// F0, F1 : {0,1}^λ -> G
 pub fn f0(rho: &[u8; 32]) -> RistrettoPoint {
-    hash_to_point(b"Gargos::F0", rho)
+    f0_in::<Ristretto>(rho)
 }
 
 pub fn f1(rho: &[u8; 32]) -> RistrettoPoint {
-    hash_to_point(b"Gargos::F1", rho)
+    f1_in::<Ristretto>(rho)
+}
+
+/// F0 in an arbitrary group.
+pub fn f0_in<G: Group>(rho: &[u8; 32]) -> G::Point {
+    G::hash_to_point(b"Gargos::F0", rho)
+}
+
+/// F1 in an arbitrary group.
+pub fn f1_in<G: Group>(rho: &[u8; 32]) -> G::Point {
+    G::hash_to_point(b"Gargos::F1", rho)
 }
 
 /// G0, G1 : M × R* -> G
@@ -96,16 +186,148 @@ pub fn g1(message: &[u8], mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
     hash_to_point(b"Gargos::G1", &buf)
 }
 
-/// Hsig : G^2 × M -> Zp (we use scalar)
-pub fn hsig(a_hat: &RistrettoPoint, pk: &RistrettoPoint, message: &[u8]) -> Scalar {
-    let mut buf = Vec::new();
-    buf.extend_from_slice(&enc_point(a_hat));
-    buf.extend_from_slice(&enc_point(pk));
+/// Domain tag of Hsig. v1 hashed (A_hat, pk, m); v2 hashed (m, A_hat, pk);
+/// v3 prefixes the signing set, so no earlier signature can be read as a
+/// v3 one.
+pub const HSIG_DOMAIN: &[u8] = b"Gargos::Hsig::v3";
+
+/// The signing set as Hsig absorbs it: |SS| (u32 LE), then the ids sorted
+/// ascending (u32 LE each), so the order SS is given in does not matter.
+pub fn encode_signing_set(ss: &[u32]) -> Vec<u8> {
+    let mut sorted = ss.to_vec();
+    sorted.sort_unstable();
+    let mut buf = Vec::with_capacity(4 + 4 * sorted.len());
+    buf.extend_from_slice(&(sorted.len() as u32).to_le_bytes());
+    for i in sorted {
+        buf.extend_from_slice(&i.to_le_bytes());
+    }
+    buf
+}
+
+/// Hsig : 2^SS × G^2 × M -> Zp (we use scalar)
+/// Input order is (SS, message, A_hat, pk). The signing set comes first
+/// and is length-prefixed, so a signature is bound to the exact quorum that
+/// produced it; the message follows so its hashing can be shared across
+/// challenges (see ChallengeHasher); the fixed-size point suffix keeps the
+/// encoding unambiguous.
+pub fn hsig(a_hat: &RistrettoPoint, pk: &RistrettoPoint, ss: &[u32], message: &[u8]) -> Scalar {
+    ChallengeHasher::for_message(ss, message).finalize(a_hat, pk)
+}
+
+/// Hsig in an arbitrary group; same transcript, so `hsig_in::<Ristretto>`
+/// equals `hsig`.
+pub fn hsig_in<G: Group>(a_hat: &G::Point, pk: &G::Point, ss: &[u32], message: &[u8]) -> G::Scalar {
+    let mut buf = encode_signing_set(ss);
     buf.extend_from_slice(message);
-    hash_to_scalar(b"Gargos::Hsig", &buf)
+    buf.extend_from_slice(&G::encode_point(a_hat));
+    buf.extend_from_slice(&G::encode_point(pk));
+    G::hash_to_scalar(HSIG_DOMAIN, &buf)
+}
+
+/// Hsig with the signing set and message absorbed once, for many
+/// (A_hat, pk) pairs. The message may also be fed in chunks with `update`
+/// before finalizing.
+#[derive(Clone)]
+pub struct ChallengeHasher {
+    state: Sha512,
+}
+
+impl ChallengeHasher {
+    /// Empty message so far, for signing set `ss`.
+    pub fn new(ss: &[u32]) -> ChallengeHasher {
+        let mut state = Sha512::new();
+        state.update(HSIG_DOMAIN);
+        state.update(encode_signing_set(ss));
+        ChallengeHasher { state }
+    }
+
+    pub fn for_message(ss: &[u32], message: &[u8]) -> ChallengeHasher {
+        let mut h = ChallengeHasher::new(ss);
+        h.update(message);
+        h
+    }
+
+    /// Append the next message chunk.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.state.update(chunk);
+    }
+
+    pub fn finalize(&self, a_hat: &RistrettoPoint, pk: &RistrettoPoint) -> Scalar {
+        let mut h = self.state.clone();
+        h.update(enc_point(a_hat));
+        h.update(enc_point(pk));
+        Digest64(h.finalize().into()).to_scalar()
+    }
+}
+
+/// G0, G1 and Hsig over a message fed in chunks, so a large message never
+/// has to be held in memory. Every chunk goes into all three transcripts as
+/// it arrives, and the results are bit-identical to `g0`, `g1` and `hsig`
+/// over the concatenated chunks. The signing set is fixed up front because
+/// Hsig absorbs it before the message.
+#[derive(Clone)]
+pub struct MessageHasher {
+    g0: Sha512,
+    g1: Sha512,
+    sig: ChallengeHasher,
+    ss: Vec<u32>,
+}
+
+impl MessageHasher {
+    /// Empty message so far, for signing set `ss`.
+    pub fn new(ss: &[u32]) -> MessageHasher {
+        let mut ss = ss.to_vec();
+        ss.sort_unstable();
+        MessageHasher {
+            g0: Sha512::new_with_prefix(b"Gargos::G0"),
+            g1: Sha512::new_with_prefix(b"Gargos::G1"),
+            sig: ChallengeHasher::new(&ss),
+            ss,
+        }
+    }
+
+    /// Append the next message chunk.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.g0.update(chunk);
+        self.g1.update(chunk);
+        self.sig.update(chunk);
+    }
+
+    /// The signing set, sorted.
+    pub fn signing_set(&self) -> &[u32] {
+        &self.ss
+    }
+
+    /// `g0` of the message so far.
+    pub fn g0(&self, mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
+        finalize_with_mu(self.g0.clone(), mu_vec)
+    }
+
+    /// `g1` of the message so far.
+    pub fn g1(&self, mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
+        finalize_with_mu(self.g1.clone(), mu_vec)
+    }
+
+    /// `hsig` of the message so far under this signing set.
+    pub fn hsig(&self, a_hat: &RistrettoPoint, pk: &RistrettoPoint) -> Scalar {
+        self.sig.finalize(a_hat, pk)
+    }
+}
+
+fn finalize_with_mu(mut h: Sha512, mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
+    for (id, mu) in mu_vec {
+        h.update(id.to_le_bytes());
+        h.update(mu);
+    }
+    Digest64(h.finalize().into()).to_point()
 }
 
 /// HFS for Fiat-Shamir in Σ-protocol (Fig.4, line 4).
+///
+/// This is v1, the original flat concatenation; it does not absorb the
+/// signer id. Current proofs use `hfs_v2`; proofs made under v1 verify only
+/// through `ProofVersion::V1`.
+#[allow(clippy::too_many_arguments)]
 pub fn hfs(
     xa: &RistrettoPoint,
     xb: &RistrettoPoint,
@@ -117,20 +339,354 @@ pub fn hfs(
     g1: &RistrettoPoint,
     rho: &[u8; 32],
 ) -> Scalar {
+    hfs_in::<Ristretto>(xa, xb, xpk, a, b, pk, g0, g1, rho)
+}
+
+/// HFS in an arbitrary group (points via `G::encode_point`).
+#[allow(clippy::too_many_arguments)]
+pub fn hfs_in<G: Group>(
+    xa: &G::Point,
+    xb: &G::Point,
+    xpk: &G::Point,
+    a: &G::Point,
+    b: &G::Point,
+    pk: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+) -> G::Scalar {
     let mut buf = Vec::new();
-    buf.extend_from_slice(&enc_point(xa));
-    buf.extend_from_slice(&enc_point(xb));
-    buf.extend_from_slice(&enc_point(xpk));
-    buf.extend_from_slice(&enc_point(a));
-    buf.extend_from_slice(&enc_point(b));
-    buf.extend_from_slice(&enc_point(pk));
-    buf.extend_from_slice(&enc_point(g0));
-    buf.extend_from_slice(&enc_point(g1));
+    for p in [xa, xb, xpk, a, b, pk, g0, g1] {
+        buf.extend_from_slice(&G::encode_point(p));
+    }
     buf.extend_from_slice(rho);
-    hash_to_scalar(b"Gargos::HFS", &buf)
+    G::hash_to_scalar(b"Gargos::HFS", &buf)
+}
+
+/// HFS, version 2: the same statement absorbed into a Merlin transcript
+/// ("Gargos::HFS::v2"), one labeled message per element, so fields cannot
+/// run into each other and new ones get their own label. It also absorbs
+/// the signer id i, so a proof is bound to its signer slot. `sig_prove` and
+/// `sig_verify` use this; `hfs` (v1) is kept for checking older proofs.
+#[allow(clippy::too_many_arguments)]
+pub fn hfs_v2(
+    i: u32,
+    xa: &RistrettoPoint,
+    xb: &RistrettoPoint,
+    xpk: &RistrettoPoint,
+    a: &RistrettoPoint,
+    b: &RistrettoPoint,
+    pk: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+) -> Scalar {
+    hfs_v2_in::<Ristretto>(i, xa, xb, xpk, a, b, pk, g0, g1, rho)
+}
+
+/// HFS v2 in an arbitrary group.
+#[allow(clippy::too_many_arguments)]
+pub fn hfs_v2_in<G: Group>(
+    i: u32,
+    xa: &G::Point,
+    xb: &G::Point,
+    xpk: &G::Point,
+    a: &G::Point,
+    b: &G::Point,
+    pk: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+) -> G::Scalar {
+    let mut t = Transcript::new(b"Gargos::HFS::v2");
+    t.append_u64(b"i", i as u64);
+    for (label, p) in [
+        (&b"XA"[..], xa),
+        (b"XB", xb),
+        (b"Xpk", xpk),
+        (b"A", a),
+        (b"B", b),
+        (b"pk", pk),
+        (b"g0", g0),
+        (b"g1", g1),
+    ] {
+        t.append_message(label, &G::encode_point(p));
+    }
+    t.append_message(b"rho", rho);
+    let mut wide = [0u8; 64];
+    t.challenge_bytes(b"e", &mut wide);
+    G::scalar_from_uniform_bytes(&wide)
+}
+
+/// HAuth for the combiner authorization (Schnorr proof of knowledge of the
+/// signer's share opening over pk_i, bound to the session and combiner).
+pub fn hauth(
+    r: &RistrettoPoint,
+    pk_i: &RistrettoPoint,
+    i: u32,
+    session_id: &[u8; 32],
+    combiner_pk: &RistrettoPoint,
+    message: &[u8],
+) -> Scalar {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&enc_point(r));
+    buf.extend_from_slice(&enc_point(pk_i));
+    buf.extend_from_slice(&i.to_le_bytes());
+    buf.extend_from_slice(session_id);
+    buf.extend_from_slice(&enc_point(combiner_pk));
+    buf.extend_from_slice(message);
+    hash_to_scalar(b"Gargos::HAuth", &buf)
+}
+
+/// HNonce for the nonce-reveal proof of signer i: R = g*a, S = g*s with
+/// A - R in <G0, G1> and pk - S in <h, v> under the same (r, u).
+#[allow(clippy::too_many_arguments)]
+pub fn hnonce(
+    i: u32,
+    r: &RistrettoPoint,
+    s: &RistrettoPoint,
+    a: &RistrettoPoint,
+    pk: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    xr: &RistrettoPoint,
+    xa: &RistrettoPoint,
+    xs: &RistrettoPoint,
+    xpk: &RistrettoPoint,
+) -> Scalar {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&i.to_le_bytes());
+    for p in [r, s, a, pk, g0, g1, xr, xa, xs, xpk] {
+        buf.extend_from_slice(&enc_point(p));
+    }
+    hash_to_scalar(b"Gargos::HNonce", &buf)
+}
+
+/// HAhat(A_hat): short tag of the A_hat a signer derived its challenge from,
+/// so the combiner can tell whether all partials share one challenge.
+pub fn hahat(a_hat: &RistrettoPoint) -> [u8; 32] {
+    hash_32(HAHAT_DOMAIN, &enc_point(a_hat)).into_bytes()
+}
+
+/// HAhatSession(SS, mu_vec, A_hat): binds an A_hat precomputed by a
+/// coordinator to the signing set and Sig1 commitments of its session.
+pub fn hahat_session(ss: &[u32], mu_vec: &[(u32, [u8; 32])], a_hat: &RistrettoPoint) -> [u8; 32] {
+    let mut buf = encode_signing_set(ss);
+    for (id, mu) in mu_vec {
+        buf.extend_from_slice(&id.to_le_bytes());
+        buf.extend_from_slice(mu);
+    }
+    buf.extend_from_slice(&enc_point(a_hat));
+    hash_32(b"Gargos::HAhatSession", &buf).into_bytes()
+}
+
+/// Hdkg for the DKG proof of knowledge of s_i(0) behind C_{i,0}.
+pub fn hdkg(i: u32, c0: &RistrettoPoint, r: &RistrettoPoint) -> Scalar {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&i.to_le_bytes());
+    buf.extend_from_slice(&enc_point(c0));
+    buf.extend_from_slice(&enc_point(r));
+    hash_to_scalar(b"Gargos::DKG-PoP", &buf)
+}
+
+/// Hjk for the proof of knowledge of s(0) behind pk_joint, bound to the
+/// share commitments [S, R, U] archived with it (each length-prefixed).
+pub fn hjoint(pk_joint: &RistrettoPoint, commitments: [&[RistrettoPoint]; 3], r: &RistrettoPoint) -> Scalar {
+    let mut buf = enc_point(pk_joint).to_vec();
+    for c in commitments {
+        buf.extend_from_slice(&(c.len() as u32).to_le_bytes());
+        for p in c {
+            buf.extend_from_slice(&enc_point(p));
+        }
+    }
+    buf.extend_from_slice(&enc_point(r));
+    hash_to_scalar(b"Gargos::JointKey-PoK", &buf)
+}
+
+/// Hctx: the additive key tweak for a signing context under pk_joint.
+pub fn hctx(pk_joint: &RistrettoPoint, context: &[u8]) -> Scalar {
+    let mut buf = enc_point(pk_joint).to_vec();
+    buf.extend_from_slice(context);
+    hash_to_scalar(b"Gargos::Context", &buf)
 }
 
 /// Deterministically derive "random generators" h, v (paper samples them randomly in Setup).
 pub fn derive_generator(tag: &'static [u8]) -> RistrettoPoint {
     hash_to_point(b"Gargos::Gen", tag)
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+
+    #[test]
+    fn decode_point_round_trips() {
+        let p = RISTRETTO_BASEPOINT_POINT * Scalar::from(7u64);
+        assert_eq!(decode_point(&enc_point(&p)), Ok(p));
+    }
+
+    #[test]
+    fn decode_point_names_non_canonical_encodings() {
+        let mut high_bit = enc_point(&RISTRETTO_BASEPOINT_POINT);
+        high_bit[31] |= 0x80;
+        assert_eq!(decode_point(&high_bit), Err(Error::NonCanonicalPoint));
+
+        let mut odd = [0u8; 32];
+        odd[0] = 1;
+        assert_eq!(decode_point(&odd), Err(Error::NonCanonicalPoint));
+
+        // p = 2^255 - 19 itself.
+        let mut p = [0xffu8; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        assert_eq!(decode_point(&p), Err(Error::NonCanonicalPoint));
+    }
+
+    #[test]
+    fn decode_point_separates_off_curve_from_non_canonical() {
+        // Canonical (even, < p) field elements that are not Ristretto points.
+        let mut found = 0;
+        for k in (2u8..=254).step_by(2) {
+            let mut b = [0u8; 32];
+            b[0] = k;
+            if CompressedRistretto(b).decompress().is_none() {
+                assert_eq!(decode_point(&b), Err(Error::PointDecodeFailed));
+                found += 1;
+            }
+        }
+        assert!(found > 0);
+    }
+
+    #[test]
+    fn decode_scalar_rejects_unreduced() {
+        let s = Scalar::from(42u64);
+        assert_eq!(decode_scalar(&enc_scalar(&s)), Ok(s));
+        assert_eq!(decode_scalar(&[0xff; 32]), Err(Error::NonCanonicalScalar));
+    }
+
+    #[test]
+    fn dec_helpers_invert_enc() {
+        let p = RISTRETTO_BASEPOINT_POINT * Scalar::from(9u64);
+        assert_eq!(dec_point(&enc_point(&p)), Some(p));
+        let mut odd = enc_point(&p);
+        odd[0] |= 1;
+        assert_eq!(dec_point(&odd), None);
+
+        let s = Scalar::from(1234u64);
+        assert_eq!(dec_scalar(&enc_scalar(&s)), s);
+        assert_eq!(dec_scalar_canonical(&enc_scalar(&s)), Some(s));
+    }
+
+    #[test]
+    fn dec_scalar_canonical_rejects_what_dec_scalar_reduces() {
+        // ℓ + 5 and 5 are the same scalar mod ℓ but distinct encodings.
+        let five = Scalar::from(5u64);
+        let l_plus_5 = crate::testutil::plus_ell(&enc_scalar(&five));
+        assert_eq!(dec_scalar(&l_plus_5), five);
+        assert_eq!(dec_scalar_canonical(&l_plus_5), None);
+        assert_eq!(dec_scalar_canonical(&[0xff; 32]), None);
+        // ℓ - 1 is the largest canonical encoding.
+        let minus_one = enc_scalar(&-Scalar::ONE);
+        assert_eq!(dec_scalar_canonical(&minus_one), Some(-Scalar::ONE));
+    }
+
+    #[test]
+    fn challenge_hasher_matches_hsig() {
+        let a_hat = RISTRETTO_BASEPOINT_POINT * Scalar::from(3u64);
+        let pk = RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64);
+        let ss = [1, 3];
+        let h = ChallengeHasher::for_message(&ss, b"hello world");
+        assert_eq!(h.finalize(&a_hat, &pk), hsig(&a_hat, &pk, &ss, b"hello world"));
+        // The absorbed state is reused, not consumed.
+        assert_eq!(h.finalize(&pk, &a_hat), hsig(&pk, &a_hat, &ss, b"hello world"));
+
+        let mut h = ChallengeHasher::new(&ss);
+        h.update(b"hello ");
+        h.update(b"world");
+        assert_eq!(h.finalize(&a_hat, &pk), hsig(&a_hat, &pk, &ss, b"hello world"));
+    }
+
+    #[test]
+    fn message_hasher_matches_the_one_shot_oracles() {
+        // 3 MiB fed in uneven chunks, so chunk and block boundaries differ.
+        let message: Vec<u8> = (0..3u32 << 20).map(|k| (k.wrapping_mul(2654435761) >> 24) as u8).collect();
+        let ss = [5, 2, 9];
+        let mu_vec = [(2, [1u8; 32]), (5, [2; 32]), (9, [3; 32])];
+        let a_hat = RISTRETTO_BASEPOINT_POINT * Scalar::from(3u64);
+        let pk = RISTRETTO_BASEPOINT_POINT * Scalar::from(5u64);
+
+        let mut h = MessageHasher::new(&ss);
+        for chunk in message.chunks(65_537) {
+            h.update(chunk);
+        }
+        assert_eq!(h.signing_set(), [2, 5, 9]);
+        assert_eq!(h.g0(&mu_vec), g0(&message, &mu_vec));
+        assert_eq!(h.g1(&mu_vec), g1(&message, &mu_vec));
+        assert_eq!(h.hsig(&a_hat, &pk), hsig(&a_hat, &pk, &ss, &message));
+        assert_ne!(h.g0(&mu_vec), h.g1(&mu_vec));
+
+        let mut short = MessageHasher::new(&ss);
+        short.update(&message[..message.len() - 1]);
+        assert_ne!(short.g0(&mu_vec), h.g0(&mu_vec));
+        assert_ne!(short.hsig(&a_hat, &pk), h.hsig(&a_hat, &pk));
+    }
+
+    #[test]
+    fn hsig_is_versioned() {
+        let a_hat = RISTRETTO_BASEPOINT_POINT;
+        let pk = RISTRETTO_BASEPOINT_POINT * Scalar::from(2u64);
+        let mut h = Sha512::new();
+        h.update(HSIG_DOMAIN);
+        h.update([2, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0]);
+        h.update(b"m");
+        h.update(enc_point(&a_hat));
+        h.update(enc_point(&pk));
+        let expected = Scalar::from_bytes_mod_order_wide(&h.finalize().into());
+        assert_eq!(hsig(&a_hat, &pk, &[4, 1], b"m"), expected);
+        assert_eq!(HSIG_DOMAIN, b"Gargos::Hsig::v3");
+    }
+
+    #[test]
+    fn hsig_binds_the_signing_set_but_not_its_order() {
+        let a_hat = RISTRETTO_BASEPOINT_POINT;
+        let pk = RISTRETTO_BASEPOINT_POINT * Scalar::from(2u64);
+        let c = hsig(&a_hat, &pk, &[1, 2, 3], b"m");
+        assert_eq!(hsig(&a_hat, &pk, &[3, 1, 2], b"m"), c);
+        assert_ne!(hsig(&a_hat, &pk, &[1, 2, 4], b"m"), c);
+        assert_ne!(hsig(&a_hat, &pk, &[1, 2], b"m"), c);
+        assert_eq!(hsig_in::<Ristretto>(&a_hat, &pk, &[2, 3, 1], b"m"), c);
+    }
+
+    #[test]
+    fn hcom_and_hahat_match_their_known_answers() {
+        fn hex(s: &str) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            for (o, i) in out.iter_mut().zip((0..s.len()).step_by(2)) {
+                *o = u8::from_str_radix(&s[i..i + 2], 16).unwrap();
+            }
+            out
+        }
+
+        // SHA-512/256(tag || input) with 3*B encoded as in RFC 9496.
+        let b = RISTRETTO_BASEPOINT_POINT * Scalar::from(3u64);
+        assert_eq!(enc_point(&b), hex("94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259"));
+        assert_eq!(
+            hcom(7, &[1u8; 32], &b),
+            hex("e8dbfe5b598a218f7c1bc019763113f09ce74206850b09525bc4f7d55ba6fc54")
+        );
+        assert_eq!(hahat(&b), hex("545bcb853247ac1d07be7846d0c6f4dada1199dc839825c67c795a48cb32db06"));
+        assert_eq!(HCOM_DOMAIN, b"Gargos::Hcom::v2");
+        assert_eq!(HAHAT_DOMAIN, b"Gargos::HAhat::v2");
+    }
+
+    #[test]
+    fn digest32_is_not_a_prefix_of_digest64() {
+        for (domain, data) in [(&b"Gargos::Hcom"[..], &b""[..]), (b"d", b"data"), (b"", &[0u8; 100][..])] {
+            let short = hash_32(domain, data).into_bytes();
+            let long = hash_64(domain, data).0;
+            assert_ne!(short[..], long[..32]);
+            assert_ne!(short[..], long[32..]);
+        }
+    }
+}
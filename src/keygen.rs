@@ -1,46 +1,723 @@
+use alloc::vec::Vec;
+
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::TryRngCore;
+use rand::CryptoRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::hash::{decode_point, decode_scalar, derive_generator, enc_point, enc_scalar, hctx, hjoint};
+use crate::error::Error;
+use crate::randutil::random_scalar_with_rng;
+use crate::shamir::{interpolate_points, invalid_id, sample_poly_with_rng, Poly};
+use crate::types::{share_for, Params, PublicKeyShare, SecretKeyShare, ShareCommitments};
+
+pub mod dkg;
+
+#[cfg(feature = "at-rest")]
+pub use crate::at_rest::{generate_to_dir, load_package};
+
+/// Why `setup` rejected (n, t).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SetupError {
+    /// n = 0: there is nobody to hold a share.
+    NoSigners,
+    /// t >= n: a quorum of t+1 signers never exists.
+    ThresholdTooLarge { n: usize, t: usize },
+}
+
+impl core::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SetupError::NoSigners => write!(f, "setup needs at least one signer"),
+            SetupError::ThresholdTooLarge { n, t } => write!(f, "threshold t={} must be < n={}", t, n),
+        }
+    }
+}
 
-use crate::hash::derive_generator;
-use crate::shamir::sample_poly_with_constant;
-use crate::types::{Params, PublicKeyShare, SecretKeyShare};
+impl core::error::Error for SetupError {}
 
 /// Setup(1^λ, n, t) (Fig.3 Setup).:contentReference[oaicite:9]{index=9}
-pub fn setup(n: usize, t: usize) -> Params {
+/// Any t+1 of the n signers can sign, so t must be < n (t = n-1 means all
+/// n signers are required). t = 0 is allowed: any single signer can sign.
+pub fn setup(n: usize, t: usize) -> Result<Params, SetupError> {
+    if n == 0 {
+        return Err(SetupError::NoSigners);
+    }
+    if t >= n {
+        return Err(SetupError::ThresholdTooLarge { n, t });
+    }
+
     // We use deterministic hash-derived generators to avoid "rng plumbing".
     // This plays the role of sampling independent random generators in the paper.
     let g = RISTRETTO_BASEPOINT_POINT;
     let h = derive_generator(b"h");
     let v = derive_generator(b"v");
-    Params { n, t, g, h, v }
+    Ok(Params { n, t, g, h, v })
 }
 
 /// KGen(par) (Fig.3 line 6-11).:contentReference[oaicite:10]{index=10}
-pub fn kgen(par: &Params) -> (RistrettoPoint, Vec<PublicKeyShare>, Vec<SecretKeyShare>) {
+/// Also returns the coefficient commitments so each recipient can audit its
+/// share with `verify_share`.
+/// Fails with Error::DegeneratePolynomial if every higher coefficient of
+/// s(x) is zero (see `check_nondegenerate`).
+#[cfg(feature = "std")]
+pub fn kgen(par: &Params) -> Result<KeyGenOutput, Error> {
+    kgen_with_rng(par, &mut OsRng.unwrap_err())
+}
+
+/// `kgen` drawing every coefficient from `rng`; with a seeded RNG the keys
+/// and commitments are reproducible.
+pub fn kgen_with_rng(par: &Params, rng: &mut impl CryptoRng) -> Result<KeyGenOutput, Error> {
+    let ids: Vec<u32> = (1..=par.n as u32).collect();
+    let (pk, pks, sks, commitments) = kgen_for_ids_with_rng(par, &ids, rng)?;
+    Ok((
+        pk,
+        pks.into_iter().map(|(_, pk_i)| pk_i).collect(),
+        sks.into_iter().map(|(_, sk_i)| sk_i).collect(),
+        commitments,
+    ))
+}
+
+/// KGen for arbitrary (sparse, non-contiguous) signer ids: share i is s(i),
+/// and shares are returned keyed by id (look them up with `share_for`).
+/// Ids must be nonzero (s(0) is the secret) and distinct, one per party.
+#[cfg(feature = "std")]
+pub fn kgen_for_ids(par: &Params, ids: &[u32]) -> Result<IdKeyGenOutput, Error> {
+    kgen_for_ids_with_rng(par, ids, &mut OsRng.unwrap_err())
+}
+
+/// `kgen_for_ids` drawing every coefficient from `rng`.
+pub fn kgen_for_ids_with_rng(
+    par: &Params,
+    ids: &[u32],
+    rng: &mut impl CryptoRng,
+) -> Result<IdKeyGenOutput, Error> {
+    deal(par, ids, rng).map(|(out, _)| out)
+}
+
+/// `kgen_for_ids` that also returns a `JointKeyProof` for the dealing. The
+/// dealer proves knowledge of s(0) while it still holds it, so nobody has
+/// to reassemble the secret from shares later.
+#[cfg(feature = "std")]
+pub fn kgen_for_ids_with_proof(par: &Params, ids: &[u32]) -> Result<(IdKeyGenOutput, JointKeyProof), Error> {
+    kgen_for_ids_with_proof_with_rng(par, ids, &mut OsRng.unwrap_err())
+}
+
+/// `kgen_for_ids_with_proof` drawing every coefficient and the proof nonce
+/// from `rng`; the keys match `kgen_for_ids_with_rng` on the same seed.
+pub fn kgen_for_ids_with_proof_with_rng(
+    par: &Params,
+    ids: &[u32],
+    rng: &mut impl CryptoRng,
+) -> Result<(IdKeyGenOutput, JointKeyProof), Error> {
+    let (out, s0) = deal(par, ids, rng)?;
+    let proof = prove_joint_key(par, &out.0, &out.3, &s0, rng);
+    Ok((out, proof))
+}
+
+// The dealing behind `kgen_for_ids_with_rng`, also handing back s(0).
+fn deal(
+    par: &Params,
+    ids: &[u32],
+    rng: &mut impl CryptoRng,
+) -> Result<(IdKeyGenOutput, Zeroizing<Scalar>), Error> {
+    if ids.len() != par.n {
+        return Err(Error::SignerCountMismatch { expected: par.n, got: ids.len() });
+    }
+    for (k, &i) in ids.iter().enumerate() {
+        if i == 0 || ids[..k].contains(&i) {
+            return Err(Error::InvalidSignerId(i));
+        }
+    }
+
     // sample degree-t polynomials s(x), r(x), u(x) with r(0)=u(0)=0.
-    let s0 = crate::randutil::random_scalar();
-    let s_poly = sample_poly_with_constant(par.t, s0);
-    let r_poly = sample_poly_with_constant(par.t, Scalar::ZERO);
-    let u_poly = sample_poly_with_constant(par.t, Scalar::ZERO);
+    let s0 = Zeroizing::new(random_scalar_with_rng(rng));
+    let s_poly = sample_poly_with_rng(par.t, *s0, rng);
+    check_nondegenerate(&s_poly)?;
+    let r_poly = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+    let u_poly = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
 
     let mut pks = Vec::with_capacity(par.n);
     let mut sks = Vec::with_capacity(par.n);
 
-    for i in 1..=par.n {
+    for &i in ids {
         let x = Scalar::from(i as u64);
         let s_i = s_poly.eval(x);
         let r_i = r_poly.eval(x);
         let u_i = u_poly.eval(x);
 
         let sk_i = SecretKeyShare { s: s_i, r: r_i, u: u_i };
-        let pk_i = par.g * s_i + par.h * r_i + par.v * u_i;
+        let pk_i = par.mul_g(&s_i) + par.h * r_i + par.v * u_i;
 
-        sks.push(sk_i);
-        pks.push(PublicKeyShare { pk_i });
+        sks.push((i, sk_i));
+        pks.push((i, PublicKeyShare { pk_i }));
     }
 
     // joint public key pk = g^{s(0)} (since r(0)=u(0)=0).:contentReference[oaicite:11]{index=11}
-    let pk = par.g * s0;
-    (pk, pks, sks)
+    let commitments = ShareCommitments {
+        s: s_poly.coeffs.iter().map(|c| par.mul_g(c)).collect(),
+        r: r_poly.coeffs.iter().map(|c| par.h * c).collect(),
+        u: u_poly.coeffs.iter().map(|c| par.v * c).collect(),
+    };
+
+    let pk = par.mul_g(&s0);
+    Ok(((pk, pks, sks, commitments), s0))
+}
+
+/// Proactive refresh (dealer-run): add shares of fresh degree-t polynomials
+/// δs(x), δr(x), δu(x) with δ(0) = 0 to every existing share, evaluated at
+/// the share's id (shares keyed by id, as returned by `kgen_for_ids`).
+/// s(0) and therefore pk_joint are unchanged, so old signatures stay valid
+/// and new ones verify under the same key, while every individual share is
+/// re-randomized and shares stolen before the refresh no longer combine
+/// with shares taken after it. Returns the new (public, secret) key shares,
+/// keyed by the same ids; an id that is 0 or repeated is rejected with
+/// Error::InvalidSignerId.
+#[cfg(feature = "std")]
+pub fn refresh(par: &Params, sk_shares: &[(u32, SecretKeyShare)]) -> Result<RefreshOutput, Error> {
+    refresh_with_rng(par, sk_shares, &mut OsRng.unwrap_err())
+}
+
+/// `refresh` drawing the δ polynomials from `rng`.
+pub fn refresh_with_rng(
+    par: &Params,
+    sk_shares: &[(u32, SecretKeyShare)],
+    rng: &mut impl CryptoRng,
+) -> Result<RefreshOutput, Error> {
+    let ids: Vec<u32> = sk_shares.iter().map(|(i, _)| *i).collect();
+    if let Some(j) = invalid_id(&ids) {
+        return Err(Error::InvalidSignerId(j));
+    }
+    let ds = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+    let dr = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+    let du = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+
+    let mut pks = Vec::with_capacity(sk_shares.len());
+    let mut sks = Vec::with_capacity(sk_shares.len());
+    for (i, old) in sk_shares {
+        let x = Scalar::from(*i as u64);
+        let sk_i = SecretKeyShare {
+            s: old.s + ds.eval(x),
+            r: old.r + dr.eval(x),
+            u: old.u + du.eval(x),
+        };
+        pks.push((*i, PublicKeyShare { pk_i: par.mul_g(&sk_i.s) + par.h * sk_i.r + par.v * sk_i.u }));
+        sks.push((*i, sk_i));
+    }
+    Ok((pks, sks))
+}
+
+/// Child key for a context: pk' = pk + g*tweak, with g = par.g.
+pub fn tweak_public_key(par: &Params, pk_joint: &RistrettoPoint, tweak: Scalar) -> RistrettoPoint {
+    pk_joint + par.mul_g(&tweak)
+}
+
+/// Share adjustment for a tweaked key: s_i' = s_i + tweak (r_i, u_i
+/// unchanged), i.e. the sharing polynomial becomes s(x) + tweak. Since the
+/// Lagrange coefficients of any signing set sum to 1, Σ L_i s_i' = s + tweak,
+/// so signers holding tweaked shares (and a coordinator using
+/// `tweak_public_key_share` / `tweak_public_key`) sign under pk' unchanged,
+/// with no extra keygen round. Every signer must apply the full tweak.
+pub fn tweak_secret_share(sk_i: &SecretKeyShare, tweak: Scalar) -> SecretKeyShare {
+    SecretKeyShare { s: sk_i.s + tweak, r: sk_i.r, u: sk_i.u }
+}
+
+/// pk_i' = pk_i + g*tweak, matching `tweak_secret_share`.
+pub fn tweak_public_key_share(par: &Params, pk_i: &PublicKeyShare, tweak: Scalar) -> PublicKeyShare {
+    PublicKeyShare { pk_i: tweak_public_key(par, &pk_i.pk_i, tweak) }
+}
+
+/// The tweak for a signing context: Hctx(pk_joint, context).
+pub fn context_tweak(pk_joint: &RistrettoPoint, context: &[u8]) -> Scalar {
+    hctx(pk_joint, context)
+}
+
+/// Per-context key from one keygen: (pk', sk_i') with the tweak
+/// `context_tweak(pk_joint, context)` applied by `tweak_public_key` and
+/// `tweak_secret_share`. Signers of the same context sign under pk' and
+/// their signatures do not verify under the root key or any other context.
+/// The coordinator tweaks the public key shares with
+/// `tweak_public_key_share` and the same tweak.
+///
+/// The tweak is public, so this is not hardened derivation in the BIP32
+/// sense: anyone who knows pk_joint and the context can link pk' to
+/// pk_joint, and sk_i' - tweak is the root share.
+pub fn derive_context_key(
+    par: &Params,
+    pk_joint: &RistrettoPoint,
+    sk_i: &SecretKeyShare,
+    context: &[u8],
+) -> (RistrettoPoint, SecretKeyShare) {
+    let tweak = context_tweak(pk_joint, context);
+    (tweak_public_key(par, pk_joint, tweak), tweak_secret_share(sk_i, tweak))
+}
+
+/// (pk_joint, public key shares, secret key shares, coefficient commitments)
+pub type KeyGenOutput = (RistrettoPoint, Vec<PublicKeyShare>, Vec<SecretKeyShare>, ShareCommitments);
+
+/// As KeyGenOutput, with every share keyed by its signer id.
+pub type IdKeyGenOutput = (
+    RistrettoPoint,
+    Vec<(u32, PublicKeyShare)>,
+    Vec<(u32, SecretKeyShare)>,
+    ShareCommitments,
+);
+
+/// (public key shares, secret key shares) after `refresh`, keyed by id.
+pub type RefreshOutput = (Vec<(u32, PublicKeyShare)>, Vec<(u32, SecretKeyShare)>);
+
+/// Σ_k C_k x^k
+pub(crate) fn eval_commitments(coeffs: &[RistrettoPoint], x: u32) -> RistrettoPoint {
+    let x = Scalar::from(x as u64);
+    let mut pow = Scalar::ONE;
+    let mut acc = RistrettoPoint::identity();
+    for c in coeffs {
+        acc += c * pow;
+        pow *= x;
+    }
+    acc
+}
+
+/// VSS check for signer i: g*s_i + h*r_i + v*u_i == Σ_k (S_k + R_k + U_k) i^k.
+///
+/// True means (s_i, r_i, u_i) are the values at i of the polynomials the
+/// dealer committed to; since nobody knows the discrete logs between g, h
+/// and v, a dealer cannot open one commitment to two different shares.
+/// It says nothing about pk_joint on its own: pair it with
+/// `check_joint_key`. Only the recipient can run it (it needs sk_i).
+pub fn verify_share(par: &Params, i: u32, sk_i: &SecretKeyShare, commitments: &ShareCommitments) -> bool {
+    let expected = eval_commitments(&commitments.s, i)
+        + eval_commitments(&commitments.r, i)
+        + eval_commitments(&commitments.u, i);
+    par.mul_g(&sk_i.s) + par.h * sk_i.r + par.v * sk_i.u == expected
+}
+
+/// Dealer-accountability checks on the public output of a dealing, for a
+/// relying party that holds pk_joint and the published commitments:
+/// 1) S, R, U each hold t+1 ≥ 1 coefficients
+/// 2) pk_joint == S_0, so pk_joint = g*s(0) interpolates from the shares
+/// 3) R_0 and U_0 are the identity (r(0) = u(0) = 0)
+/// 4) S_1..S_t are not all the identity (see `check_nondegenerate`)
+///
+/// Prime order of every point is guaranteed by Ristretto itself. This is a
+/// consistency check, not a proof that anyone knows s(0); together with
+/// `verify_share` on each recipient it shows the shares interpolate to
+/// pk_joint.
+pub fn check_joint_key(pk_joint: &RistrettoPoint, commitments: &ShareCommitments) -> bool {
+    let len = commitments.s.len();
+    len >= 1
+        && commitments.r.len() == len
+        && commitments.u.len() == len
+        && commitments.s[0] == *pk_joint
+        && commitments.r[0].is_identity()
+        && commitments.u[0].is_identity()
+        && (len == 1 || !commitments.s[1..].iter().all(|c| c.is_identity()))
+}
+
+/// Recompute the joint key from the public key shares of SS, for an
+/// auditor comparing it with an advertised pk_joint:
+///   Σ_{i∈SS} L_{i,SS} * pk_i = g*s(0) + h*r(0) + v*u(0) = g*s(0),
+/// since pk_i = g*s(i) + h*r(i) + v*u(i) and the dealer's r and u vanish
+/// at 0. The h and v terms cancel only for honestly dealt r, u of degree
+/// at most t, so a mismatch also flags a bad dealing.
+///
+/// SS needs t+1 or more distinct nonzero ids (Error::InvalidSignerId,
+/// Error::NotEnoughShares), each with a share in `pk_shares`
+/// (Error::NotEnoughShares counting those found otherwise).
+pub fn aggregate_pk(
+    par: &Params,
+    pk_shares: &[(u32, PublicKeyShare)],
+    ss: &[u32],
+) -> Result<RistrettoPoint, Error> {
+    if let Some(j) = invalid_id(ss) {
+        return Err(Error::InvalidSignerId(j));
+    }
+    if ss.len() < par.t + 1 {
+        return Err(Error::NotEnoughShares { got: ss.len(), required: par.t + 1 });
+    }
+    let points: Vec<(u32, RistrettoPoint)> =
+        ss.iter().filter_map(|&i| share_for(pk_shares, i).map(|pk| (i, pk.pk_i))).collect();
+    if points.len() < ss.len() {
+        return Err(Error::NotEnoughShares { got: points.len(), required: ss.len() });
+    }
+    Ok(interpolate_points(&points).expect("SS ids checked above"))
+}
+
+/// Portable proof that a dealt joint key is well formed, made by the
+/// dealer in `kgen_for_ids_with_proof`, for a relying party to archive:
+/// pk_joint, the commitments S, R, U it passes `check_joint_key` against,
+/// and a Schnorr proof of knowledge (R, z) of s(0) with pk_joint = g*s(0),
+/// whose challenge binds pk_joint and every commitment. `verify_joint_key`
+/// checks it against the pk_joint and commitments the verifier expects.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JointKeyProof {
+    pub pk_joint: [u8; 32],
+    pub s: Vec<[u8; 32]>,
+    pub r: Vec<[u8; 32]>,
+    pub u: Vec<[u8; 32]>,
+    pub pok_r: [u8; 32],
+    pub pok_z: [u8; 32],
+}
+
+impl JointKeyProof {
+    /// The archived commitments; fails if one does not decode.
+    pub fn commitments(&self) -> Result<ShareCommitments, Error> {
+        let decode = |v: &[[u8; 32]]| v.iter().map(decode_point).collect::<Result<Vec<_>, Error>>();
+        Ok(ShareCommitments { s: decode(&self.s)?, r: decode(&self.r)?, u: decode(&self.u)? })
+    }
+}
+
+// Schnorr PoK of s(0) for pk_joint = g*s(0):
+// R = g*k, e = Hjk(pk_joint, S, R, U, R), z = k + e*s(0)
+fn prove_joint_key(
+    par: &Params,
+    pk_joint: &RistrettoPoint,
+    commitments: &ShareCommitments,
+    s0: &Scalar,
+    rng: &mut impl CryptoRng,
+) -> JointKeyProof {
+    let k = Zeroizing::new(random_scalar_with_rng(rng));
+    let r_point = par.mul_g(&k);
+    let e = hjoint(pk_joint, [&commitments.s, &commitments.r, &commitments.u], &r_point);
+    let z = *k + e * s0;
+
+    JointKeyProof {
+        pk_joint: enc_point(pk_joint),
+        s: encode_points(&commitments.s),
+        r: encode_points(&commitments.r),
+        u: encode_points(&commitments.u),
+        pok_r: enc_point(&r_point),
+        pok_z: enc_scalar(&z),
+    }
+}
+
+fn encode_points(v: &[RistrettoPoint]) -> Vec<[u8; 32]> {
+    v.iter().map(enc_point).collect()
+}
+
+/// Check a `JointKeyProof` for the expected pk_joint and commitments: the
+/// proof archives exactly these, they pass `check_joint_key`, and (R, z)
+/// proves knowledge of s(0) for pk_joint = g*s(0) (g the basepoint, as in
+/// `setup`) under a challenge bound to exactly these commitments. A proof
+/// for any other key or dealing is rejected.
+pub fn verify_joint_key(proof: &JointKeyProof, pk_joint: &RistrettoPoint, commitments: &ShareCommitments) -> bool {
+    if proof.pk_joint != enc_point(pk_joint)
+        || proof.s != encode_points(&commitments.s)
+        || proof.r != encode_points(&commitments.r)
+        || proof.u != encode_points(&commitments.u)
+        || !check_joint_key(pk_joint, commitments)
+    {
+        return false;
+    }
+    let (Ok(r_point), Ok(z)) = (decode_point(&proof.pok_r), decode_scalar(&proof.pok_z)) else {
+        return false;
+    };
+    let e = hjoint(pk_joint, [&commitments.s, &commitments.r, &commitments.u], &r_point);
+    RistrettoPoint::mul_base(&z) == r_point + pk_joint * e
+}
+
+/// Sanity check against a catastrophic RNG failure: if s(x) has no nonzero
+/// coefficient above the constant, every share equals s(0) and a single
+/// share reveals the key. (For t = 0 there are no higher coefficients.)
+fn check_nondegenerate(s_poly: &Poly) -> Result<(), Error> {
+    if s_poly.coeffs.len() > 1 && s_poly.coeffs[1..].iter().all(|c| *c == Scalar::ZERO) {
+        return Err(Error::DegeneratePolynomial);
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    /// A "broken" RNG that only ever produces zero bytes.
+    struct ZeroRng;
+
+    impl RngCore for ZeroRng {
+        fn next_u32(&mut self) -> u32 {
+            0
+        }
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(0);
+        }
+    }
+
+    impl CryptoRng for ZeroRng {}
+
+    #[test]
+    fn setup_checks_n_and_t() {
+        for n in [1, 2, 5] {
+            let par = setup(n, n - 1).unwrap();
+            assert_eq!((par.n, par.t), (n, n - 1));
+            assert_eq!(setup(n, n).err(), Some(SetupError::ThresholdTooLarge { n, t: n }));
+        }
+        assert_eq!(setup(4, 10).err(), Some(SetupError::ThresholdTooLarge { n: 4, t: 10 }));
+        assert_eq!(setup(0, 0).err(), Some(SetupError::NoSigners));
+        assert!(setup(3, 0).is_ok());
+        assert_eq!(Error::from(SetupError::NoSigners), Error::Setup(SetupError::NoSigners));
+    }
+
+    #[test]
+    fn all_zero_rng_is_caught() {
+        let par = setup(3, 1).unwrap();
+        assert_eq!(kgen_with_rng(&par, &mut ZeroRng).err(), Some(Error::DegeneratePolynomial));
+        assert_eq!(kgen_for_ids_with_rng(&par, &[2, 4, 6], &mut ZeroRng).err(), Some(Error::DegeneratePolynomial));
+
+        // t = 0 has no higher coefficients to check.
+        let par0 = setup(1, 0).unwrap();
+        assert!(kgen_with_rng(&par0, &mut ZeroRng).is_ok());
+        assert!(kgen(&par).is_ok());
+    }
+
+    #[test]
+    fn seeded_rng_reproduces_keys_and_commitments() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        use crate::hash::enc_point;
+        use crate::protocol::sig1_with_rng;
+
+        // Everything a run publishes or keeps, as bytes.
+        let run = |seed: u64| {
+            let mut rng = ChaCha20Rng::seed_from_u64(seed);
+            let par = setup(4, 2).unwrap();
+            let (pk, pks, sks, commitments) = kgen_with_rng(&par, &mut rng).unwrap();
+            let mut out = vec![enc_point(&pk).to_vec()];
+            out.extend(pks.iter().map(|pk_i| enc_point(&pk_i.pk_i).to_vec()));
+            out.extend(sks.iter().map(|sk_i| sk_i.to_bytes().to_vec()));
+            for c in [&commitments.s, &commitments.r, &commitments.u] {
+                out.extend(c.iter().map(|p| enc_point(p).to_vec()));
+            }
+            let (cm, st) = sig1_with_rng(&par, 1, &sks[0], &mut rng);
+            out.extend([cm.mu_i.to_vec(), st.rho_i.to_vec(), st.a_i.to_bytes().to_vec()]);
+            let keyed: Vec<(u32, SecretKeyShare)> = (1..).zip(sks).collect();
+            let (new_pks, new_sks) = refresh_with_rng(&par, &keyed, &mut rng).unwrap();
+            out.extend(new_pks.iter().map(|(_, pk_i)| enc_point(&pk_i.pk_i).to_vec()));
+            out.extend(new_sks.iter().map(|(_, sk_i)| sk_i.to_bytes().to_vec()));
+            let (dealing, shares) = dkg::round1_with_rng(&par, 1, &mut rng);
+            out.extend(dealing.coeffs.iter().map(|c| c.to_vec()));
+            out.extend([dealing.pop_r.to_vec(), dealing.pop_z.to_vec()]);
+            out.extend(shares.iter().map(|sh| [sh.s.to_bytes(), sh.r.to_bytes(), sh.u.to_bytes()].concat()));
+            out
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn dealt_shares_verify_against_commitments() {
+        let par = setup(4, 2).unwrap();
+        let (_, _, sks, commitments) = kgen(&par).unwrap();
+        for (k, sk) in sks.iter().enumerate() {
+            assert!(verify_share(&par, k as u32 + 1, sk, &commitments));
+        }
+        assert!(!verify_share(&par, 2, &sks[0], &commitments));
+
+        let tampered = SecretKeyShare { s: sks[0].s + Scalar::ONE, r: sks[0].r, u: sks[0].u };
+        assert!(!verify_share(&par, 1, &tampered, &commitments));
+        let tampered = SecretKeyShare { s: sks[0].s, r: sks[0].r + Scalar::ONE, u: sks[0].u };
+        assert!(!verify_share(&par, 1, &tampered, &commitments));
+    }
+
+    #[test]
+    fn joint_key_must_match_the_commitments() {
+        let par = setup(4, 2).unwrap();
+        let (pk_joint, _, _, commitments) = kgen(&par).unwrap();
+        assert!(check_joint_key(&pk_joint, &commitments));
+        assert!(!check_joint_key(&(pk_joint + par.g), &commitments));
+
+        let mut bad = commitments.clone();
+        bad.r[0] += par.h;
+        assert!(!check_joint_key(&pk_joint, &bad));
+
+        let mut bad = commitments.clone();
+        bad.u.pop();
+        assert!(!check_joint_key(&pk_joint, &bad));
+    }
+
+    #[test]
+    fn joint_key_proof_round_trips_and_verifies() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let par = setup(4, 2).unwrap();
+        let ((pk_joint, _, _, commitments), proof) = kgen_for_ids_with_proof(&par, &[1, 2, 3, 4]).unwrap();
+        assert!(verify_joint_key(&proof, &pk_joint, &commitments));
+
+        // The archived proof re-verifies.
+        let back: JointKeyProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+        assert_eq!(back.commitments().unwrap().s, commitments.s);
+        assert!(verify_joint_key(&back, &pk_joint, &commitments));
+
+        // The dealing is the one kgen_for_ids_with_rng makes from the same seed.
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let ((pk, _, sks, _), _) = kgen_for_ids_with_proof_with_rng(&par, &[1, 2, 3, 4], &mut rng).unwrap();
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let (pk2, _, sks2, _) = kgen_for_ids_with_rng(&par, &[1, 2, 3, 4], &mut rng).unwrap();
+        assert_eq!(pk, pk2);
+        assert_eq!(sks[0].1.s, sks2[0].1.s);
+    }
+
+    #[test]
+    fn joint_key_proof_rejects_another_key() {
+        let par = setup(4, 2).unwrap();
+        let ((pk_joint, _, _, commitments), proof) = kgen_for_ids_with_proof(&par, &[1, 2, 3, 4]).unwrap();
+        let ((pk2, _, _, commitments2), other) = kgen_for_ids_with_proof(&par, &[1, 2, 3, 4]).unwrap();
+        assert!(verify_joint_key(&other, &pk2, &commitments2));
+
+        // A valid proof of another dealing does not vouch for this key, and
+        // this proof does not vouch for another key or commitments.
+        assert!(!verify_joint_key(&other, &pk_joint, &commitments));
+        assert!(!verify_joint_key(&proof, &pk2, &commitments2));
+        assert!(!verify_joint_key(&proof, &pk_joint, &commitments2));
+        assert!(!verify_joint_key(&proof, &(pk_joint + par.g), &commitments));
+    }
+
+    #[test]
+    fn joint_key_proof_rejects_a_tampered_proof() {
+        let par = setup(4, 2).unwrap();
+        let ((pk_joint, _, _, commitments), proof) = kgen_for_ids_with_proof(&par, &[1, 2, 3, 4]).unwrap();
+        let ((pk2, _, _, commitments2), other) = kgen_for_ids_with_proof(&par, &[1, 2, 3, 4]).unwrap();
+
+        // The PoK covers exactly these commitments: splicing in parts of
+        // another dealing with the same key shape does not verify.
+        let mut spliced = other.clone();
+        spliced.r = proof.r.clone();
+        assert!(!verify_joint_key(&spliced, &pk2, &commitments2));
+        let mut spliced = proof.clone();
+        (spliced.pok_r, spliced.pok_z) = (other.pok_r, other.pok_z);
+        assert!(!verify_joint_key(&spliced, &pk_joint, &commitments));
+
+        let mut bad = proof.clone();
+        bad.pok_z = enc_scalar(&(decode_scalar(&proof.pok_z).unwrap() + Scalar::ONE));
+        assert!(!verify_joint_key(&bad, &pk_joint, &commitments));
+
+        let mut bad = proof.clone();
+        bad.s[1] = [0xff; 32];
+        assert!(bad.commitments().is_err());
+        assert!(!verify_joint_key(&bad, &pk_joint, &commitments));
+    }
+
+    #[test]
+    fn refresh_keeps_the_key_and_rerandomizes_shares() {
+        use crate::protocol::{sign, verify};
+        use crate::shamir::lagrange_coeff;
+        use crate::types::share_for;
+
+        let par = setup(4, 2).unwrap();
+        let ids = [3, 17, 42, 99];
+        let (pk_joint, pks, sks, _) = kgen_for_ids(&par, &ids).unwrap();
+        let ss = [3, 42, 99];
+
+        let before = sign(&par, &sks, &pks, &pk_joint, &ss, b"m").unwrap();
+        let (new_pks, new_sks) = refresh(&par, &sks).unwrap();
+        let after = sign(&par, &new_sks, &new_pks, &pk_joint, &ss, b"m").unwrap();
+        assert!(verify(&par, &pk_joint, &ss, b"m", &before));
+        assert!(verify(&par, &pk_joint, &ss, b"m", &after));
+
+        for ((i, old), (j, new)) in sks.iter().zip(&new_sks) {
+            assert_eq!(i, j);
+            assert_ne!(old.s, new.s);
+            assert_ne!(old.r, new.r);
+            assert_ne!(old.u, new.u);
+        }
+        for ((_, old), (_, new)) in pks.iter().zip(&new_pks) {
+            assert_ne!(old.pk_i, new.pk_i);
+        }
+        let rebuilt: RistrettoPoint = ss.iter()
+            .map(|&i| share_for(&new_pks, i).unwrap().pk_i * lagrange_coeff(i, &ss).unwrap())
+            .sum();
+        assert_eq!(rebuilt, pk_joint);
+
+        let mut twice = sks.clone();
+        twice[1].0 = 3;
+        assert_eq!(refresh(&par, &twice).err(), Some(Error::InvalidSignerId(3)));
+    }
+
+    #[test]
+    fn tweaked_key_signs_under_the_tweaked_key_only() {
+        use crate::protocol::{sign, verify};
+
+        let par = setup(4, 1).unwrap();
+        let ids = [1, 2, 3, 4];
+        let (pk_joint, pks, sks, _) = kgen_for_ids(&par, &ids).unwrap();
+        let tweak = Scalar::from(7u64);
+        let pk_tweaked = tweak_public_key(&par, &pk_joint, tweak);
+        assert_eq!(pk_tweaked, pk_joint + par.g * tweak);
+
+        let sks: Vec<_> = sks.iter().map(|(i, sk)| (*i, tweak_secret_share(sk, tweak))).collect();
+        let pks: Vec<_> = pks.iter().map(|(i, pk)| (*i, tweak_public_key_share(&par, pk, tweak))).collect();
+        let ss = [2, 4];
+        let sig = sign(&par, &sks, &pks, &pk_tweaked, &ss, b"m").unwrap();
+        assert!(verify(&par, &pk_tweaked, &ss, b"m", &sig));
+        assert!(!verify(&par, &pk_joint, &ss, b"m", &sig));
+    }
+
+    #[test]
+    fn context_keys_are_bound_to_their_context() {
+        use crate::protocol::{sign, verify};
+
+        let par = setup(4, 1).unwrap();
+        let ids = [1, 2, 3, 4];
+        let (pk_joint, pks, sks, _) = kgen_for_ids(&par, &ids).unwrap();
+        let for_context = |context: &[u8]| {
+            let tweak = context_tweak(&pk_joint, context);
+            let derived: Vec<_> =
+                sks.iter().map(|(i, sk)| (*i, derive_context_key(&par, &pk_joint, sk, context))).collect();
+            let pk_ctx = derived[0].1 .0;
+            assert!(derived.iter().all(|(_, (pk, _))| *pk == pk_ctx));
+            let sks: Vec<_> = derived.into_iter().map(|(i, (_, sk))| (i, sk)).collect();
+            let pks: Vec<_> = pks.iter().map(|(i, pk)| (*i, tweak_public_key_share(&par, pk, tweak))).collect();
+            (pk_ctx, pks, sks)
+        };
+
+        let (pk_a, pks_a, sks_a) = for_context(b"committee A");
+        let (pk_b, pks_b, sks_b) = for_context(b"committee B");
+        assert_ne!(pk_a, pk_b);
+
+        // Disjoint quorums, one per context.
+        let sig_a = sign(&par, &sks_a, &pks_a, &pk_a, &[1, 2], b"m").unwrap();
+        let sig_b = sign(&par, &sks_b, &pks_b, &pk_b, &[3, 4], b"m").unwrap();
+        assert!(verify(&par, &pk_a, &[1, 2], b"m", &sig_a));
+        assert!(verify(&par, &pk_b, &[3, 4], b"m", &sig_b));
+        assert!(!verify(&par, &pk_b, &[1, 2], b"m", &sig_a));
+        assert!(!verify(&par, &pk_a, &[3, 4], b"m", &sig_b));
+        assert!(!verify(&par, &pk_joint, &[1, 2], b"m", &sig_a));
+    }
+
+    #[test]
+    fn aggregate_pk_recovers_the_joint_key() {
+        let par = setup(6, 2).unwrap();
+        let (pk_joint, pks, _, _) = kgen(&par).unwrap();
+        let pks: Vec<_> = pks.into_iter().enumerate().map(|(k, pk)| (k as u32 + 1, pk)).collect();
+        for ss in [&[1, 2, 3][..], &[2, 4, 6], &[6, 1, 3, 5], &[1, 2, 3, 4, 5, 6]] {
+            assert_eq!(aggregate_pk(&par, &pks, ss).unwrap(), pk_joint);
+        }
+
+        // A share off by an h or v term no longer interpolates to pk_joint.
+        let mut bad = pks.clone();
+        bad[1].1.pk_i += par.h;
+        assert_ne!(aggregate_pk(&par, &bad, &[1, 2, 3]).unwrap(), pk_joint);
+        assert_eq!(aggregate_pk(&par, &bad, &[1, 3, 4]).unwrap(), pk_joint);
+
+        assert_eq!(aggregate_pk(&par, &pks, &[1, 2]), Err(Error::NotEnoughShares { got: 2, required: 3 }));
+        assert_eq!(aggregate_pk(&par, &pks, &[1, 2, 2]), Err(Error::InvalidSignerId(2)));
+        assert_eq!(aggregate_pk(&par, &pks, &[1, 2, 7]), Err(Error::NotEnoughShares { got: 2, required: 3 }));
+
+        let ids = [3, 10, 42, 99];
+        let par = setup(4, 1).unwrap();
+        let (pk_joint, pks, _, _) = kgen_for_ids(&par, &ids).unwrap();
+        assert_eq!(aggregate_pk(&par, &pks, &[42, 10]).unwrap(), pk_joint);
+    }
 }
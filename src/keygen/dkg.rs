@@ -0,0 +1,285 @@
+// src/keygen/dkg.rs
+//
+// Pedersen-style distributed key generation (no trusted dealer).
+//
+// Every party k acts as a dealer of its own degree-t polynomials
+// s_k(x), r_k(x), u_k(x) with r_k(0) = u_k(0) = 0:
+// - Round 1: broadcast coefficient commitments C_{k,m} = g*s_m + h*r_m + v*u_m
+//   plus a Schnorr proof of knowledge of s_k(0) w.r.t. g for C_{k,0}
+//   (so C_{k,0} carries no h/v component), and send (s_k(j), r_k(j), u_k(j))
+//   privately to every party j.
+// - Round 2: party j checks each received share against the sender's
+//   commitments, g*s + h*r + v*u == Σ_m C_{k,m} j^m, and sums them into its
+//   SecretKeyShare.
+//
+// The joint key is pk = Σ_k C_{k,0} = g * Σ_k s_k(0); no party ever learns
+// s(0) = Σ_k s_k(0). Shares and public key shares have exactly the shape
+// produced by `kgen`, so the signing protocol is unchanged.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::TryRngCore;
+use rand::CryptoRng;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::Error;
+use crate::hash::{decode_point, decode_scalar, enc_point, enc_scalar, hdkg};
+use crate::keygen::eval_commitments;
+use crate::randutil::random_scalar_with_rng;
+use crate::shamir::sample_poly_with_rng;
+use crate::types::{Params, PublicKeyShare, SecretKeyShare};
+
+/// Broadcast message of dealer i.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct DkgCommitments {
+    pub i: u32,
+    pub coeffs: Vec<[u8; 32]>, // C_{i,0..=t} compressed
+    pub pop_r: [u8; 32],       // proof of knowledge of s_i(0): (R, z)
+    pub pop_z: [u8; 32],
+}
+
+/// Private message from dealer `from` to party `to`. Wiped on drop and
+/// left out of Debug output.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct DkgShare {
+    pub from: u32,
+    pub to: u32,
+    pub s: Scalar,
+    pub r: Scalar,
+    pub u: Scalar,
+}
+
+impl fmt::Debug for DkgShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DkgShare")
+            .field("from", &self.from)
+            .field("to", &self.to)
+            .field("s", &"<redacted>")
+            .field("r", &"<redacted>")
+            .field("u", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Round 1 for party i: sample polynomials, commit, and deal shares to
+/// every party 1..=n (including itself). The polynomials are dropped once
+/// the shares are dealt.
+#[cfg(feature = "std")]
+pub fn round1(par: &Params, i: u32) -> (DkgCommitments, Vec<DkgShare>) {
+    round1_with_rng(par, i, &mut OsRng.unwrap_err())
+}
+
+/// `round1` drawing the polynomials and the PoP nonce from `rng`.
+pub fn round1_with_rng(par: &Params, i: u32, rng: &mut impl CryptoRng) -> (DkgCommitments, Vec<DkgShare>) {
+    let s0 = random_scalar_with_rng(rng);
+    let s_poly = sample_poly_with_rng(par.t, s0, rng);
+    let r_poly = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+    let u_poly = sample_poly_with_rng(par.t, Scalar::ZERO, rng);
+
+    let coeffs: Vec<[u8; 32]> = (0..=par.t)
+        .map(|m| {
+            enc_point(&(par.mul_g(&s_poly.coeffs[m]) + par.h * r_poly.coeffs[m] + par.v * u_poly.coeffs[m]))
+        })
+        .collect();
+
+    // PoP: R = g*k, e = Hdkg(i, C_{i,0}, R), z = k + e*s_i(0)
+    let k = random_scalar_with_rng(rng);
+    let r_point = par.mul_g(&k);
+    let c0 = par.mul_g(&s_poly.coeffs[0]);
+    let e = hdkg(i, &c0, &r_point);
+    let z = k + e * s_poly.coeffs[0];
+
+    let shares = (1..=par.n as u32)
+        .map(|j| {
+            let x = Scalar::from(j as u64);
+            DkgShare { from: i, to: j, s: s_poly.eval(x), r: r_poly.eval(x), u: u_poly.eval(x) }
+        })
+        .collect();
+
+    let msg = DkgCommitments { i, coeffs, pop_r: enc_point(&r_point), pop_z: enc_scalar(&z) };
+    (msg, shares)
+}
+
+fn decode_commitments(par: &Params, cm: &DkgCommitments) -> Option<Vec<RistrettoPoint>> {
+    if cm.coeffs.len() != par.t + 1 {
+        return None;
+    }
+    let pts = cm
+        .coeffs
+        .iter()
+        .map(|c| decode_point(c).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    let r_point = decode_point(&cm.pop_r).ok()?;
+    let z = decode_scalar(&cm.pop_z).ok()?;
+    let e = hdkg(cm.i, &pts[0], &r_point);
+    if par.mul_g(&z) != r_point + pts[0] * e {
+        return None;
+    }
+    Some(pts)
+}
+
+/// The one item dealt by each of 1..=n, in dealer order. A dealer that is
+/// missing, appears twice, or is out of range is reported as
+/// Error::InvalidDealing(dealer).
+fn one_per_dealer<T>(n: usize, items: &[T], dealer: impl Fn(&T) -> u32) -> Result<Vec<&T>, Error> {
+    if let Some(x) = items.iter().find(|x| !(1..=n as u32).contains(&dealer(x))) {
+        return Err(Error::InvalidDealing(dealer(x)));
+    }
+    (1..=n as u32)
+        .map(|k| {
+            let mut it = items.iter().filter(|x| dealer(x) == k);
+            match (it.next(), it.next()) {
+                (Some(x), None) => Ok(x),
+                _ => Err(Error::InvalidDealing(k)),
+            }
+        })
+        .collect()
+}
+
+/// Round 2 for party j: verify every dealer's commitments and the share it
+/// sent to j, then sum the shares. Every party 1..=n must have dealt exactly
+/// once, both in `commitments` and in the shares addressed to j. Returns
+/// (pk_joint, own SecretKeyShare); a bad, missing or repeated dealing is
+/// reported as Error::InvalidDealing(dealer).
+pub fn round2(
+    par: &Params,
+    j: u32,
+    commitments: &[DkgCommitments],
+    received: &[DkgShare],
+) -> Result<(RistrettoPoint, SecretKeyShare), Error> {
+    let mut pk_joint = RistrettoPoint::identity();
+    let mut sk = SecretKeyShare { s: Scalar::ZERO, r: Scalar::ZERO, u: Scalar::ZERO };
+
+    let commitments = one_per_dealer(par.n, commitments, |cm| cm.i)?;
+    let to_j: Vec<&DkgShare> = received.iter().filter(|sh| sh.to == j).collect();
+    let received = one_per_dealer(par.n, &to_j, |sh| sh.from)?;
+
+    for (cm, sh) in commitments.into_iter().zip(received) {
+        let k = cm.i;
+        let pts = decode_commitments(par, cm).ok_or(Error::InvalidDealing(k))?;
+
+        let lhs = par.mul_g(&sh.s) + par.h * sh.r + par.v * sh.u;
+        if lhs != eval_commitments(&pts, j) {
+            return Err(Error::InvalidDealing(k));
+        }
+
+        pk_joint += pts[0];
+        sk.s += sh.s;
+        sk.r += sh.r;
+        sk.u += sh.u;
+    }
+
+    Ok((pk_joint, sk))
+}
+
+/// Public key shares pk_i = Σ_k Σ_m C_{k,m} i^m for i = 1..=n, computable by
+/// anyone from the broadcast commitments (one per dealer, as in `round2`).
+pub fn public_key_shares(par: &Params, commitments: &[DkgCommitments]) -> Result<Vec<PublicKeyShare>, Error> {
+    let decoded = one_per_dealer(par.n, commitments, |cm| cm.i)?
+        .into_iter()
+        .map(|cm| decode_commitments(par, cm).ok_or(Error::InvalidDealing(cm.i)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((1..=par.n as u32)
+        .map(|i| PublicKeyShare {
+            pk_i: decoded.iter().map(|pts| eval_commitments(pts, i)).sum(),
+        })
+        .collect())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::keygen::setup;
+    use crate::protocol::{sign, verify};
+
+    /// Every party's round-1 output for an honest n-party DKG.
+    fn dealings(par: &Params) -> (Vec<DkgCommitments>, Vec<DkgShare>) {
+        let mut commitments = Vec::new();
+        let mut shares = Vec::new();
+        for i in 1..=par.n as u32 {
+            let (cm, sh) = round1(par, i);
+            commitments.push(cm);
+            shares.extend(sh);
+        }
+        (commitments, shares)
+    }
+
+    #[test]
+    fn share_debug_redacts_the_secrets() {
+        let par = setup(3, 1).unwrap();
+        let (_, shares) = dealings(&par);
+        let sh = &shares[1];
+        let shown = format!("{:?}", sh);
+        assert!(shown.contains(&format!("from: {}, to: {}", sh.from, sh.to)));
+        for secret in [&sh.s, &sh.r, &sh.u] {
+            assert!(!shown.contains(&format!("{:?}", secret)));
+        }
+    }
+
+    #[test]
+    fn joint_key_verifies_a_protocol_signature() {
+        let par = setup(5, 2).unwrap();
+        let (commitments, shares) = dealings(&par);
+
+        let mut sk_shares = Vec::new();
+        for j in 1..=5u32 {
+            let (pk, sk) = round2(&par, j, &commitments, &shares).unwrap();
+            sk_shares.push((j, sk));
+            if j > 1 {
+                assert_eq!(pk, round2(&par, 1, &commitments, &shares).unwrap().0);
+            }
+        }
+        let pk_joint: RistrettoPoint = commitments.iter().map(|cm| decode_point(&cm.coeffs[0]).unwrap()).sum();
+        let pk_shares: Vec<(u32, PublicKeyShare)> =
+            (1..=5).zip(public_key_shares(&par, &commitments).unwrap()).collect();
+
+        let ss = [1, 3, 5];
+        let sig = sign(&par, &sk_shares, &pk_shares, &pk_joint, &ss, b"dkg").unwrap();
+        assert!(verify(&par, &pk_joint, &ss, b"dkg", &sig));
+    }
+
+    #[test]
+    fn each_dealer_must_deal_exactly_once() {
+        let par = setup(3, 1).unwrap();
+        let (commitments, shares) = dealings(&par);
+
+        let missing = &commitments[1..];
+        assert_eq!(round2(&par, 1, missing, &shares).err(), Some(Error::InvalidDealing(1)));
+        assert_eq!(public_key_shares(&par, missing).err(), Some(Error::InvalidDealing(1)));
+
+        let mut twice = commitments.clone();
+        twice.push(round1(&par, 2).0);
+        assert_eq!(round2(&par, 1, &twice, &shares).err(), Some(Error::InvalidDealing(2)));
+
+        let mut outsider = commitments.clone();
+        outsider[2].i = 4;
+        assert_eq!(round2(&par, 1, &outsider, &shares).err(), Some(Error::InvalidDealing(4)));
+
+        let mut no_share = shares.clone();
+        no_share.retain(|sh| !(sh.from == 3 && sh.to == 1));
+        assert_eq!(round2(&par, 1, &commitments, &no_share).err(), Some(Error::InvalidDealing(3)));
+
+        let mut two_shares = shares.clone();
+        two_shares.push(shares.iter().find(|sh| sh.from == 2 && sh.to == 1).unwrap().clone());
+        assert_eq!(round2(&par, 1, &commitments, &two_shares).err(), Some(Error::InvalidDealing(2)));
+    }
+
+    #[test]
+    fn share_inconsistent_with_commitments_is_rejected() {
+        let par = setup(3, 1).unwrap();
+        let (commitments, mut shares) = dealings(&par);
+        let sh = shares.iter_mut().find(|sh| sh.from == 2 && sh.to == 3).unwrap();
+        sh.s += Scalar::ONE;
+        assert_eq!(round2(&par, 3, &commitments, &shares).err(), Some(Error::InvalidDealing(2)));
+        assert!(round2(&par, 1, &commitments, &shares).is_ok());
+    }
+}
@@ -1,3 +1,7 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod group;
 pub mod hash;
 pub mod shamir;
@@ -6,8 +10,27 @@ pub mod nizk;
 pub mod keygen;
 pub mod protocol;
 pub mod randutil;
+#[cfg(feature = "std")]
+pub mod prime;
+#[cfg(feature = "std")]
 pub mod timed;
 pub mod commitment;
+#[cfg(feature = "std")]
 pub mod tracing;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod combiner;
+#[cfg(feature = "std")]
+pub mod audit;
+
+#[cfg(feature = "test-support")]
+pub mod sim;
+
+#[cfg(any(all(test, feature = "std"), feature = "test-support"))]
+pub mod testutil;
 
+#[cfg(feature = "at-rest")]
+pub mod at_rest;
 
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,16 +1,20 @@
-use threshold_signature::keygen::{kgen, setup};
-use threshold_signature::protocol::{combine, sig1, sig2, sig3_with_pk, verify};
-use threshold_signature::timed::{timed_encrypt, timed_decrypt, derive_h as timed_derive_h, TimedParams};
+// Benchmark harness: keeps the paper's notation (T, VC_Timed, ...).
+#![allow(non_snake_case, non_camel_case_types)]
+
+use threshold_signature::keygen::{kgen_for_ids, setup};
+use threshold_signature::types::share_for;
+use threshold_signature::protocol::{combine_with_a_hat, compute_a_hat, sig1, verify, SigningSession};
+use threshold_signature::nizk::sig_prove;
+use threshold_signature::timed::{
+    setup_timed, timed_aggregate, timed_decrypt_combined, timed_encrypt_combined, TimedParams,
+};
 use threshold_signature::commitment::{
     commit_z, derive_h_from_g, aggregate_commitments, aggregate_openings, verify_aggregate,
 };
 use threshold_signature::tracing::{setup_admitter, admitter_issue_token, trace_encrypt, trace_decrypt};
 
-use num_bigint::BigUint;
-use std::time::{Duration, Instant};
-
-mod prime;
-use prime::random_prime;
+use std::error::Error;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Mode {
@@ -32,7 +36,7 @@ impl Mode {
     }
 }
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 struct Timings {
     total: Duration,
     keygen: Duration,
@@ -49,6 +53,25 @@ struct Timings {
     tracing_dec: Duration,
 }
 
+/// One `run_exp` result as a JSON Lines record (`--json`): the averaged
+/// timings plus the run's parameters.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ResultRecord {
+    n: usize,
+    t: usize,
+    mode: String,
+    T: u64,
+    reps: usize,
+    ok: bool,
+    timings: Timings,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Output {
+    Csv,
+    Json,
+}
+
 fn add(a: &mut Duration, b: Duration) { *a += b; }
 
 fn avg(d: Duration, n: usize) -> Duration {
@@ -57,22 +80,18 @@ fn avg(d: Duration, n: usize) -> Duration {
 
 fn make_timed_params(T: u64) -> TimedParams {
     let mut rng = rand::rng();
-    let p = random_prime(1024, &mut rng);
-    let q = random_prime(1024, &mut rng);
-    let n = &p * &q;
-
-    let g = BigUint::from(5u32);
-    let h = timed_derive_h(&n, &g, T);
-
-    TimedParams { n, g, h, t: T }
+    setup_timed(2048, T, &mut rng).expect("timed parameters").params
 }
 
-fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> (Timings, bool) {
+fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> Result<(Timings, bool), Box<dyn Error>> {
     let total_start = Instant::now();
     let mut tm = Timings::default();
 
-    let par = setup(n, t);
-    let (pk_joint, pk_shares, sk_shares) = kgen(&par);
+    let par = setup(n, t)?;
+    let ids: Vec<u32> = (1..=n as u32).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids)?;
+    let sk_of = |i| share_for(&sk_shares, i).ok_or(format!("no secret key share for signer {}", i));
+    let pk_of = |i| share_for(&pk_shares, i).ok_or(format!("no public key share for signer {}", i));
 
     let msg = b"hello gargos threshold schnorr";
     let ss: Vec<u32> = (1..=(t+1)).map(|i| i as u32).collect();
@@ -81,30 +100,29 @@ fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> (Tim
     let mut commits = vec![];
     let mut states = vec![];
     for &i in &ss {
-        let (cm, st) = sig1(&par, i, &sk_shares[i as usize - 1]);
+        let (cm, st) = sig1(&par, i, sk_of(i)?);
         commits.push((cm.i, cm.mu_i));
         states.push(st);
     }
     tm.r1 = t1.elapsed();
 
+    let pk_map: Vec<(u32, _)> = pk_shares.iter().map(|(i, pk)| (*i, pk.pk_i)).collect();
+
     let t2 = Instant::now();
+    let mut session = SigningSession::new(&par, msg, &ss, &pk_joint, &pk_map);
     let mut opens = vec![];
     let mut states2 = vec![];
     for (idx, &i) in ss.iter().enumerate() {
-        let (om, st2) = sig2(&par, msg, i, &commits,
-                             &pk_shares[i as usize - 1],
-                             &sk_shares[i as usize - 1],
-                             &states[idx]);
+        let (om, st2) = session.sig2(i, &commits, pk_of(i)?, sk_of(i)?, &states[idx])?;
         opens.push(om);
         states2.push(st2);
     }
     tm.r2 = t2.elapsed();
 
-    let pk_map: Vec<(u32, _)> = (1..=n as u32)
-        .map(|i| (i, pk_shares[i as usize - 1].pk_i)).collect();
-
     let h_vc = derive_h_from_g(&par.g);
     let admitter = setup_admitter();
+    // Tokens stay valid for an hour.
+    let expiry = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
 
     let t3 = Instant::now();
     let mut sigshares = vec![];
@@ -113,11 +131,16 @@ fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> (Tim
     let mut timed_ct = vec![];
     let mut trace_ct = vec![];
 
-    for (idx, &i) in ss.iter().enumerate() {
-        let ps = sig3_with_pk(&par, msg, &ss, i,
-            &pk_joint, &pk_map, &sk_shares[i as usize - 1],
-            &states2[idx], &commits, &opens).unwrap();
+    let signers = ss.iter().zip(&states2).map(|(&i, st)| Ok((i, sk_of(i)?, st))).collect::<Result<Vec<_>, String>>()?;
+    // On a rejected round, report every signer at fault before failing.
+    let mut log = Vec::new();
+    let partials = SigningSession::new(&par, msg, &ss, &pk_joint, &pk_map)
+        .with_log(&mut log)
+        .sig3_batch(&signers, &commits, &opens);
+    let partials = partials.inspect_err(|_| log.iter().for_each(|event| eprintln!("round 3: {:?}", event)))?;
 
+    for ps in partials {
+        let i = ps.i;
         let z = curve25519_dalek::scalar::Scalar::from_bytes_mod_order(ps.z_i);
 
         let t = Instant::now();
@@ -126,12 +149,12 @@ fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> (Tim
         vc_cm.push(cm); vc_op.push(op);
 
         let t = Instant::now();
-        let ct = timed_encrypt(timed, &ps.z_i, b"timed-z");
+        let ct = timed_encrypt_combined(timed, &[ps.z_i], b"timed-z").expect("z_i < N");
         if matches!(mode, Mode::VC_Timed | Mode::VC_Timed_Tracing) { tm.timed_enc += t.elapsed(); }
         timed_ct.push(ct);
 
         let t = Instant::now();
-        let tok = admitter_issue_token(&admitter, msg);
+        let tok = admitter_issue_token(&admitter, msg, expiry);
         let tc = trace_encrypt(&tok, &ps.z_i, b"trace-z");
         if matches!(mode, Mode::VC_Timed_Tracing) { tm.tracing_enc += t.elapsed(); }
         trace_ct.push(tc);
@@ -141,39 +164,42 @@ fn run_once(n: usize, t: usize, mode: Mode, timed: &TimedParams, T: u64) -> (Tim
     tm.r3 = t3.elapsed();
 
     let t = Instant::now();
-    let sig = combine(&ss, &opens, &sigshares).unwrap();
+    let a_hat = compute_a_hat(&ss, &opens).ok_or("failed to combine partial signatures")?;
+    let sig = combine_with_a_hat(&ss, &a_hat, &sigshares)?;
     tm.combine = t.elapsed();
 
     let t = Instant::now();
-    let ok_sig = verify(&par, &pk_joint, msg, &sig);
+    let ok_sig = verify(&par, &pk_joint, &ss, msg, &sig);
     tm.verify = t.elapsed();
 
     let t = Instant::now();
-    let c_agg = aggregate_commitments(&vc_cm);
     let r_agg = aggregate_openings(&vc_op);
+    let c_agg = aggregate_commitments(&vc_cm).ok_or("undecodable VC commitment")?;
     let ok_vc = verify_aggregate(&par.g, &h_vc, &c_agg, &sig.z, &r_agg);
     tm.vc_verify = t.elapsed();
 
     let t = Instant::now();
-    let ok_timed = timed_ct.iter().all(|c| timed_decrypt(timed, c, b"timed-z").is_some());
+    // One puzzle for all signers: it opens to Σ z_i, i.e. z.
+    let puzzle = timed_aggregate(timed, &timed_ct).ok_or("could not aggregate timed ciphertexts")?;
+    let ok_timed = timed_decrypt_combined(timed, &puzzle, b"timed-z").is_ok_and(|z| z == sig.z);
     tm.timed_dec = t.elapsed();
 
     let t = Instant::now();
-    let ok_trace = trace_decrypt(&admitter_issue_token(&admitter, msg), &trace_ct[0]).is_some();
+    let ok_trace = trace_decrypt(&admitter_issue_token(&admitter, msg, expiry), &trace_ct[0], b"trace-z").is_some();
     tm.tracing_dec = t.elapsed();
 
     tm.total = total_start.elapsed();
 
-    (tm, ok_sig && ok_vc && ok_timed && ok_trace && timed.t == T)
+    Ok((tm, ok_sig && ok_vc && ok_timed && ok_trace && timed.t == T))
 }
 
-fn run_exp(n: usize, t: usize, mode: Mode, reps: usize, T: u64) {
-    let timed = make_timed_params(T);
+fn run_exp(n: usize, t: usize, mode: Mode, reps: usize, timed: &TimedParams, out: Output) -> Result<(), Box<dyn Error>> {
+    let T = timed.t;
     let mut sum = Timings::default();
     let mut ok = true;
 
     for _ in 0..reps {
-        let (tm, good) = run_once(n, t, mode, &timed, T);
+        let (tm, good) = run_once(n, t, mode, timed, T)?;
         ok &= good;
         add(&mut sum.total, tm.total);
         add(&mut sum.keygen, tm.keygen);
@@ -206,6 +232,12 @@ fn run_exp(n: usize, t: usize, mode: Mode, reps: usize, T: u64) {
         tracing_dec: avg(sum.tracing_dec, reps),
     };
 
+    if out == Output::Json {
+        let rec = ResultRecord { n, t, mode: mode.name().to_string(), T, reps, ok, timings: avg };
+        println!("{}", serde_json::to_string(&rec)?);
+        return Ok(());
+    }
+
     println!(
         "RESULT,n={},t={},mode={},T={},reps={},ok={},total_ms={:.3},r1_ms={:.3},r2_ms={:.3},r3_ms={:.3},combine_ms={:.3},verify_ms={:.3},vc_commit_ms={:.3},vc_verify_ms={:.3},timed_enc_ms={:.3},timed_dec_ms={:.3},tracing_enc_ms={:.3},tracing_dec_ms={:.3}",
         n, t, mode.name(), T, reps, ok,
@@ -222,22 +254,112 @@ fn run_exp(n: usize, t: usize, mode: Mode, reps: usize, T: u64) {
         avg.tracing_enc.as_secs_f64()*1e3,
         avg.tracing_dec.as_secs_f64()*1e3,
     );
+    Ok(())
 }
 
-fn main() {
+// sig_prove in isolation over the states of an n-signer round 2, and the
+// whole round-2 phase (sig2 for every signer) for comparison.
+fn bench_sig_prove(n: usize, reps: usize) -> Result<(), Box<dyn Error>> {
+    let par = setup(n, n - 1)?;
+    let ids: Vec<u32> = (1..=n as u32).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids)?;
+    let msg = b"hello gargos threshold schnorr";
+    let sk_of = |i| share_for(&sk_shares, i).ok_or(format!("no secret key share for signer {}", i));
+    let pk_of = |i| share_for(&pk_shares, i).ok_or(format!("no public key share for signer {}", i));
+
+    let mut commits = vec![];
+    let mut states = vec![];
+    for &i in &ids {
+        let (cm, st) = sig1(&par, i, sk_of(i)?);
+        commits.push((cm.i, cm.mu_i));
+        states.push(st);
+    }
+
+    let pk_map: Vec<(u32, _)> = pk_shares.iter().map(|(i, pk)| (*i, pk.pk_i)).collect();
+    let mut session = SigningSession::new(&par, msg, &ids, &pk_joint, &pk_map);
+    let mut r2 = Duration::ZERO;
+    let mut prove = Duration::ZERO;
+    for _ in 0..reps {
+        let t = Instant::now();
+        let mut states2 = vec![];
+        for (idx, &i) in ids.iter().enumerate() {
+            let (_, st2) = session.sig2(i, &commits, pk_of(i)?, sk_of(i)?, &states[idx])?;
+            states2.push(st2);
+        }
+        r2 += t.elapsed();
+
+        let t = Instant::now();
+        for (st, &i) in states2.iter().zip(&ids) {
+            sig_prove(&par, i, &pk_of(i)?.pk_i, &st.a_i_point, &st.b_i,
+                      &st.g0, &st.g1, &st.rho_i, &st.a_i, sk_of(i)?);
+        }
+        prove += t.elapsed();
+    }
+
+    println!(
+        "RESULT,bench=sig_prove,n={},reps={},sig_prove_us={:.1},r2_ms={:.3}",
+        n, reps,
+        prove.as_secs_f64() * 1e6 / (n * reps) as f64,
+        avg(r2, reps).as_secs_f64() * 1e3,
+    );
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    if std::env::args().any(|a| a == "--bench-sig-prove") {
+        return bench_sig_prove(64, 20);
+    }
+
+    // CSV by default; --json (or HARNESS_FORMAT=json) emits JSON Lines.
+    let out = if std::env::args().any(|a| a == "--json")
+        || std::env::var("HARNESS_FORMAT").is_ok_and(|f| f == "json")
+    {
+        Output::Json
+    } else {
+        Output::Csv
+    };
+
     let reps = 10;
+    // 2048-bit parameters take a while to generate; every run shares them.
+    let timed = make_timed_params(12);
 
     for n in [8,16,32,64,128] {
         let t = n/2;
         for m in [Mode::Baseline, Mode::VC, Mode::VC_Timed, Mode::VC_Timed_Tracing] {
-            run_exp(n, t, m, reps, 12);
+            run_exp(n, t, m, reps, &timed, out)?;
         }
     }
 
     let n = 64;
     for t in [1,4,8,16,32] {
         for m in [Mode::Baseline, Mode::VC, Mode::VC_Timed, Mode::VC_Timed_Tracing] {
-            run_exp(n, t, m, reps, 12);
+            run_exp(n, t, m, reps, &timed, out)?;
         }
     }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_record_round_trips() {
+        let timings = Timings {
+            total: Duration::from_micros(12_345),
+            r1: Duration::from_nanos(1),
+            r3: Duration::from_millis(7),
+            timed_dec: Duration::new(3, 999_999_999),
+            ..Timings::default()
+        };
+        let rec = ResultRecord {
+            n: 8, t: 4, mode: Mode::VC_Timed.name().to_string(), T: 12, reps: 10, ok: true,
+            timings: timings.clone(),
+        };
+        let line = serde_json::to_string(&rec).unwrap();
+        assert!(!line.contains('\n'));
+        let back: ResultRecord = serde_json::from_str(&line).unwrap();
+        assert_eq!(back.timings, timings);
+        assert_eq!(back, rec);
+    }
 }
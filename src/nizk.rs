@@ -1,9 +1,22 @@
+use alloc::vec::Vec;
+
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::TryRngCore;
+use rand::CryptoRng;
 use serde::{Deserialize, Serialize};
 
-use crate::hash::{f0, f1, hfs};
-use crate::types::{Params, SecretKeyShare};
+use crate::error::Error;
+use crate::group::{scalar_from_canonical_ct, Bases, Group, Ristretto};
+use crate::hash::{
+    dec_point, decode_point, decode_scalar, enc_point, enc_scalar, f0_in, f1_in, hfs_in, hfs_v2_in, hnonce,
+};
+use crate::randutil::random_scalar_with_rng;
+use crate::types::{NonceReveal, Params, SecretKeyShare};
 
 /// Proof π := (XA, XB, Xpk, za, zs, zr, zu) as in Fig.4.:contentReference[oaicite:6]{index=6}
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -17,25 +30,77 @@ pub struct Proof {
     pub zu: [u8; 32],
 }
 
-fn dec_point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
-    use curve25519_dalek::ristretto::CompressedRistretto;
-    CompressedRistretto(*bytes).decompress()
-}
+impl Proof {
+    /// Fixed wire layout: xa || xb || xpk || za || zs || zr || zu.
+    pub fn to_bytes(&self) -> [u8; 224] {
+        let mut out = [0u8; 224];
+        for (k, f) in [&self.xa, &self.xb, &self.xpk, &self.za, &self.zs, &self.zr, &self.zu]
+            .iter()
+            .enumerate()
+        {
+            out[32 * k..32 * (k + 1)].copy_from_slice(*f);
+        }
+        out
+    }
 
-fn dec_scalar(bytes: &[u8; 32]) -> Scalar {
-    Scalar::from_bytes_mod_order(*bytes)
+    /// Inverse of `to_bytes`; rejects non-canonical point and scalar encodings.
+    pub fn from_bytes(bytes: &[u8; 224]) -> Result<Proof, Error> {
+        let mut f = [[0u8; 32]; 7];
+        for (k, chunk) in bytes.chunks_exact(32).enumerate() {
+            f[k].copy_from_slice(chunk);
+        }
+        for p in &f[..3] {
+            decode_point(p)?;
+        }
+        for s in &f[3..] {
+            decode_scalar(s)?;
+        }
+        Ok(Proof { xa: f[0], xb: f[1], xpk: f[2], za: f[3], zs: f[4], zr: f[5], zu: f[6] })
+    }
 }
 
-fn enc_point(p: &RistrettoPoint) -> [u8; 32] {
-    p.compress().to_bytes()
+/// Which HFS transcript a proof's challenge was derived from. `sig_prove`
+/// always produces V2; V1 proofs only verify through
+/// `sig_verify_versioned` with `ProofVersion::V1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProofVersion {
+    /// `hfs`: flat concatenation under "Gargos::HFS", as the crate first
+    /// shipped it. It does not absorb the signer id, so a V1 proof is not
+    /// bound to its slot.
+    V1,
+    /// `hfs_v2`: labeled Merlin transcript.
+    #[default]
+    V2,
 }
-fn enc_scalar(s: &Scalar) -> [u8; 32] {
-    s.to_bytes()
+
+impl ProofVersion {
+    #[allow(clippy::too_many_arguments)]
+    fn challenge_in<G: Group>(
+        self,
+        i: u32,
+        xa: &G::Point,
+        xb: &G::Point,
+        xpk: &G::Point,
+        a: &G::Point,
+        b: &G::Point,
+        pk: &G::Point,
+        g0: &G::Point,
+        g1: &G::Point,
+        rho: &[u8; 32],
+    ) -> G::Scalar {
+        match self {
+            ProofVersion::V1 => hfs_in::<G>(xa, xb, xpk, a, b, pk, g0, g1, rho),
+            ProofVersion::V2 => hfs_v2_in::<G>(i, xa, xb, xpk, a, b, pk, g0, g1, rho),
+        }
+    }
 }
 
-/// SigProve((pk, A, B, g0, g1, rho); (a, sk=(s,r,u))) -> π (Fig.4).:contentReference[oaicite:7]{index=7}
+/// SigProve((i, pk, A, B, g0, g1, rho); (a, sk=(s,r,u))) -> π (Fig.4).:contentReference[oaicite:7]{index=7}
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
 pub fn sig_prove(
     par: &Params,
+    i: u32,
     pk_i: &RistrettoPoint,
     a_i_point: &RistrettoPoint, // A
     b_i: &RistrettoPoint,        // B
@@ -45,89 +110,715 @@ pub fn sig_prove(
     a: &Scalar,
     sk: &SecretKeyShare,
 ) -> Proof {
+    sig_prove_with_rng(par, i, pk_i, a_i_point, b_i, g0, g1, rho, a, sk, &mut OsRng.unwrap_err())
+}
+
+/// `sig_prove` drawing the proof nonces from `rng`.
+#[allow(clippy::too_many_arguments)]
+pub fn sig_prove_with_rng(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    b_i: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+    a: &Scalar,
+    sk: &SecretKeyShare,
+    rng: &mut impl CryptoRng,
+) -> Proof {
+    let pi = sig_prove_in::<Ristretto>(
+        &par.bases(),
+        i,
+        pk_i,
+        a_i_point,
+        b_i,
+        g0,
+        g1,
+        rho,
+        a,
+        (&sk.s, &sk.r, &sk.u),
+        rng,
+    );
+    Proof {
+        xa: enc_point(&pi.xa),
+        xb: enc_point(&pi.xb),
+        xpk: enc_point(&pi.xpk),
+        za: enc_scalar(&pi.za),
+        zs: enc_scalar(&pi.zs),
+        zr: enc_scalar(&pi.zr),
+        zu: enc_scalar(&pi.zu),
+    }
+}
+
+/// SigVer((i, pk, A, B, g0, g1, rho); π) -> bool (Fig.4).:contentReference[oaicite:8]{index=8}
+/// The responses z* must be canonical (< ℓ); a reduced alias is rejected.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+pub fn sig_verify(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint, // A
+    b_i: &RistrettoPoint,        // B
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+    proof: &Proof,
+) -> bool {
+    sig_verify_versioned(par, ProofVersion::V2, i, pk_i, a_i_point, b_i, g0, g1, rho, proof)
+}
+
+/// `sig_verify` drawing the weights that fold its three equations from
+/// `rng`.
+#[allow(clippy::too_many_arguments)]
+pub fn sig_verify_with_rng(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    b_i: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+    proof: &Proof,
+    rng: &mut impl CryptoRng,
+) -> bool {
+    sig_verify_versioned_with_rng(par, ProofVersion::V2, i, pk_i, a_i_point, b_i, g0, g1, rho, proof, rng)
+}
+
+/// `sig_verify` for a proof made under the given HFS `version`, e.g. a V1
+/// proof archived before the Merlin transcript. `i` is ignored for V1.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+pub fn sig_verify_versioned(
+    par: &Params,
+    version: ProofVersion,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    b_i: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+    proof: &Proof,
+) -> bool {
+    sig_verify_versioned_with_rng(par, version, i, pk_i, a_i_point, b_i, g0, g1, rho, proof, &mut OsRng.unwrap_err())
+}
+
+/// `sig_verify_versioned` drawing its weights from `rng`.
+#[allow(clippy::too_many_arguments)]
+pub fn sig_verify_versioned_with_rng(
+    par: &Params,
+    version: ProofVersion,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    b_i: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    rho: &[u8; 32],
+    proof: &Proof,
+    rng: &mut impl CryptoRng,
+) -> bool {
+    let Some(pi) = decode_proof(proof) else {
+        return false;
+    };
+    verify_in::<Ristretto>(
+        &par.bases(),
+        version,
+        i,
+        pk_i,
+        a_i_point,
+        b_i,
+        g0,
+        g1,
+        rho,
+        &pi,
+        rng,
+    )
+}
+
+/// One `sig_verify` statement: (i, pk_i, A, B, g0, g1, rho, π).
+pub type SigVerifyItem<'a> = (
+    u32,
+    RistrettoPoint,
+    RistrettoPoint,
+    RistrettoPoint,
+    RistrettoPoint,
+    RistrettoPoint,
+    [u8; 32],
+    &'a Proof,
+);
+
+/// `sig_verify` for many proofs with one multi-exponentiation: each
+/// proof's folded equation (see `sig_verify_in`) is weighted by a fresh
+/// random scalar and the g, h, v terms are merged across proofs. Accepts
+/// iff every proof verifies, up to a ~1/ℓ error; on false, call
+/// `sig_verify` per item to find the bad one. An empty batch is rejected.
+/// Only `ProofVersion::V2` proofs are accepted.
+#[cfg(feature = "std")]
+pub fn sig_verify_batch(par: &Params, items: &[SigVerifyItem<'_>]) -> bool {
+    sig_verify_batch_with_rng(par, items, &mut OsRng.unwrap_err())
+}
+
+/// `sig_verify_batch` drawing the per-equation weights from `rng`.
+pub fn sig_verify_batch_with_rng(par: &Params, items: &[SigVerifyItem<'_>], rng: &mut impl CryptoRng) -> bool {
+    if items.is_empty() {
+        return false;
+    }
+    let (mut g_coef, mut h_coef, mut v_coef) = (Scalar::ZERO, Scalar::ZERO, Scalar::ZERO);
+    let mut scalars = Vec::with_capacity(10 * items.len() + 3);
+    let mut points = Vec::with_capacity(10 * items.len() + 3);
+
+    for &(i, pk_i, a_i_point, b_i, g0, g1, ref rho, proof) in items {
+        let Some(ProofIn { xa, xb, xpk, za, zs, zr, zu }) = decode_proof(proof) else {
+            return false;
+        };
+
+        let e = hfs_v2_in::<Ristretto>(i, &xa, &xb, &xpk, &a_i_point, &b_i, &pk_i, &g0, &g1, rho);
+
+        // weights of equations (1), (2), (3) for this proof
+        let d1 = random_scalar_with_rng(rng);
+        let d2 = random_scalar_with_rng(rng);
+        let d3 = random_scalar_with_rng(rng);
+
+        g_coef += (d1 + d2) * za + d3 * zs;
+        h_coef += d3 * zr;
+        v_coef += d3 * zu;
+        scalars.extend([
+            d1 * zr,   // g0
+            d1 * zu,   // g1
+            d2 * zr,   // h0
+            d2 * zu,   // h1
+            -d1,       // XA
+            -(d1 * e), // A
+            -d2,       // XB
+            -(d2 * e), // B
+            -d3,       // Xpk
+            -(d3 * e), // pk
+        ]);
+        let (h0, h1) = (f0_in::<Ristretto>(rho), f1_in::<Ristretto>(rho));
+        points.extend([g0, g1, h0, h1, xa, a_i_point, xb, b_i, xpk, pk_i]);
+    }
+
+    scalars.extend([g_coef, h_coef, v_coef]);
+    points.extend([par.g, par.h, par.v]);
+    RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity()
+}
+
+/// The Fig.4 proof with decoded group elements, for any `Group`.
+#[derive(Clone, Copy, Debug)]
+pub struct ProofIn<G: Group> {
+    pub xa: G::Point,
+    pub xb: G::Point,
+    pub xpk: G::Point,
+    pub za: G::Scalar,
+    pub zs: G::Scalar,
+    pub zr: G::Scalar,
+    pub zu: G::Scalar,
+}
+
+/// The wire proof's points and responses, or None if a point does not
+/// decompress or a response is not reduced mod ℓ (a reduced alias would
+/// give a second encoding of the same proof). The four responses go
+/// through `scalar_from_canonical_ct` and their range checks are combined
+/// before the one branch, so a batch verifier does not branch per field.
+fn decode_proof(proof: &Proof) -> Option<ProofIn<Ristretto>> {
+    let (za, zs, zr, zu) = (
+        scalar_from_canonical_ct(&proof.za),
+        scalar_from_canonical_ct(&proof.zs),
+        scalar_from_canonical_ct(&proof.zr),
+        scalar_from_canonical_ct(&proof.zu),
+    );
+    let canonical = za.is_some() & zs.is_some() & zr.is_some() & zu.is_some();
+    if !bool::from(canonical) {
+        return None;
+    }
+    Some(ProofIn {
+        xa: dec_point(&proof.xa)?,
+        xb: dec_point(&proof.xb)?,
+        xpk: dec_point(&proof.xpk)?,
+        za: za.unwrap(),
+        zs: zs.unwrap(),
+        zr: zr.unwrap(),
+        zu: zu.unwrap(),
+    })
+}
+
+/// `sig_prove` over any `Group`; `sk` is the share opening (s, r, u).
+#[allow(clippy::too_many_arguments)]
+pub fn sig_prove_in<G: Group>(
+    bases: &Bases<G>,
+    i: u32,
+    pk_i: &G::Point,
+    a_i_point: &G::Point,
+    b_i: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+    a: &G::Scalar,
+    sk: (&G::Scalar, &G::Scalar, &G::Scalar),
+    rng: &mut impl CryptoRng,
+) -> ProofIn<G> {
+    prove_in(bases, ProofVersion::V2, i, pk_i, a_i_point, b_i, g0, g1, rho, a, sk, rng)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_in<G: Group>(
+    bases: &Bases<G>,
+    version: ProofVersion,
+    i: u32,
+    pk_i: &G::Point,
+    a_i_point: &G::Point,
+    b_i: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+    a: &G::Scalar,
+    sk: (&G::Scalar, &G::Scalar, &G::Scalar),
+    rng: &mut impl CryptoRng,
+) -> ProofIn<G> {
+    let (s, r, u) = sk;
     // (h0, h1) := (F0(rho), F1(rho))
-    let h0 = f0(rho);
-    let h1 = f1(rho);
+    let h0 = f0_in::<G>(rho);
+    let h1 = f1_in::<G>(rho);
 
     // sample hats
-    let a_hat = crate::randutil::random_scalar();
-    let s_hat = crate::randutil::random_scalar();
-    let r_hat = crate::randutil::random_scalar();
-    let u_hat = crate::randutil::random_scalar();
+    let a_hat = G::random_scalar(rng);
+    let s_hat = G::random_scalar(rng);
+    let r_hat = G::random_scalar(rng);
+    let u_hat = G::random_scalar(rng);
 
+    // g^{a_hat} is shared by XA and XB and comes from the basepoint table;
+    // the remaining terms of each commitment are one constant-time
+    // multi-exponentiation (the hats are secret), giving the same points as
+    // the term-by-term sums.
+    let g_a_hat = bases.mul_g(&a_hat);
     // XA := g^{a_hat} g0^{r_hat} g1^{u_hat}
-    let xa = par.g * a_hat + (*g0) * r_hat + (*g1) * u_hat;
+    let xa = G::add(&g_a_hat, &G::multiscalar_mul(&[r_hat, u_hat], &[*g0, *g1]));
     // XB := g^{a_hat} h0^{r_hat} h1^{u_hat}
-    let xb = par.g * a_hat + h0 * r_hat + h1 * u_hat;
+    let xb = G::add(&g_a_hat, &G::multiscalar_mul(&[r_hat, u_hat], &[h0, h1]));
     // Xpk := g^{s_hat} h^{r_hat} v^{u_hat}
-    let xpk = par.g * s_hat + par.h * r_hat + par.v * u_hat;
+    let xpk = G::add(&bases.mul_g(&s_hat), &G::multiscalar_mul(&[r_hat, u_hat], &[bases.h, bases.v]));
 
-    // e := HFS(...)
-    let e = hfs(&xa, &xb, &xpk, a_i_point, b_i, pk_i, g0, g1, rho);
+    // e := HFS(...) under `version`
+    let e = version.challenge_in::<G>(i, &xa, &xb, &xpk, a_i_point, b_i, pk_i, g0, g1, rho);
 
     // responses
-    let za = a_hat + (*a) * e;
-    let zs = s_hat + sk.s * e;
-    let zr = r_hat + sk.r * e;
-    let zu = u_hat + sk.u * e;
+    let resp = |hat: &G::Scalar, w: &G::Scalar| G::scalar_add(hat, &G::scalar_mul(w, &e));
+    ProofIn {
+        xa,
+        xb,
+        xpk,
+        za: resp(&a_hat, a),
+        zs: resp(&s_hat, s),
+        zr: resp(&r_hat, r),
+        zu: resp(&u_hat, u),
+    }
+}
 
-    Proof {
+/// `sig_verify` over any `Group`; `rng` draws the batching weights.
+#[allow(clippy::too_many_arguments)]
+pub fn sig_verify_in<G: Group>(
+    bases: &Bases<G>,
+    i: u32,
+    pk_i: &G::Point,
+    a_i_point: &G::Point,
+    b_i: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+    pi: &ProofIn<G>,
+    rng: &mut impl CryptoRng,
+) -> bool {
+    verify_in(bases, ProofVersion::V2, i, pk_i, a_i_point, b_i, g0, g1, rho, pi, rng)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn verify_in<G: Group>(
+    bases: &Bases<G>,
+    version: ProofVersion,
+    i: u32,
+    pk_i: &G::Point,
+    a_i_point: &G::Point,
+    b_i: &G::Point,
+    g0: &G::Point,
+    g1: &G::Point,
+    rho: &[u8; 32],
+    pi: &ProofIn<G>,
+    rng: &mut impl CryptoRng,
+) -> bool {
+    let h0 = f0_in::<G>(rho);
+    let h1 = f1_in::<G>(rho);
+
+    let e = version.challenge_in::<G>(i, &pi.xa, &pi.xb, &pi.xpk, a_i_point, b_i, pk_i, g0, g1, rho);
+
+    // The three checks
+    //   (1) g^za g0^zr g1^zu == XA * A^e
+    //   (2) g^za h0^zr h1^zu == XB * B^e
+    //   (3) g^zs h^zr  v^zu  == Xpk * pk^e
+    // are folded into one multi-exponentiation E1 + d2*E2 + d3*E3 == 0 with
+    // E_k = left_k - right_k and fresh random d2, d3. If any E_k != 0 the sum
+    // vanishes with probability ~1/p, so this accepts exactly when all three
+    // hold (up to that negligible error). Public inputs only: vartime is fine.
+    let d2 = G::random_scalar(rng);
+    let d3 = G::random_scalar(rng);
+
+    let add = G::scalar_add;
+    let mul = G::scalar_mul;
+    let neg = G::scalar_neg;
+    G::vartime_multiscalar_mul(
+        &[
+            add(&add(&pi.za, &mul(&d2, &pi.za)), &mul(&d3, &pi.zs)), // g
+            pi.zr,                                                    // g0
+            pi.zu,                                                    // g1
+            mul(&d2, &pi.zr),                                         // h0
+            mul(&d2, &pi.zu),                                         // h1
+            mul(&d3, &pi.zr),                                         // h
+            mul(&d3, &pi.zu),                                         // v
+            neg(&G::scalar_one()),                                    // XA
+            neg(&e),                                                  // A
+            neg(&d2),                                                 // XB
+            neg(&mul(&d2, &e)),                                       // B
+            neg(&d3),                                                 // Xpk
+            neg(&mul(&d3, &e)),                                       // pk
+        ],
+        &[
+            bases.g, *g0, *g1, h0, h1, bases.h, bases.v, pi.xa, *a_i_point, pi.xb, *b_i,
+            pi.xpk, *pk_i,
+        ],
+    ) == G::identity()
+}
+
+/// Nonce reveal for signer i: R = g*a and S = g*s with a proof of
+/// knowledge of (a, s, r, u) such that
+///   R = g^a,  A = R g0^r g1^u,  S = g^s,  pk = S h^r v^u.
+#[cfg(feature = "std")]
+#[allow(clippy::too_many_arguments)]
+pub fn nonce_prove(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    a: &Scalar,
+    sk: &SecretKeyShare,
+) -> NonceReveal {
+    nonce_prove_with_rng(par, i, pk_i, a_i_point, g0, g1, a, sk, &mut OsRng.unwrap_err())
+}
+
+/// `nonce_prove` drawing the proof nonces from `rng`.
+#[allow(clippy::too_many_arguments)]
+pub fn nonce_prove_with_rng(
+    par: &Params,
+    i: u32,
+    pk_i: &RistrettoPoint,
+    a_i_point: &RistrettoPoint,
+    g0: &RistrettoPoint,
+    g1: &RistrettoPoint,
+    a: &Scalar,
+    sk: &SecretKeyShare,
+    rng: &mut impl CryptoRng,
+) -> NonceReveal {
+    let r = par.mul_g(a);
+    let s = par.mul_g(&sk.s);
+
+    let a_hat = random_scalar_with_rng(rng);
+    let s_hat = random_scalar_with_rng(rng);
+    let r_hat = random_scalar_with_rng(rng);
+    let u_hat = random_scalar_with_rng(rng);
+
+    // XR := g^{a_hat}, XA := XR g0^{r_hat} g1^{u_hat}
+    // XS := g^{s_hat}, Xpk := XS h^{r_hat} v^{u_hat}
+    let xr = par.mul_g(&a_hat);
+    let xa = xr + g0 * r_hat + g1 * u_hat;
+    let xs = par.mul_g(&s_hat);
+    let xpk = xs + par.h * r_hat + par.v * u_hat;
+
+    let e = hnonce(i, &r, &s, a_i_point, pk_i, g0, g1, &xr, &xa, &xs, &xpk);
+
+    NonceReveal {
+        i,
+        r_point: enc_point(&r),
+        s_point: enc_point(&s),
+        xr: enc_point(&xr),
         xa: enc_point(&xa),
-        xb: enc_point(&xb),
+        xs: enc_point(&xs),
         xpk: enc_point(&xpk),
-        za: enc_scalar(&za),
-        zs: enc_scalar(&zs),
-        zr: enc_scalar(&zr),
-        zu: enc_scalar(&zu),
+        za: enc_scalar(&(a_hat + a * e)),
+        zs: enc_scalar(&(s_hat + sk.s * e)),
+        zr: enc_scalar(&(r_hat + sk.r * e)),
+        zu: enc_scalar(&(u_hat + sk.u * e)),
     }
 }
 
-/// SigVer((pk, A, B, g0, g1, rho); π) -> bool (Fig.4).:contentReference[oaicite:8]{index=8}
-pub fn sig_verify(
+/// Check a `NonceReveal` against signer i's (pk_i, A_i):
+///   g^za == XR R^e,  g^za g0^zr g1^zu == XA A^e,
+///   g^zs == XS S^e,  g^zs h^zr v^zu == Xpk pk^e.
+/// Returns (R, S) on success. Rejects non-canonical encodings.
+pub fn nonce_verify(
     par: &Params,
     pk_i: &RistrettoPoint,
-    a_i_point: &RistrettoPoint, // A
-    b_i: &RistrettoPoint,        // B
+    a_i_point: &RistrettoPoint,
     g0: &RistrettoPoint,
     g1: &RistrettoPoint,
-    rho: &[u8; 32],
-    proof: &Proof,
-) -> bool {
-    let xa = match dec_point(&proof.xa) {
-        Some(p) => p,
-        None => return false,
-    };
-    let xb = match dec_point(&proof.xb) {
-        Some(p) => p,
-        None => return false,
-    };
-    let xpk = match dec_point(&proof.xpk) {
-        Some(p) => p,
-        None => return false,
-    };
+    reveal: &NonceReveal,
+) -> Option<(RistrettoPoint, RistrettoPoint)> {
+    let point = |b: &[u8; 32]| decode_point(b).ok();
+    let scalar = |b: &[u8; 32]| decode_scalar(b).ok();
+    let (r, s) = (point(&reveal.r_point)?, point(&reveal.s_point)?);
+    let (xr, xa, xs, xpk) = (point(&reveal.xr)?, point(&reveal.xa)?, point(&reveal.xs)?, point(&reveal.xpk)?);
+    let (za, zs, zr, zu) = (scalar(&reveal.za)?, scalar(&reveal.zs)?, scalar(&reveal.zr)?, scalar(&reveal.zu)?);
 
-    let za = dec_scalar(&proof.za);
-    let zs = dec_scalar(&proof.zs);
-    let zr = dec_scalar(&proof.zr);
-    let zu = dec_scalar(&proof.zu);
+    let e = hnonce(reveal.i, &r, &s, a_i_point, pk_i, g0, g1, &xr, &xa, &xs, &xpk);
 
-    let h0 = f0(rho);
-    let h1 = f1(rho);
+    let g_za = par.mul_g(&za);
+    let g_zs = par.mul_g(&zs);
+    let ok = g_za == xr + r * e
+        && g_za + g0 * zr + g1 * zu == xa + a_i_point * e
+        && g_zs == xs + s * e
+        && g_zs + par.h * zr + par.v * zu == xpk + pk_i * e;
+    ok.then_some((r, s))
+}
 
-    let e = hfs(&xa, &xb, &xpk, a_i_point, b_i, pk_i, g0, g1, rho);
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::testutil::Session;
 
-    // Check:
-    // g^za g0^zr g1^zu == XA * A^e
-    let left1 = par.g * za + (*g0) * zr + (*g1) * zu;
-    let right1 = xa + (*a_i_point) * e;
+    #[test]
+    fn proof_bytes_round_trip() {
+        let proof = Session::new(3, 1).openings[0].proof.clone();
+        let back = Proof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(back.to_bytes(), proof.to_bytes());
+    }
 
-    // g^za h0^zr h1^zu == XB * B^e
-    let left2 = par.g * za + h0 * zr + h1 * zu;
-    let right2 = xb + (*b_i) * e;
+    #[test]
+    fn proof_from_bytes_rejects_non_canonical_fields() {
+        let proof = Session::new(3, 1).openings[0].proof.clone();
+        let mut bytes = proof.to_bytes();
+        bytes[31] |= 0x80; // xa
+        assert_eq!(Proof::from_bytes(&bytes).err(), Some(Error::NonCanonicalPoint));
 
-    // g^zs h^zr v^zu == Xpk * pk^e
-    let left3 = par.g * zs + par.h * zr + par.v * zu;
-    let right3 = xpk + (*pk_i) * e;
+        let mut bytes = proof.to_bytes();
+        bytes[96..128].copy_from_slice(&[0xff; 32]); // za
+        assert_eq!(Proof::from_bytes(&bytes).err(), Some(Error::NonCanonicalScalar));
+    }
+
+    #[test]
+    fn proof_round_trips_under_the_v2_transcript() {
+        let s = Session::new(3, 1);
+        let st = &s.states[0];
+        let pk = s.pk(st.i).pk_i;
+        let proof = sig_prove(&s.par, st.i, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &st.a_i,
+                              s.sk(st.i));
+        assert!(sig_verify(&s.par, st.i, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &proof));
+        // The Fig.4 equations hold with e recomputed from `hfs_v2`.
+        assert!(naive_verify(&s, st.i, st, &proof));
+
+        // The proof does not carry over to another rho.
+        let mut rho = st.rho_i;
+        rho[0] ^= 1;
+        assert!(!sig_verify(&s.par, st.i, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &rho, &proof));
+    }
+
+    #[test]
+    fn v1_proofs_verify_only_as_v1() {
+        let s = Session::new(3, 1);
+        let st = &s.states[0];
+        let pk = s.pk(st.i).pk_i;
+        let sk = s.sk(st.i);
+        let pi = prove_in::<Ristretto>(
+            &s.par.bases(),
+            ProofVersion::V1,
+            st.i,
+            &pk,
+            &st.a_i_point,
+            &st.b_i,
+            &st.g0,
+            &st.g1,
+            &st.rho_i,
+            &st.a_i,
+            (&sk.s, &sk.r, &sk.u),
+            &mut OsRng.unwrap_err(),
+        );
+        let v1 = Proof {
+            xa: enc_point(&pi.xa),
+            xb: enc_point(&pi.xb),
+            xpk: enc_point(&pi.xpk),
+            za: enc_scalar(&pi.za),
+            zs: enc_scalar(&pi.zs),
+            zr: enc_scalar(&pi.zr),
+            zu: enc_scalar(&pi.zu),
+        };
+        let check = |version, proof: &Proof| {
+            sig_verify_versioned(&s.par, version, st.i, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, proof)
+        };
+        assert!(check(ProofVersion::V1, &v1));
+        assert!(!check(ProofVersion::V2, &v1));
+        assert!(!sig_verify(&s.par, st.i, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &v1));
 
-    left1 == right1 && left2 == right2 && left3 == right3
+        let v2 = &s.openings[0].proof;
+        assert!(check(ProofVersion::V2, v2));
+        assert!(!check(ProofVersion::V1, v2));
+    }
+
+    #[test]
+    fn proof_from_the_original_hfs_verifies_as_v1() {
+        fn hex(s: &str) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            for (o, i) in out.iter_mut().zip((0..s.len()).step_by(2)) {
+                *o = u8::from_str_radix(&s[i..i + 2], 16).unwrap();
+            }
+            out
+        }
+
+        // Made by the first release's `sig_prove` (flat HFS, no signer id)
+        // for sk = (5, 7, 11), a = 13, rho = 9^32, g0 = 17g, g1 = 19g.
+        let par = crate::keygen::setup(3, 1).unwrap();
+        let (s, r, u, a) = (Scalar::from(5u64), Scalar::from(7u64), Scalar::from(11u64), Scalar::from(13u64));
+        let rho = [9u8; 32];
+        let (g0, g1) = (par.g * Scalar::from(17u64), par.g * Scalar::from(19u64));
+        let pk = par.g * s + par.h * r + par.v * u;
+        let a_point = par.g * a + g0 * r + g1 * u;
+        let b_point = par.g * a + crate::hash::f0(&rho) * r + crate::hash::f1(&rho) * u;
+        let proof = Proof {
+            xa: hex("7c754dae679fdcbc3b13afbb14e2df6056ac4399660785000b1e7f80751c8076"),
+            xb: hex("20ab15cc0cf5ece783b0a910b585247a4811fc4b4cffdad864842a4a85398e34"),
+            xpk: hex("3c2ab4f0056777185b2a152bf04e70d3309baffff15a8efb5fa2efcbe0bd7042"),
+            za: hex("e9311eb9414637d1884b6848f68a7c39d02635d000944da13dc889ea9cfdff09"),
+            zs: hex("901df00f99cb637387eca0c302ee427a232e32a640170564ec3f8c17859f270f"),
+            zr: hex("470fd34dd3c5f06bd115805010020b71e4f4d5c8cb79868590b1db99d8e2200f"),
+            zu: hex("bdb3eee4e03f9d0366cee79e2bc79bcdb53d9228c58cc94f43d3fdb598488a0b"),
+        };
+        let check = |version, i| sig_verify_versioned(&par, version, i, &pk, &a_point, &b_point, &g0, &g1, &rho, &proof);
+        // V1 never bound the id, so any slot accepts it.
+        assert!(check(ProofVersion::V1, 1));
+        assert!(check(ProofVersion::V1, 2));
+        assert!(!check(ProofVersion::V2, 1));
+    }
+
+    #[test]
+    fn proof_is_bound_to_the_signer_id() {
+        let s = Session::new(3, 2);
+        let st = &s.states[1];
+        assert_eq!(st.i, 2);
+        let pk = s.pk(2).pk_i;
+        let proof = sig_prove(&s.par, 2, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &st.a_i, s.sk(2));
+
+        assert!(sig_verify(&s.par, 2, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &proof));
+        // Same statement in every other respect, claimed for slot 3.
+        assert!(!sig_verify(&s.par, 3, &pk, &st.a_i_point, &st.b_i, &st.g0, &st.g1, &st.rho_i, &proof));
+    }
+
+    /// The three Fig.4 checks term by term, as before the
+    /// multi-exponentiation rewrite, for the statement (pk, A, B).
+    fn naive_checks(
+        s: &Session,
+        i: u32,
+        (pk, a, b): (RistrettoPoint, RistrettoPoint, RistrettoPoint),
+        st: &crate::types::SignerState,
+        proof: &Proof,
+    ) -> Option<[bool; 3]> {
+        use crate::hash::{dec_scalar, f0, f1, hfs_v2};
+        let (xa, xb, xpk) = (dec_point(&proof.xa)?, dec_point(&proof.xb)?, dec_point(&proof.xpk)?);
+        let (za, zs, zr, zu) =
+            (dec_scalar(&proof.za), dec_scalar(&proof.zs), dec_scalar(&proof.zr), dec_scalar(&proof.zu));
+        let par = &s.par;
+        let (h0, h1) = (f0(&st.rho_i), f1(&st.rho_i));
+        let e = hfs_v2(i, &xa, &xb, &xpk, &a, &b, &pk, &st.g0, &st.g1, &st.rho_i);
+        Some([
+            par.g * za + st.g0 * zr + st.g1 * zu == xa + a * e,
+            par.g * za + h0 * zr + h1 * zu == xb + b * e,
+            par.g * zs + par.h * zr + par.v * zu == xpk + pk * e,
+        ])
+    }
+
+    fn naive_verify(s: &Session, i: u32, st: &crate::types::SignerState, proof: &Proof) -> bool {
+        let statement = (s.pk(i).pk_i, st.a_i_point, st.b_i);
+        naive_checks(s, i, statement, st, proof) == Some([true; 3])
+    }
+
+    #[test]
+    fn multiexp_verify_agrees_with_the_naive_checks() {
+        let s = Session::new(3, 2);
+        for (k, om) in s.openings.iter().enumerate() {
+            let st = &s.states[k];
+            let mut proofs = vec![om.proof.clone()];
+            for field in 0..7 {
+                let mut bytes = om.proof.to_bytes();
+                if field < 3 {
+                    // Another valid point: the proof's own XB/Xpk/XA rotated in.
+                    let src = 32 * ((field + 1) % 3);
+                    let p: [u8; 32] = om.proof.to_bytes()[src..src + 32].try_into().unwrap();
+                    bytes[32 * field..32 * (field + 1)].copy_from_slice(&p);
+                } else {
+                    bytes[32 * field] ^= 1;
+                }
+                proofs.push(Proof::from_bytes(&bytes).unwrap());
+            }
+            let mut unreduced = om.proof.clone();
+            unreduced.zr = [0xff; 32];
+            proofs.push(unreduced);
+
+            for (n, proof) in proofs.iter().enumerate() {
+                let fast = sig_verify(&s.par, st.i, &s.pk(st.i).pk_i, &st.a_i_point, &st.b_i, &st.g0, &st.g1,
+                                      &st.rho_i, proof);
+                assert_eq!(fast, naive_verify(&s, st.i, st, proof));
+                assert_eq!(fast, n == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn failing_any_single_equation_rejects() {
+        let s = Session::new(3, 2);
+        let st = &s.states[0];
+        let i = st.i;
+        let honest = (s.pk(i).pk_i, st.a_i_point, st.b_i);
+        for k in 0..3 {
+            // Prove with the honest witness for a statement with one point
+            // moved: only the equation that point appears in can fail.
+            let mut points = [honest.1, honest.2, honest.0];
+            points[k] += s.par.g;
+            let [a, b, pk] = points;
+            let proof = sig_prove(&s.par, i, &pk, &a, &b, &st.g0, &st.g1, &st.rho_i, &st.a_i, s.sk(i));
+
+            let mut expected = [true; 3];
+            expected[k] = false;
+            assert_eq!(naive_checks(&s, i, (pk, a, b), st, &proof), Some(expected));
+            assert!(!sig_verify(&s.par, i, &pk, &a, &b, &st.g0, &st.g1, &st.rho_i, &proof));
+        }
+    }
+
+    fn items(s: &Session) -> Vec<SigVerifyItem<'_>> {
+        s.states
+            .iter()
+            .zip(&s.openings)
+            .map(|(st, om)| (st.i, s.pk(st.i).pk_i, st.a_i_point, st.b_i, st.g0, st.g1, st.rho_i, &om.proof))
+            .collect()
+    }
+
+    #[test]
+    fn batch_verify_agrees_with_per_proof_verify() {
+        let s = Session::new(4, 2);
+        let mut forged = s.openings[1].proof.clone();
+        forged.zs = s.openings[0].proof.zs;
+
+        let honest = items(&s);
+        assert!(sig_verify_batch(&s.par, &honest));
+        for k in 0..honest.len() {
+            let mut mixed = honest.clone();
+            mixed[k].7 = &forged;
+            let per_proof = mixed.iter().all(|&(i, pk, a, b, g0, g1, ref rho, proof)| {
+                sig_verify(&s.par, i, &pk, &a, &b, &g0, &g1, rho, proof)
+            });
+            assert!(!per_proof);
+            assert_eq!(sig_verify_batch(&s.par, &mixed), per_proof);
+        }
+        assert!(!sig_verify_batch(&s.par, &[]));
+    }
 }
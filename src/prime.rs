@@ -1,24 +1,40 @@
+use std::fmt;
+use std::sync::OnceLock;
+
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
-use rand::RngCore;
+use rand::{CryptoRng, RngCore};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrimeError {
+    /// The requested size is below the generator's minimum.
+    TooFewBits { bits: usize, min: usize },
+    /// Zero Miller–Rabin rounds were requested, which would accept any
+    /// candidate that survives trial division.
+    NoRounds,
+}
 
-fn modexp(mut base: BigUint, mut exp: BigUint, modu: &BigUint) -> BigUint {
-    let mut res = BigUint::one();
-    base %= modu;
-    while exp > BigUint::zero() {
-        if &exp & BigUint::one() == BigUint::one() {
-            res = (&res * &base) % modu;
+impl fmt::Display for PrimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrimeError::TooFewBits { bits, min } => write!(f, "{} bits requested, need at least {}", bits, min),
+            PrimeError::NoRounds => write!(f, "at least one Miller-Rabin round is required"),
         }
-        base = (&base * &base) % modu;
-        exp >>= 1;
     }
-    res
 }
 
-// Miller–Rabin primality test
-fn is_probable_prime(n: &BigUint, k: usize, rng: &mut impl RngCore) -> bool {
+impl std::error::Error for PrimeError {}
+
+/// Miller–Rabin with `k` random bases, after trial division by the odd
+/// primes below 2000. A composite passes with probability at most 4^-k.
+pub fn is_probable_prime(n: &BigUint, k: usize, rng: &mut impl RngCore) -> bool {
     if *n < BigUint::from(4u32) { return *n == BigUint::from(2u32) || *n == BigUint::from(3u32); }
     if n % 2u32 == BigUint::zero() { return false; }
+    for &sp in small_primes() {
+        if n % sp == BigUint::zero() {
+            return *n == BigUint::from(sp);
+        }
+    }
 
     let mut d = n - 1u32;
     let mut s = 0;
@@ -28,8 +44,8 @@ fn is_probable_prime(n: &BigUint, k: usize, rng: &mut impl RngCore) -> bool {
     }
 
     for _ in 0..k {
-        let a = BigUint::from(2u32) + BigUint::from(rng.next_u64()) % (n - 3u32);
-        let mut x = modexp(a, d.clone(), n);
+        let a = BigUint::from(2u32) + random_below(&(n - 3u32), rng);
+        let mut x = a.modpow(&d, n);
         if x == BigUint::one() || x == n - 1u32 { continue; }
         let mut composite = true;
         for _ in 0..s - 1 {
@@ -44,14 +60,166 @@ fn is_probable_prime(n: &BigUint, k: usize, rng: &mut impl RngCore) -> bool {
     true
 }
 
-pub fn random_prime(bits: usize, rng: &mut impl RngCore) -> BigUint {
+// Uniform-ish in [0, bound): 64 bits more than bound, reduced, so the
+// modulo bias is below 2^-64.
+fn random_below(bound: &BigUint, rng: &mut impl RngCore) -> BigUint {
+    let mut buf = vec![0u8; bound.bits().div_ceil(8) as usize + 8];
+    rng.fill_bytes(&mut buf);
+    BigUint::from_bytes_be(&buf) % bound
+}
+
+// `bits` fresh random bits with the top and bottom one set.
+fn random_odd_candidate(bits: usize, rng: &mut impl CryptoRng) -> BigUint {
+    let mut buf = vec![0u8; bits.div_ceil(8)];
+    rng.fill_bytes(&mut buf);
+    let mut candidate = BigUint::from_bytes_be(&buf) >> (buf.len() * 8 - bits);
+    candidate.set_bit(bits as u64 - 1, true);
+    candidate.set_bit(0, true);
+    candidate
+}
+
+/// Random `bits`-bit prime; every bit below the top one is drawn from `rng`.
+/// Fails with PrimeError::TooFewBits below 2 bits.
+pub fn random_prime(bits: usize, rng: &mut impl CryptoRng) -> Result<BigUint, PrimeError> {
+    random_prime_with_rounds(bits, 40, rng)
+}
+
+/// `random_prime` with `rounds` Miller–Rabin rounds per candidate; fails
+/// with PrimeError::NoRounds if `rounds` is 0.
+pub fn random_prime_with_rounds(bits: usize, rounds: usize, rng: &mut impl CryptoRng) -> Result<BigUint, PrimeError> {
+    if bits < 2 {
+        return Err(PrimeError::TooFewBits { bits, min: 2 });
+    }
+    if rounds == 0 {
+        return Err(PrimeError::NoRounds);
+    }
     loop {
-        let mut candidate = BigUint::from(rng.next_u64());
-        candidate |= BigUint::one() << (bits - 1);
-        candidate |= BigUint::one();
+        let candidate = random_odd_candidate(bits, rng);
 
-        if is_probable_prime(&candidate, 40, rng) {
-            return candidate;
+        if is_probable_prime(&candidate, rounds, rng) {
+            return Ok(candidate);
         }
     }
 }
+
+// Odd primes below 2000, for trial division ahead of Miller–Rabin.
+fn small_primes() -> &'static [u32] {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        (3u32..2000)
+            .step_by(2)
+            .filter(|&k| (3..).step_by(2).take_while(|d| d * d <= k).all(|d| k % d != 0))
+            .collect()
+    })
+}
+
+/// Random `bits`-bit safe prime p = 2p' + 1 with p' prime as well. The top
+/// two bits are set, so a product of two such primes has exactly 2*bits.
+///
+/// Candidates p' are sieved so that neither p' nor 2p'+1 has a factor
+/// below 2000, then screened with one Miller–Rabin round each before the
+/// full 40. Expect this to be slow for RSA sizes (seconds to minutes).
+/// Fails with PrimeError::TooFewBits below 8 bits.
+pub fn random_safe_prime(bits: usize, rng: &mut impl CryptoRng) -> Result<BigUint, PrimeError> {
+    if bits < 8 {
+        return Err(PrimeError::TooFewBits { bits, min: 8 });
+    }
+    let sieve = small_primes();
+    let mut buf = vec![0u8; (bits - 1).div_ceil(8)];
+    loop {
+        rng.fill_bytes(&mut buf);
+        // p' is odd with its top two of bits-1 bits set; p = 2p'+1 then
+        // has the top two of `bits` set.
+        let mut q = BigUint::from_bytes_be(&buf) >> (buf.len() * 8 - (bits - 1));
+        q.set_bit(bits as u64 - 2, true);
+        q.set_bit(bits as u64 - 3, true);
+        q |= BigUint::one();
+        let p = (&q << 1) + 1u32;
+
+        let sieved = sieve.iter().all(|&sp| {
+            (&q % sp != BigUint::zero() || q == BigUint::from(sp))
+                && (&p % sp != BigUint::zero() || p == BigUint::from(sp))
+        });
+        if !sieved {
+            continue;
+        }
+        if is_probable_prime(&q, 1, rng)
+            && is_probable_prime(&p, 1, rng)
+            && is_probable_prime(&q, 40, rng)
+            && is_probable_prime(&p, 40, rng)
+        {
+            return Ok(p);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_prime_has_a_prime_half() {
+        let mut rng = rand::rng();
+        for bits in [8, 64, 128] {
+            let p = random_safe_prime(bits, &mut rng).unwrap();
+            assert_eq!(p.bits(), bits as u64);
+            assert!(is_probable_prime(&p, 40, &mut rng));
+            assert!(is_probable_prime(&((&p - 1u32) >> 1), 40, &mut rng));
+        }
+        assert_eq!(random_safe_prime(7, &mut rng), Err(PrimeError::TooFewBits { bits: 7, min: 8 }));
+    }
+
+    #[test]
+    fn random_primes_vary_across_their_full_width() {
+        let mut rng = rand::rng();
+        let primes: Vec<BigUint> = (0..32).map(|_| random_prime(256, &mut rng).unwrap()).collect();
+        for p in &primes {
+            assert_eq!(p.bits(), 256);
+            assert!(p.bit(0));
+        }
+        // Each inner bit is set in some prime and clear in another; a
+        // fixed bit would survive 32 draws with probability 2^-31.
+        for bit in 1..255 {
+            let set = primes.iter().filter(|p| p.bit(bit)).count();
+            assert!(0 < set && set < primes.len(), "bit {} never varies", bit);
+        }
+    }
+
+    #[test]
+    fn random_prime_rejects_degenerate_requests() {
+        let mut rng = rand::rng();
+        for bits in [0, 1] {
+            assert_eq!(random_prime(bits, &mut rng), Err(PrimeError::TooFewBits { bits, min: 2 }));
+        }
+        assert_eq!(random_prime_with_rounds(64, 0, &mut rng), Err(PrimeError::NoRounds));
+        let p = random_prime(2, &mut rng).unwrap();
+        assert!(p == BigUint::from(2u32) || p == BigUint::from(3u32));
+        assert_eq!(random_prime_with_rounds(64, 1, &mut rng).unwrap().bits(), 64);
+    }
+
+    #[test]
+    fn carmichael_numbers_are_rejected() {
+        let mut rng = rand::rng();
+        // 561 = 3 * 11 * 17 falls to trial division. 2221 * 4441 * 6661 has
+        // no factor below 2000 and passes Fermat's test to every coprime
+        // base, so only Miller–Rabin catches it.
+        let big = BigUint::from(65_700_513_721u64);
+        assert_eq!(BigUint::from(2u32).modpow(&(&big - 1u32), &big), BigUint::one());
+        for n in [BigUint::from(561u32), big] {
+            assert!(!is_probable_prime(&n, 40, &mut rng));
+        }
+        assert!(is_probable_prime(&BigUint::from(6661u32), 40, &mut rng));
+    }
+
+    #[test]
+    fn miller_rabin_bases_span_the_whole_range() {
+        let mut rng = rand::rng();
+        let bound = (BigUint::one() << 1000u32) - 3u32;
+        let draws: Vec<BigUint> = (0..64).map(|_| random_below(&bound, &mut rng)).collect();
+        assert!(draws.iter().all(|a| *a < bound));
+        // Uniform draws miss the top byte 64 times with probability 2^-512
+        // and leave bit 500 clear with probability 2^-64.
+        assert!(draws.iter().any(|a| a.bits() > 992));
+        assert!(draws.iter().any(|a| a.bit(500)));
+    }
+}
@@ -1,21 +1,144 @@
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::fmt;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::Identity;
+use curve25519_dalek::traits::{Identity, IsIdentity, VartimeMultiscalarMul};
+use hmac::{Hmac, Mac};
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::TryRngCore;
+use rand::CryptoRng;
+use sha2::Sha512;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
 
-use crate::hash::{enc_point, enc_scalar, g0, g1, hcom, hsig};
-use crate::nizk::{sig_prove, sig_verify, Proof};
-use crate::shamir::lagrange_coeff;
+use crate::error::Error;
+use crate::group::{Bases, Group, Ristretto};
+use crate::hash::{
+    dec_point, dec_scalar_canonical, decode_scalar, enc_point, enc_scalar, g0, g1, hahat, hahat_session, hcom, hsig,
+    hsig_in, ChallengeHasher, MessageHasher,
+};
+#[cfg(feature = "std")]
+use crate::nizk::sig_verify;
+use crate::nizk::{
+    nonce_prove_with_rng, nonce_verify, sig_prove_with_rng, sig_verify_batch_with_rng, sig_verify_with_rng, Proof,
+    SigVerifyItem,
+};
+use crate::shamir::{interpolate_points, invalid_id, lagrange_coeff, LagrangeTable};
 use crate::types::{
-    CommitmentMessage, OpeningMessage, Params, PartialSignature, PublicKeyShare, SecretKeyShare,
-    Signature, SignerState,
+    CommitmentMessage, NonceReveal, OpeningMessage, Params, PartialSignature, PublicKeyShare,
+    share_for, SecretKeyShare, Signature, SignerState, VerificationKey,
 };
 
-fn dec_point(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
-    CompressedRistretto(*bytes).decompress()
+type HmacSha512 = Hmac<Sha512>;
+
+/// Why Sig3 refused to sign. Variants carrying an id name the signer whose
+/// message was rejected, so the coordinator can drop it and retry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignError {
+    /// SS has fewer than t+1 signers.
+    QuorumTooSmall,
+    /// The signing signer is not in SS.
+    NotInSigningSet(u32),
+    /// SS lists this id twice, or contains the reserved id 0.
+    InvalidSigningSet(u32),
+    MissingCommitment(u32),
+    CommitmentMismatch(u32),
+    MissingPublicKeyShare(u32),
+    PointDecodeFailed(u32),
+    /// This signer's A_j or B_j is the identity (see `check_openings`).
+    IdentityPoint(u32),
+    ProofInvalid(u32),
+    /// The openings do not hold exactly one from each signer of SS: this
+    /// signer's is missing or repeated, or it is not in SS.
+    OpeningsMismatch(u32),
+    /// `sign` was not given this signer's secret key share.
+    MissingSecretKeyShare(u32),
+    /// `sign` could not combine the partials, or the result did not verify.
+    Combine(Error),
+    /// A precomputed A_hat was made for another signing set or session.
+    SessionMismatch,
+}
+
+impl SignError {
+    /// The misbehaving signer, if the failure is attributable to one.
+    pub fn signer(&self) -> Option<u32> {
+        match self {
+            SignError::QuorumTooSmall
+            | SignError::NotInSigningSet(_)
+            | SignError::InvalidSigningSet(_)
+            | SignError::MissingSecretKeyShare(_)
+            | SignError::Combine(_)
+            | SignError::SessionMismatch => None,
+            SignError::MissingCommitment(j)
+            | SignError::CommitmentMismatch(j)
+            | SignError::MissingPublicKeyShare(j)
+            | SignError::PointDecodeFailed(j)
+            | SignError::IdentityPoint(j)
+            | SignError::ProofInvalid(j)
+            | SignError::OpeningsMismatch(j) => Some(*j),
+        }
+    }
+}
+
+impl fmt::Display for SignError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignError::QuorumTooSmall => write!(f, "signing set smaller than t+1"),
+            SignError::NotInSigningSet(i) => write!(f, "signer {} is not in the signing set", i),
+            SignError::InvalidSigningSet(j) => {
+                write!(f, "signing set has a duplicate or zero id {}", j)
+            }
+            SignError::MissingCommitment(j) => write!(f, "no commitment from signer {}", j),
+            SignError::CommitmentMismatch(j) => write!(f, "commitment mismatch for signer {}", j),
+            SignError::MissingPublicKeyShare(j) => write!(f, "no public key share for signer {}", j),
+            SignError::PointDecodeFailed(j) => write!(f, "undecodable point from signer {}", j),
+            SignError::IdentityPoint(j) => write!(f, "identity A_j or B_j from signer {}", j),
+            SignError::ProofInvalid(j) => write!(f, "invalid proof from signer {}", j),
+            SignError::OpeningsMismatch(j) => write!(f, "not exactly one opening from signer {}", j),
+            SignError::MissingSecretKeyShare(j) => write!(f, "no secret key share for signer {}", j),
+            SignError::Combine(e) => write!(f, "combine failed: {}", e),
+            SignError::SessionMismatch => write!(f, "precomputed A_hat is for another session"),
+        }
+    }
+}
+
+impl core::error::Error for SignError {}
+
+/// An accountability event from the signing rounds, naming the signer at
+/// fault.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProtocolEvent {
+    /// This signer's opening failed a check before its proof was looked
+    /// at; `reason` is the SignError it would cause on its own.
+    OpeningRejected { signer: u32, reason: SignError },
+    /// This signer's Sig2 NIZK did not verify.
+    ProofFailed { signer: u32 },
 }
 
-fn dec_scalar(bytes: &[u8; 32]) -> Scalar {
-    Scalar::from_bytes_mod_order(*bytes)
+/// Sink for `ProtocolEvent`s, for a coordinator that wants an audit record
+/// of a failed round without changing what the rounds return.
+///
+/// On a rejected round a `SigningSession` built `with_log` reports every
+/// bad opening from `sig3` and `sig3_batch`, in `openings` order, not only
+/// the first one they return. `()` is the no-op sink and a
+/// `Vec<ProtocolEvent>` collects the events.
+pub trait ProtocolLog {
+    /// Called once per event. The default drops it.
+    fn record(&mut self, event: ProtocolEvent) {
+        let _ = event;
+    }
+}
+
+impl ProtocolLog for () {}
+
+impl ProtocolLog for Vec<ProtocolEvent> {
+    fn record(&mut self, event: ProtocolEvent) {
+        self.push(event);
+    }
 }
 
 /// Helper: normalize mu vector as Vec<(id, mu)> sorted by id.
@@ -24,19 +147,109 @@ fn normalize_mu_vec(mut mu: Vec<(u32, [u8; 32])>) -> Vec<(u32, [u8; 32])> {
     mu
 }
 
+/// The message as the rounds read it: whole, or already absorbed by a
+/// `MessageHasher`.
+#[derive(Clone, Copy)]
+enum Msg<'a> {
+    Bytes(&'a [u8]),
+    Hashed(&'a MessageHasher),
+}
+
+impl Msg<'_> {
+    fn g0(&self, mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
+        match self {
+            Msg::Bytes(m) => g0(m, mu_vec),
+            Msg::Hashed(h) => h.g0(mu_vec),
+        }
+    }
+
+    fn g1(&self, mu_vec: &[(u32, [u8; 32])]) -> RistrettoPoint {
+        match self {
+            Msg::Bytes(m) => g1(m, mu_vec),
+            Msg::Hashed(h) => h.g1(mu_vec),
+        }
+    }
+
+    /// A hashed message carries its own SS, which its callers pass as `ss`.
+    fn hsig(&self, a_hat: &RistrettoPoint, pk: &RistrettoPoint, ss: &[u32]) -> Scalar {
+        match self {
+            Msg::Bytes(m) => hsig(a_hat, pk, ss, m),
+            Msg::Hashed(h) => h.hsig(a_hat, pk),
+        }
+    }
+}
+
 /// Sig1: commitment phase.
 /// - sample rho_i (32 bytes) and a_i (scalar)
 /// - compute B_i = g*a_i + F0(rho_i)*r(i) + F1(rho_i)*u(i)
 /// - mu_i = Hcom(i, rho_i, B_i)
+#[cfg(feature = "std")]
 pub fn sig1(par: &Params, i: u32, sk_i: &SecretKeyShare) -> (CommitmentMessage, SignerState) {
-    let a_i = crate::randutil::random_scalar();
+    sig1_with_rng(par, i, sk_i, &mut OsRng.unwrap_err())
+}
+
+/// `sig1` drawing a_i and rho_i from `rng`. Never reuse a seed across
+/// sessions: repeating a_i under a different challenge reveals s(i).
+pub fn sig1_with_rng(
+    par: &Params,
+    i: u32,
+    sk_i: &SecretKeyShare,
+    rng: &mut impl CryptoRng,
+) -> (CommitmentMessage, SignerState) {
+    let a_i = crate::randutil::random_scalar_with_rng(rng);
+
+    let mut rho_i = [0u8; 32];
+    rng.fill_bytes(&mut rho_i);
 
-    let rho_i: [u8; 32] = rand::random();
+    sig1_from_nonce(par, i, sk_i, a_i, rho_i)
+}
+
+/// Deterministic Sig1 (RFC 6979 style): a_i and rho_i are HMAC-SHA512
+/// outputs keyed by the share (s || r || u) over (i, message, counter), so
+/// a broken RNG cannot repeat a nonce.
+///
+/// The same (message, counter) always gives the same a_i, but the challenge
+/// also depends on the other signers' commitments, which change between
+/// sessions. Signing one message twice with one counter under a different
+/// signing set or peer nonces therefore reveals s(i): the counter must be
+/// unique per session for this share (e.g. a persisted monotonic counter),
+/// not just per message.
+pub fn sig1_deterministic(
+    par: &Params,
+    i: u32,
+    sk_i: &SecretKeyShare,
+    message: &[u8],
+    counter: u64,
+) -> (CommitmentMessage, SignerState) {
+    let key = sk_i.to_bytes();
+    let derive = |label: &[u8]| -> [u8; 64] {
+        let mut m = <HmacSha512 as Mac>::new_from_slice(&key[..]).expect("HMAC takes any key length");
+        m.update(b"Gargos::Nonce");
+        m.update(label);
+        m.update(&i.to_le_bytes());
+        m.update(&counter.to_le_bytes());
+        m.update(&(message.len() as u64).to_le_bytes());
+        m.update(message);
+        m.finalize().into_bytes().into()
+    };
 
+    let a_i = Scalar::from_bytes_mod_order_wide(&Zeroizing::new(derive(b"a")));
+    let mut rho_i = [0u8; 32];
+    rho_i.copy_from_slice(&derive(b"rho")[..32]);
 
+    sig1_from_nonce(par, i, sk_i, a_i, rho_i)
+}
+
+fn sig1_from_nonce(
+    par: &Params,
+    i: u32,
+    sk_i: &SecretKeyShare,
+    a_i: Scalar,
+    rho_i: [u8; 32],
+) -> (CommitmentMessage, SignerState) {
     let h0 = crate::hash::f0(&rho_i);
     let h1 = crate::hash::f1(&rho_i);
-    let b_i = par.g * a_i + h0 * sk_i.r + h1 * sk_i.u;
+    let b_i = par.mul_g(&a_i) + h0 * sk_i.r + h1 * sk_i.u;
 
     let mu_i = hcom(i, &rho_i, &b_i);
 
@@ -54,153 +267,1963 @@ pub fn sig1(par: &Params, i: u32, sk_i: &SecretKeyShare) -> (CommitmentMessage,
     (CommitmentMessage { i, mu_i }, st)
 }
 
-/// Sig2: opening phase.
-/// - compute G0,G1 from (m, mu_vec)
-/// - compute A_i = g*a_i + G0*r(i) + G1*u(i)
-/// - proof π_i binds (pk_i, A_i, B_i, rho_i, G0, G1)
-pub fn sig2(
+/// Offline Sig1: `count` first-round states for signer i, ahead of any
+/// message (a_i, rho_i and B_i do not depend on it). Put them in a
+/// `PresignPool` and take one per session.
+///
+/// Each state is single-use: its a_i must go into exactly one Sig2. Signing
+/// two messages with the same a_i reveals s(i).
+#[cfg(feature = "std")]
+pub fn sig1_preprocess(par: &Params, i: u32, sk_i: &SecretKeyShare, count: usize) -> Vec<SignerState> {
+    sig1_preprocess_with_rng(par, i, sk_i, count, &mut OsRng.unwrap_err())
+}
+
+/// `sig1_preprocess` drawing every state's nonces from `rng`.
+pub fn sig1_preprocess_with_rng(
     par: &Params,
-    message: &[u8],
     i: u32,
-    mu_vec: &[(u32, [u8; 32])],
-    pk_i: &PublicKeyShare,
     sk_i: &SecretKeyShare,
-    st: &SignerState,
-) -> (OpeningMessage, SignerState) {
-    let mu_vec = normalize_mu_vec(mu_vec.to_vec());
-    let g0p = g0(message, &mu_vec);
-    let g1p = g1(message, &mu_vec);
-
-    let a_i_point = par.g * st.a_i + g0p * sk_i.r + g1p * sk_i.u;
-
-    let proof: Proof = sig_prove(
-        par,
-        &pk_i.pk_i,
-        &a_i_point,
-        &st.b_i,
-        &g0p,
-        &g1p,
-        &st.rho_i,
-        &st.a_i,
-        sk_i,
-    );
-
-    let msg = OpeningMessage {
-        i,
-        // kept only for debugging symmetry; not required by protocol
-        a_i: enc_scalar(&st.a_i),
-        a_point: enc_point(&a_i_point),
-        rho_i: st.rho_i,
-        b_point: enc_point(&st.b_i),
-        proof,
-    };
+    count: usize,
+    rng: &mut impl CryptoRng,
+) -> Vec<SignerState> {
+    (0..count).map(|_| sig1_with_rng(par, i, sk_i, rng).1).collect()
+}
 
-    let st2 = SignerState {
-        i,
-        a_i: st.a_i,
-        rho_i: st.rho_i,
-        b_i: st.b_i,
-        a_i_point,
-        mu_vec,
-        g0: g0p,
-        g1: g1p,
-    };
+/// Presigned first-round states for one signer. `take` moves a state out
+/// (with its Sig1 commitment to broadcast), so the pool itself never hands
+/// the same a_i out twice; callers must not clone a taken state into a
+/// second session either.
+#[derive(Debug, Default)]
+pub struct PresignPool {
+    states: VecDeque<SignerState>,
+}
+
+impl PresignPool {
+    pub fn new(states: Vec<SignerState>) -> PresignPool {
+        PresignPool { states: states.into() }
+    }
+
+    /// Add freshly preprocessed states.
+    pub fn refill(&mut self, states: Vec<SignerState>) {
+        self.states.extend(states);
+    }
 
-    (msg, st2)
+    /// Next unused state and its Sig1 message; feed the state to
+    /// `SigningSession::sig2`. None once the pool is empty.
+    pub fn take(&mut self) -> Option<(CommitmentMessage, SignerState)> {
+        let st = self.states.pop_front()?;
+        let mu_i = hcom(st.i, &st.rho_i, &st.b_i);
+        Some((CommitmentMessage { i: st.i, mu_i }, st))
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
 }
 
-/// Sig3: share-signing phase (practical).
-/// Checks:
-/// 1) commitment correctness: mu_j == Hcom(j, rho_j, B_j)
-/// 2) NIZK verifies for each signer j
-/// Then compute:
-/// - A_hat = Σ_j L_{j,SS} * A_j
-/// - c = Hsig(A_hat, pk_joint, m)
-/// - z_i = L_{i,SS} * (a_i + c*s(i))
-pub fn sig3_with_pk(
-    par: &Params,
-    message: &[u8],
-    ss: &[u32],
-    i: u32,
-    pk_joint: &RistrettoPoint,
-    pk_shares: &[(u32, RistrettoPoint)],
-    sk_i: &SecretKeyShare,
-    st: &SignerState,
-    commitments: &[(u32, [u8; 32])],
-    openings: &[OpeningMessage],
-) -> Option<PartialSignature> {
-    let mu_vec = normalize_mu_vec(commitments.to_vec());
-    let g0p = g0(message, &mu_vec);
-    let g1p = g1(message, &mu_vec);
+/// Where a `SigningSession` draws its randomness: the OS, or an rng its
+/// caller supplied.
+enum RngSource<'a> {
+    #[cfg(feature = "std")]
+    Os,
+    Given(&'a mut dyn CryptoRng),
+}
 
-    // verify each opening
-    for om in openings {
-        let bj = dec_point(&om.b_point)?;
-        let muj_expected = hcom(om.i, &om.rho_i, &bj);
+impl rand::RngCore for RngSource<'_> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            #[cfg(feature = "std")]
+            RngSource::Os => OsRng.unwrap_err().next_u32(),
+            RngSource::Given(rng) => rng.next_u32(),
+        }
+    }
 
-        let muj = mu_vec
-            .iter()
-            .find(|(id, _)| *id == om.i)
-            .map(|x| x.1)?;
-        if muj != muj_expected {
-            return None;
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            #[cfg(feature = "std")]
+            RngSource::Os => OsRng.unwrap_err().next_u64(),
+            RngSource::Given(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        match self {
+            #[cfg(feature = "std")]
+            RngSource::Os => OsRng.unwrap_err().fill_bytes(dst),
+            RngSource::Given(rng) => rng.fill_bytes(dst),
         }
+    }
+}
+
+impl CryptoRng for RngSource<'_> {}
+
+/// What every signer's Sig2 and Sig3 in one signing session share: the
+/// parameters, the message, SS, pk_joint and the pk_j of the signers, plus
+/// the rng the rounds draw from and an optional `ProtocolLog`.
+///
+/// Build one per session and call the rounds on it; only the per-signer
+/// inputs (i, sk_i, the state, the messages received) are passed per call.
+/// Under `std` the rng defaults to the OS; the `_with_rng` constructors
+/// take one instead, and are the only ones without `std`.
+pub struct SigningSession<'a> {
+    par: &'a Params,
+    message: Msg<'a>,
+    ss: &'a [u32],
+    pk_joint: &'a RistrettoPoint,
+    pk_shares: &'a [(u32, RistrettoPoint)],
+    rng: RngSource<'a>,
+    log: Option<&'a mut dyn ProtocolLog>,
+}
+
+impl<'a> SigningSession<'a> {
+    /// A session signing `message` with SS `ss`, drawing from the OS rng.
+    #[cfg(feature = "std")]
+    pub fn new(
+        par: &'a Params,
+        message: &'a [u8],
+        ss: &'a [u32],
+        pk_joint: &'a RistrettoPoint,
+        pk_shares: &'a [(u32, RistrettoPoint)],
+    ) -> Self {
+        Self::with_source(par, Msg::Bytes(message), ss, pk_joint, pk_shares, RngSource::Os)
+    }
 
-        let aj = dec_point(&om.a_point)?;
-        let pkj = pk_shares
+    /// `new` drawing the NIZK nonces and proof-batching weights from `rng`.
+    pub fn new_with_rng(
+        par: &'a Params,
+        message: &'a [u8],
+        ss: &'a [u32],
+        pk_joint: &'a RistrettoPoint,
+        pk_shares: &'a [(u32, RistrettoPoint)],
+        rng: &'a mut impl CryptoRng,
+    ) -> Self {
+        Self::with_source(par, Msg::Bytes(message), ss, pk_joint, pk_shares, RngSource::Given(rng))
+    }
+
+    /// A session over a message streamed into `message` (see
+    /// `MessageHasher`), for messages too large to buffer. SS is the
+    /// hasher's signing set; every round gives the same result as over the
+    /// whole message.
+    #[cfg(feature = "std")]
+    pub fn hashed(
+        par: &'a Params,
+        message: &'a MessageHasher,
+        pk_joint: &'a RistrettoPoint,
+        pk_shares: &'a [(u32, RistrettoPoint)],
+    ) -> Self {
+        Self::with_source(par, Msg::Hashed(message), message.signing_set(), pk_joint, pk_shares, RngSource::Os)
+    }
+
+    /// `hashed` drawing its randomness from `rng`.
+    pub fn hashed_with_rng(
+        par: &'a Params,
+        message: &'a MessageHasher,
+        pk_joint: &'a RistrettoPoint,
+        pk_shares: &'a [(u32, RistrettoPoint)],
+        rng: &'a mut impl CryptoRng,
+    ) -> Self {
+        let ss = message.signing_set();
+        Self::with_source(par, Msg::Hashed(message), ss, pk_joint, pk_shares, RngSource::Given(rng))
+    }
+
+    fn with_source(
+        par: &'a Params,
+        message: Msg<'a>,
+        ss: &'a [u32],
+        pk_joint: &'a RistrettoPoint,
+        pk_shares: &'a [(u32, RistrettoPoint)],
+        rng: RngSource<'a>,
+    ) -> Self {
+        SigningSession { par, message, ss, pk_joint, pk_shares, rng, log: None }
+    }
+
+    /// Report every rejected opening to `log` (see `ProtocolLog`); what the
+    /// rounds return is the same.
+    pub fn with_log(mut self, log: &'a mut impl ProtocolLog) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    fn record(&mut self, event: ProtocolEvent) {
+        if let Some(log) = self.log.as_deref_mut() {
+            log.record(event);
+        }
+    }
+
+    /// Sig2: opening phase.
+    /// - compute G0,G1 from (m, mu_vec)
+    /// - compute A_i = g*a_i + G0*r(i) + G1*u(i)
+    /// - proof π_i binds (pk_i, A_i, B_i, rho_i, G0, G1)
+    ///
+    /// Self-check: the opening (rho_i, B_i) held in `st` must match the mu_i
+    /// this signer broadcast in Sig1 (its own entry in `mu_vec`). `mu_vec`
+    /// arrives from the network, so a missing entry yields
+    /// SignError::MissingCommitment(i) and a wrong one (or a state for
+    /// another signer) SignError::CommitmentMismatch(i), rather than an
+    /// opening every honest peer would reject.
+    pub fn sig2(
+        &mut self,
+        i: u32,
+        mu_vec: &[(u32, [u8; 32])],
+        pk_i: &PublicKeyShare,
+        sk_i: &SecretKeyShare,
+        st: &SignerState,
+    ) -> Result<(OpeningMessage, SignerState), SignError> {
+        let par = self.par;
+        let mu_vec = normalize_mu_vec(mu_vec.to_vec());
+
+        let own_mu = mu_vec
             .iter()
-            .find(|(id, _)| *id == om.i)
-            .map(|x| x.1)?;
+            .find(|(id, _)| *id == i)
+            .map(|x| x.1)
+            .ok_or(SignError::MissingCommitment(i))?;
+        if st.i != i || !bool::from(own_mu.ct_eq(&hcom(i, &st.rho_i, &st.b_i))) {
+            return Err(SignError::CommitmentMismatch(i));
+        }
+
+        let g0p = self.message.g0(&mu_vec);
+        let g1p = self.message.g1(&mu_vec);
+
+        let a_i_point = par.mul_g(&st.a_i) + g0p * sk_i.r + g1p * sk_i.u;
+
+        let proof: Proof = sig_prove_with_rng(
+            par,
+            i,
+            &pk_i.pk_i,
+            &a_i_point,
+            &st.b_i,
+            &g0p,
+            &g1p,
+            &st.rho_i,
+            &st.a_i,
+            sk_i,
+            &mut self.rng,
+        );
 
-        let ok = sig_verify(par, &pkj, &aj, &bj, &g0p, &g1p, &om.rho_i, &om.proof);
-        if !ok {
-            return None;
+        let msg = OpeningMessage {
+            i,
+            a_point: enc_point(&a_i_point),
+            rho_i: st.rho_i,
+            b_point: enc_point(&st.b_i),
+            proof,
+        };
+
+        let st2 = SignerState {
+            i,
+            a_i: st.a_i,
+            rho_i: st.rho_i,
+            b_i: st.b_i,
+            a_i_point,
+            mu_vec,
+            g0: g0p,
+            g1: g1p,
+        };
+
+        Ok((msg, st2))
+    }
+
+    /// Sig3: share-signing phase (practical).
+    /// Checks:
+    /// 1) commitment correctness: mu_j == Hcom(j, rho_j, B_j)
+    /// 2) NIZK verifies for each signer j
+    ///
+    /// Then compute:
+    /// - A_hat = Σ_j L_{j,SS} * A_j
+    /// - c = Hsig(SS, m, A_hat, pk_joint)
+    /// - z_i = L_{i,SS} * (a_i + c*s(i))
+    ///
+    /// A rejected opening yields the SignError naming its sender; with a log
+    /// attached, every rejected opening is reported to it, in `openings`
+    /// order.
+    pub fn sig3(
+        &mut self,
+        i: u32,
+        sk_i: &SecretKeyShare,
+        st: &SignerState,
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+    ) -> Result<PartialSignature, SignError> {
+        check_signing_set(self.par, self.ss, i)?;
+
+        // verify each opening
+        self.check_openings(commitments, openings)?;
+
+        let table = LagrangeTable::new(self.ss).expect("SS checked by check_signing_set");
+        let a_hat = a_hat_from(&table, openings).map_err(sign_error)?;
+        let c = self.message.hsig(&a_hat, self.pk_joint, self.ss);
+        partial(self.par, self.ss, i, sk_i, st, &a_hat, &c)
+    }
+
+    /// Sig3 checks 1) and 2) over every opening; on failure names the first
+    /// signer whose opening is rejected.
+    ///
+    /// A_j and B_j must also not be the identity. Ristretto is a prime-order
+    /// group, so every other point generates it and there are no small
+    /// subgroups to confine a share to; the cofactor of the underlying curve
+    /// is quotiented out by the encoding. The identity is the one degenerate
+    /// element left: an honest A_j = g*a_j + G0*r(j) + G1*u(j) is the
+    /// identity only with probability ~1/ℓ, and a signer sending it
+    /// contributes no nonce to A_hat, so it is screened out before the
+    /// proofs are checked.
+    fn check_openings(
+        &mut self,
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+    ) -> Result<(), SignError> {
+        let par = self.par;
+        let (items, rejected) = self.opening_items(commitments, openings);
+
+        // One batched check; only on failure go proof by proof to name the
+        // bad signers.
+        if rejected.is_none() && sig_verify_batch_with_rng(par, &items, &mut self.rng) {
+            return Ok(());
         }
+        let mut first = rejected;
+        for &(j, pkj, aj, bj, g0p, g1p, rho, proof) in &items {
+            if !sig_verify_with_rng(par, j, &pkj, &aj, &bj, &g0p, &g1p, &rho, proof, &mut self.rng) {
+                self.record(ProtocolEvent::ProofFailed { signer: j });
+                first.get_or_insert(SignError::ProofInvalid(j));
+            }
+        }
+        first.map_or(Ok(()), Err)
     }
 
-    // A_hat = Σ_j L_{j,SS} * A_j
-    let mut a_hat = RistrettoPoint::identity();
-    for om in openings {
-        let aj = dec_point(&om.a_point)?;
-        let lj = lagrange_coeff(om.i, ss);
-        a_hat += aj * lj;
+    /// The decoding, identity and commitment checks of `check_openings`, in
+    /// opening order: the statements whose proofs are left to check, and the
+    /// first opening rejected outright. Every rejected opening is logged.
+    fn opening_items<'o>(
+        &mut self,
+        commitments: &[(u32, [u8; 32])],
+        openings: &'o [OpeningMessage],
+    ) -> (Vec<SigVerifyItem<'o>>, Option<SignError>) {
+        let mu_vec = normalize_mu_vec(commitments.to_vec());
+        let g0p = self.message.g0(&mu_vec);
+        let g1p = self.message.g1(&mu_vec);
+
+        let mut items = Vec::with_capacity(openings.len());
+        let mut first = None;
+        for om in openings {
+            match opening_item(om, &mu_vec, self.pk_shares) {
+                Ok((pkj, aj, bj)) => items.push((om.i, pkj, aj, bj, g0p, g1p, om.rho_i, &om.proof)),
+                Err(reason) => {
+                    self.record(ProtocolEvent::OpeningRejected { signer: om.i, reason: reason.clone() });
+                    first.get_or_insert(reason);
+                }
+            }
+        }
+        (items, first)
+    }
+
+    /// Sig3 for several signers held by one host (a simulation, or a server
+    /// running many shares), given as (i, sk_i, state) from their Sig2.
+    ///
+    /// The openings are checked once for all of them rather than once per
+    /// signer, each proof on its own; then every signer's z_i is computed.
+    /// With the `rayon` feature both the proof checks and the per-signer work
+    /// run on the rayon thread pool. The partial signatures come back in
+    /// ascending id order and equal those of `sig3` signer by signer, however
+    /// the threads are scheduled. A rejected opening gives the same SignError
+    /// as `sig3`: the first bad opening in `openings` order.
+    #[cfg(feature = "std")]
+    pub fn sig3_batch(
+        &mut self,
+        signers: &[(u32, &SecretKeyShare, &SignerState)],
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+    ) -> Result<Vec<PartialSignature>, SignError> {
+        #[cfg(feature = "rayon")]
+        use rayon::prelude::*;
+
+        let (par, ss) = (self.par, self.ss);
+        for &(i, _, _) in signers {
+            check_signing_set(par, ss, i)?;
+        }
+        let (items, mut first) = self.opening_items(commitments, openings);
+        let proof_ok = |&(j, pkj, aj, bj, g0p, g1p, rho, proof): &SigVerifyItem<'_>| {
+            sig_verify(par, j, &pkj, &aj, &bj, &g0p, &g1p, &rho, proof)
+        };
+        #[cfg(feature = "rayon")]
+        let ok: Vec<bool> = items.par_iter().map(proof_ok).collect();
+        #[cfg(not(feature = "rayon"))]
+        let ok: Vec<bool> = items.iter().map(proof_ok).collect();
+        for ((j, ..), _) in items.iter().zip(&ok).filter(|(_, ok)| !**ok) {
+            self.record(ProtocolEvent::ProofFailed { signer: *j });
+            first.get_or_insert(SignError::ProofInvalid(*j));
+        }
+        if let Some(e) = first {
+            return Err(e);
+        }
+
+        let table = LagrangeTable::new(ss).expect("SS checked by check_signing_set");
+        let a_hat = a_hat_from(&table, openings).map_err(sign_error)?;
+        let c = self.message.hsig(&a_hat, self.pk_joint, ss);
+        let sign_one =
+            |&(i, sk_i, st): &(u32, &SecretKeyShare, &SignerState)| partial(par, ss, i, sk_i, st, &a_hat, &c);
+        #[cfg(feature = "rayon")]
+        let shares: Result<Vec<_>, _> = signers.par_iter().map(sign_one).collect();
+        #[cfg(not(feature = "rayon"))]
+        let shares: Result<Vec<_>, _> = signers.iter().map(sign_one).collect();
+        let mut shares = shares?;
+        shares.sort_by_key(|ps| ps.i);
+        Ok(shares)
+    }
+
+    /// Sig3 with A_hat supplied by a coordinator (see `precompute_a_hat`), so
+    /// the signer does O(1) work for its challenge instead of O(|SS|).
+    ///
+    /// Preconditions: the opening checks of `sig3` are skipped, so the
+    /// coordinator is trusted to have run them and to have summed the right
+    /// A_j. What the signer does check is that `pre` was made for this SS and
+    /// for the Sig1 commitments in its own state, failing with
+    /// SignError::SessionMismatch otherwise, so an A_hat from another session
+    /// is never signed under. A signer that does not trust the coordinator
+    /// should recompute `precompute_a_hat` from the openings and compare, or
+    /// simply call `sig3`.
+    pub fn sig3_with_precomputed_a_hat(
+        &self,
+        i: u32,
+        sk_i: &SecretKeyShare,
+        st: &SignerState,
+        pre: &PrecomputedAHat,
+    ) -> Result<PartialSignature, SignError> {
+        let transcript = hahat_session(self.ss, &st.mu_vec, &pre.a_hat);
+        if pre.ss != self.ss || !bool::from(pre.transcript.ct_eq(&transcript)) {
+            return Err(SignError::SessionMismatch);
+        }
+        let c = self.message.hsig(&pre.a_hat, self.pk_joint, self.ss);
+        partial(self.par, self.ss, i, sk_i, st, &pre.a_hat, &c)
+    }
+
+    /// Check one partial signature before combining.
+    ///
+    /// z_i cannot be checked against pk_i directly: pk_i and A_i carry h/v
+    /// and G0/G1 terms that only cancel in the Lagrange sum over SS. With
+    /// signer i's `NonceReveal` (proof checked against its A_i and pk_i) this
+    /// checks
+    ///   g*z_i == L_{i,SS} * (R_i + c * S_i),  c = Hsig(SS, m, A_hat, pk_joint).
+    /// The openings are assumed to have passed Sig3's checks; false if signer
+    /// i is not in SS, has no pk_i or sent no opening, z_i is not canonical,
+    /// or the reveal is not signer i's.
+    pub fn verify_partial(
+        &self,
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+        reveal: &NonceReveal,
+        ps: &PartialSignature,
+    ) -> bool {
+        let i = ps.i;
+        if reveal.i != i {
+            return false;
+        }
+        let Some(pk_i) = share_for(self.pk_shares, i) else {
+            return false;
+        };
+        let Some(table) = LagrangeTable::new(self.ss) else {
+            return false;
+        };
+        let (Ok(a_hat), Some(li)) = (a_hat_from(&table, openings), table.get(i)) else {
+            return false;
+        };
+        let Some(a_i) = openings.iter().find(|om| om.i == i).and_then(|om| dec_point(&om.a_point)) else {
+            return false;
+        };
+        let Ok(z_i) = decode_scalar(&ps.z_i) else {
+            return false;
+        };
+
+        let mu_vec = normalize_mu_vec(commitments.to_vec());
+        let (g0p, g1p) = (self.message.g0(&mu_vec), self.message.g1(&mu_vec));
+        let Some((r_i, s_i)) = nonce_verify(self.par, pk_i, &a_i, &g0p, &g1p, reveal) else {
+            return false;
+        };
+
+        let c = self.message.hsig(&a_hat, self.pk_joint, self.ss);
+        self.par.mul_g(&z_i) == (r_i + s_i * c) * li
+    }
+
+    /// Combine, checking every partial against its signer's `NonceReveal`
+    /// first, and the result against pk_joint before returning it.
+    ///
+    /// Exactly one partial and one reveal per signer in SS are used:
+    /// Error::BadPartials names every signer of SS whose partial or reveal is
+    /// missing or repeated and every one from outside SS, and openings that
+    /// do not cover SS exactly once fail with Error::CombineFailed (see
+    /// `combine`). Each z_i is then checked with `verify_partial`, and
+    /// Error::BadPartials names every signer whose share fails or who has no
+    /// pk_i. A signature that still does not verify is rejected with
+    /// Error::InvalidSignature instead of being handed out.
+    pub fn combine_checked(
+        &self,
+        commitments: &[(u32, [u8; 32])],
+        openings: &[OpeningMessage],
+        reveals: &[NonceReveal],
+        sigshares: &[PartialSignature],
+    ) -> Result<Signature, Error> {
+        let ss = self.ss;
+        let checked = one_per_signer(ss, sigshares, |ps| ps.i).map_err(Error::BadPartials)?;
+        let reveals = one_per_signer(ss, reveals, |r| r.i).map_err(Error::BadPartials)?;
+        let checked: Vec<PartialSignature> = checked.into_iter().cloned().collect();
+        let sig = combine(ss, openings, &checked)?;
+
+        let bad: Vec<u32> = checked
+            .iter()
+            .zip(reveals)
+            .filter(|(ps, reveal)| !self.verify_partial(commitments, openings, reveal, ps))
+            .map(|(ps, _)| ps.i)
+            .collect();
+        if !bad.is_empty() {
+            return Err(Error::BadPartials(bad));
+        }
+        let c = self.message.hsig(&sig.A_hat, self.pk_joint, ss);
+        if !schnorr_check(self.par, self.pk_joint, &sig, c) {
+            return Err(Error::InvalidSignature);
+        }
+        Ok(sig)
     }
+}
 
-    let c = hsig(&a_hat, pk_joint, message);
+/// (pk_j, A_j, B_j) for one opening, once it passes the checks that need
+/// no proof.
+fn opening_item(
+    om: &OpeningMessage,
+    mu_vec: &[(u32, [u8; 32])],
+    pk_shares: &[(u32, RistrettoPoint)],
+) -> Result<(RistrettoPoint, RistrettoPoint, RistrettoPoint), SignError> {
+    let j = om.i;
+    let bj = dec_point(&om.b_point).ok_or(SignError::PointDecodeFailed(j))?;
+    if bj.is_identity() {
+        return Err(SignError::IdentityPoint(j));
+    }
+    let muj_expected = hcom(j, &om.rho_i, &bj);
 
-    let li = lagrange_coeff(i, ss);
+    let muj = mu_vec
+        .iter()
+        .find(|(id, _)| *id == j)
+        .map(|x| x.1)
+        .ok_or(SignError::MissingCommitment(j))?;
+    if !bool::from(muj.ct_eq(&muj_expected)) {
+        return Err(SignError::CommitmentMismatch(j));
+    }
+
+    let aj = dec_point(&om.a_point).ok_or(SignError::PointDecodeFailed(j))?;
+    if aj.is_identity() {
+        return Err(SignError::IdentityPoint(j));
+    }
+    let pkj = pk_shares
+        .iter()
+        .find(|(id, _)| *id == j)
+        .map(|x| x.1)
+        .ok_or(SignError::MissingPublicKeyShare(j))?;
+    Ok((pkj, aj, bj))
+}
+
+/// SS must be t+1 or more distinct nonzero ids including i (for t = n-1
+/// that is all n).
+fn check_signing_set(par: &Params, ss: &[u32], i: u32) -> Result<(), SignError> {
+    if let Some(j) = invalid_id(ss) {
+        return Err(SignError::InvalidSigningSet(j));
+    }
+    if ss.len() < par.t + 1 {
+        return Err(SignError::QuorumTooSmall);
+    }
+    if !ss.contains(&i) {
+        return Err(SignError::NotInSigningSet(i));
+    }
+    Ok(())
+}
+
+/// z_i = L_{i,SS} * (a_i + c*s(i)) for the challenge c over A_hat.
+fn partial(
+    par: &Params,
+    ss: &[u32],
+    i: u32,
+    sk_i: &SecretKeyShare,
+    st: &SignerState,
+    a_hat: &RistrettoPoint,
+    c: &Scalar,
+) -> Result<PartialSignature, SignError> {
+    check_signing_set(par, ss, i)?;
+
+    let li = lagrange_coeff(i, ss).ok_or(SignError::NotInSigningSet(i))?;
     let z_i = li * (st.a_i + c * sk_i.s);
 
-    Some(PartialSignature {
+    Ok(PartialSignature {
         i,
         z_i: enc_scalar(&z_i),
+        a_hat_digest: hahat(a_hat),
     })
 }
 
-/// Combine:
-/// - A_hat from openings
-/// - z = Σ z_i
-pub fn combine(ss: &[u32], openings: &[OpeningMessage], sigshares: &[PartialSignature]) -> Option<Signature> {
+/// Optional, after Sig2: reveal R_i = g*a_i and S_i = g*s(i) with a proof
+/// tying them to A_i and pk_i, so `SigningSession::verify_partial` can check
+/// this signer's z_i on its own.
+///
+/// Sending it is a trade-off. A_i hides g*a_i behind G0*r(i) + G1*u(i);
+/// revealing R_i gives up that masking (each signer's nonce commitment
+/// becomes public, as in FROST), in exchange for attributing a bad z_i to
+/// its sender. S_i is already public through the dealer's commitments.
+#[cfg(feature = "std")]
+pub fn reveal_nonce(par: &Params, pk_i: &PublicKeyShare, sk_i: &SecretKeyShare, st: &SignerState) -> NonceReveal {
+    reveal_nonce_with_rng(par, pk_i, sk_i, st, &mut OsRng.unwrap_err())
+}
+
+/// `reveal_nonce` drawing the proof nonces from `rng`.
+pub fn reveal_nonce_with_rng(
+    par: &Params,
+    pk_i: &PublicKeyShare,
+    sk_i: &SecretKeyShare,
+    st: &SignerState,
+    rng: &mut impl CryptoRng,
+) -> NonceReveal {
+    nonce_prove_with_rng(par, st.i, &pk_i.pk_i, &st.a_i_point, &st.g0, &st.g1, &st.a_i, sk_i, rng)
+}
+
+/// The one item of each signer in SS, in SS order. Otherwise lists every
+/// id of SS with no item or several, then every item id from outside SS.
+pub(crate) fn one_per_signer<'a, T>(
+    ss: &[u32],
+    items: &'a [T],
+    id: impl Fn(&T) -> u32,
+) -> Result<Vec<&'a T>, Vec<u32>> {
+    let mut found = Vec::with_capacity(ss.len());
+    let mut bad = Vec::new();
+    for &i in ss {
+        let mut it = items.iter().filter(|x| id(x) == i);
+        match (it.next(), it.next()) {
+            (Some(x), None) => found.push(x),
+            _ => bad.push(i),
+        }
+    }
+    for x in items {
+        let j = id(x);
+        if !ss.contains(&j) && !bad.contains(&j) {
+            bad.push(j);
+        }
+    }
+    if bad.is_empty() { Ok(found) } else { Err(bad) }
+}
+
+/// A_hat = Σ_j L_{j,SS} * A_j over the openings; None if some A_j does not
+/// decode, SS is not a set of distinct nonzero ids, or the openings do not
+/// hold exactly one per signer of SS. A coordinator can compute it once per
+/// session and hand it to `combine_with_a_hat`.
+pub fn compute_a_hat(ss: &[u32], openings: &[OpeningMessage]) -> Option<RistrettoPoint> {
+    compute_a_hat_with_table(&LagrangeTable::new(ss)?, openings)
+}
+
+/// `compute_a_hat` with the coefficients from a table built once for SS.
+pub fn compute_a_hat_with_table(
+    table: &LagrangeTable,
+    openings: &[OpeningMessage],
+) -> Option<RistrettoPoint> {
+    a_hat_from(table, openings).ok()
+}
+
+/// An A_hat precomputed by a coordinator, with the SS and session
+/// transcript hash (`hahat_session` over SS and the Sig1 commitments) that
+/// `SigningSession::sig3_with_precomputed_a_hat` checks it against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrecomputedAHat {
+    pub ss: Vec<u32>,
+    pub a_hat: RistrettoPoint,
+    pub transcript: [u8; 32],
+}
+
+/// `compute_a_hat` run once by a coordinator for
+/// `SigningSession::sig3_with_precomputed_a_hat` (and `combine_with_a_hat`,
+/// through the `a_hat` field), bound to SS and the session's Sig1
+/// `commitments`.
+///
+/// The openings are expected to have passed `SigningSession::sig3`'s
+/// checks; only their shape is checked here. Fails with Error::InvalidSignerId for a
+/// duplicate or zero id in SS, or for the first signer without exactly one
+/// opening (or from outside SS), and with Error::PointDecodeFailed if some
+/// A_j does not decode.
+pub fn precompute_a_hat(
+    ss: &[u32],
+    commitments: &[(u32, [u8; 32])],
+    openings: &[OpeningMessage],
+) -> Result<PrecomputedAHat, Error> {
+    if invalid_id(ss).is_some() {
+        return Err(invalid_signing_set(ss));
+    }
+    one_per_signer(ss, openings, |om| om.i).map_err(|bad| Error::InvalidSignerId(bad[0]))?;
+    let a_hat = compute_a_hat(ss, openings).ok_or(Error::PointDecodeFailed)?;
+    let transcript = hahat_session(ss, &normalize_mu_vec(commitments.to_vec()), &a_hat);
+    Ok(PrecomputedAHat { ss: ss.to_vec(), a_hat, transcript })
+}
+
+enum AHatError {
+    /// First id of `one_per_signer`'s complaint list.
+    Uncovered(u32),
+    PointDecodeFailed(u32),
+}
+
+fn sign_error(e: AHatError) -> SignError {
+    match e {
+        AHatError::Uncovered(j) => SignError::OpeningsMismatch(j),
+        AHatError::PointDecodeFailed(j) => SignError::PointDecodeFailed(j),
+    }
+}
+
+/// Error::InvalidSignerId for the first zero or repeated id of an SS that
+/// `LagrangeTable::new` rejected.
+fn invalid_signing_set(ss: &[u32]) -> Error {
+    Error::InvalidSignerId(invalid_id(ss).unwrap_or_default())
+}
+
+/// As `compute_a_hat`, naming the signer whose opening was rejected (its
+/// A_j does not decode, or it is not exactly one opening of SS).
+fn a_hat_from(table: &LagrangeTable, openings: &[OpeningMessage]) -> Result<RistrettoPoint, AHatError> {
+    let openings = one_per_signer(table.ids(), openings, |om| om.i).map_err(|bad| AHatError::Uncovered(bad[0]))?;
     let mut a_hat = RistrettoPoint::identity();
     for om in openings {
-        let aj = dec_point(&om.a_point)?;
-        let lj = lagrange_coeff(om.i, ss);
+        let aj = dec_point(&om.a_point).ok_or(AHatError::PointDecodeFailed(om.i))?;
+        let lj = table.get(om.i).ok_or(AHatError::Uncovered(om.i))?;
         a_hat += aj * lj;
     }
+    Ok(a_hat)
+}
 
+/// Combine:
+/// - A_hat from openings
+/// - z = Σ z_i
+///
+/// Fails with Error::InconsistentInputLengths unless there are as many
+/// openings and partials as signers in SS, with Error::InvalidSignerId if
+/// SS repeats an id or contains 0, with Error::CombineFailed if some A_j
+/// does not decode or the openings do not hold exactly one per signer of
+/// SS, with Error::BadPartials naming the signers of SS whose partial is
+/// missing or repeated and any partial from outside SS, and with
+/// Error::ChallengeDivergence naming every signer whose partial carries the
+/// digest of a different A_hat (its z_i was computed under another c).
+/// A z_i that is not a canonical scalar encoding is rejected with
+/// Error::BadPartials rather than reduced mod ℓ.
+pub fn combine(ss: &[u32], openings: &[OpeningMessage], sigshares: &[PartialSignature]) -> Result<Signature, Error> {
+    let table = LagrangeTable::new(ss).ok_or_else(|| invalid_signing_set(ss))?;
+    combine_with_table(&table, openings, sigshares)
+}
+
+/// `combine` with the coefficients from a table built once for SS.
+pub fn combine_with_table(
+    table: &LagrangeTable,
+    openings: &[OpeningMessage],
+    sigshares: &[PartialSignature],
+) -> Result<Signature, Error> {
+    let ss = table.ids();
+    if openings.len() != ss.len() || sigshares.len() != ss.len() {
+        return Err(Error::InconsistentInputLengths {
+            ss: ss.len(),
+            openings: openings.len(),
+            partials: sigshares.len(),
+        });
+    }
+    let a_hat = compute_a_hat_with_table(table, openings).ok_or(Error::CombineFailed)?;
+    combine_with_a_hat(ss, &a_hat, sigshares)
+}
+
+/// Combine with an A_hat already computed by `compute_a_hat` (or
+/// `precompute_a_hat`'s `a_hat`). Applies the same checks to the partials as
+/// `combine`: Error::InvalidSignerId if SS repeats an id or contains 0,
+/// Error::BadPartials for a missing, repeated or outside partial, and
+/// Error::ChallengeDivergence for a digest of a different A_hat.
+/// Error::BadPartials also names every signer whose z_i is not reduced
+/// mod ℓ.
+pub fn combine_with_a_hat(
+    ss: &[u32],
+    a_hat: &RistrettoPoint,
+    sigshares: &[PartialSignature],
+) -> Result<Signature, Error> {
+    if invalid_id(ss).is_some() {
+        return Err(invalid_signing_set(ss));
+    }
+    one_per_signer(ss, sigshares, |ps| ps.i).map_err(Error::BadPartials)?;
+
+    let digest = hahat(a_hat);
+    let diverged: Vec<u32> = sigshares
+        .iter()
+        .filter(|ps| !bool::from(ps.a_hat_digest.ct_eq(&digest)))
+        .map(|ps| ps.i)
+        .collect();
+    if !diverged.is_empty() {
+        return Err(Error::ChallengeDivergence(diverged));
+    }
+
+    // z_i comes from the network: only its canonical encoding is accepted,
+    // so one partial signature has one byte string.
     let mut z = Scalar::ZERO;
+    let mut non_canonical = Vec::new();
     for ps in sigshares {
-        z += dec_scalar(&ps.z_i);
+        match dec_scalar_canonical(&ps.z_i) {
+            Some(z_i) => z += z_i,
+            None => non_canonical.push(ps.i),
+        }
+    }
+    if !non_canonical.is_empty() {
+        return Err(Error::BadPartials(non_canonical));
     }
 
-    Some(Signature { A_hat: a_hat, z })
+    Ok(Signature { A_hat: *a_hat, z })
+}
+
+/// Run all three rounds in-process for the signers in SS (honest case) and
+/// combine: Sig1 for every signer, Sig2 against all commitments, Sig3
+/// against all openings. Shares are looked up by id, as returned by
+/// `kgen_for_ids`. Check the result with `verify`.
+///
+/// For signers on different machines, drive `sig1`, a `SigningSession`'s
+/// `sig2`/`sig3` and `combine` directly.
+#[cfg(feature = "std")]
+pub fn sign(
+    par: &Params,
+    sk_shares: &[(u32, SecretKeyShare)],
+    pk_shares: &[(u32, PublicKeyShare)],
+    pk_joint: &RistrettoPoint,
+    ss: &[u32],
+    message: &[u8],
+) -> Result<Signature, SignError> {
+    sign_with_rng(par, sk_shares, pk_shares, pk_joint, ss, message, &mut OsRng.unwrap_err())
+}
+
+/// `sign` drawing every round's randomness from `rng`.
+pub fn sign_with_rng(
+    par: &Params,
+    sk_shares: &[(u32, SecretKeyShare)],
+    pk_shares: &[(u32, PublicKeyShare)],
+    pk_joint: &RistrettoPoint,
+    ss: &[u32],
+    message: &[u8],
+    rng: &mut impl CryptoRng,
+) -> Result<Signature, SignError> {
+    let sk_of = |i| share_for(sk_shares, i).ok_or(SignError::MissingSecretKeyShare(i));
+    let pk_of = |i| share_for(pk_shares, i).ok_or(SignError::MissingPublicKeyShare(i));
+    if let Some(j) = invalid_id(ss) {
+        return Err(SignError::InvalidSigningSet(j));
+    }
+
+    let mut commitments = Vec::with_capacity(ss.len());
+    let mut states = Vec::with_capacity(ss.len());
+    for &i in ss {
+        let (cm, st) = sig1_with_rng(par, i, sk_of(i)?, rng);
+        commitments.push((cm.i, cm.mu_i));
+        states.push(st);
+    }
+
+    let pk_map: Vec<(u32, RistrettoPoint)> = pk_shares.iter().map(|(i, pk)| (*i, pk.pk_i)).collect();
+    let mut session = SigningSession::new_with_rng(par, message, ss, pk_joint, &pk_map, rng);
+    let mut openings = Vec::with_capacity(ss.len());
+    let mut states2 = Vec::with_capacity(ss.len());
+    for (&i, st) in ss.iter().zip(&states) {
+        let (om, st2) = session.sig2(i, &commitments, pk_of(i)?, sk_of(i)?, st)?;
+        openings.push(om);
+        states2.push(st2);
+    }
+
+    let mut partials = Vec::with_capacity(ss.len());
+    for (&i, st2) in ss.iter().zip(&states2) {
+        partials.push(session.sig3(i, sk_of(i)?, st2, &commitments, &openings)?);
+    }
+
+    let sig = combine(ss, &openings, &partials).map_err(SignError::Combine)?;
+    if !verify(par, pk_joint, ss, message, &sig) {
+        return Err(SignError::Combine(Error::InvalidSignature));
+    }
+    Ok(sig)
+}
+
+/// Verify against a bundled VerificationKey (same check as `verify`).
+pub fn verify_with_key(vk: &VerificationKey, ss: &[u32], message: &[u8], sig: &Signature) -> bool {
+    verify(&vk.params_public, &vk.pk_joint, ss, message, sig)
+}
+
+/// One signer's partial signature together with the round-1/2 messages it
+/// received and signed over (its view of the session).
+#[derive(Clone, Debug)]
+pub struct ViewedPartial {
+    pub partial: PartialSignature,
+    pub commitments: Vec<(u32, [u8; 32])>,
+    pub openings: Vec<OpeningMessage>,
+}
+
+/// Transcript of one signing session run over a chosen signing set: the
+/// coordinator's view (commitments, openings) and every partial signature
+/// with the view its signer signed over. Signers that aborted in Sig3
+/// simply contribute no partial signature.
+pub type SessionTranscript = (Vec<(u32, [u8; 32])>, Vec<OpeningMessage>, Vec<ViewedPartial>);
+
+/// Resilient combine for a coordinator with more than t+1 available signers.
+///
+/// Picks the first t+1 non-excluded ids of `ss_candidates` as SS and asks
+/// `run_session` to run a fresh signing session over it, then excludes
+/// signers only on evidence and selects a new quorum, up to `max_retries`
+/// times:
+/// - a signer in SS that returned no partial signature (or several);
+/// - a signer whose partial was signed over a view that is not a valid
+///   session (an opening fails Sig3's checks, the openings do not cover SS,
+///   or its A_hat digest is not that view's A_hat);
+/// - a signer with two different openings in two valid views. Each
+///   opening carries a NIZK under pk_j, so only j can have made both, and an
+///   honest signer opens once per session.
+///
+/// A partial's A_hat digest is never trusted on its own: a signer whose
+/// valid view differs from the coordinator's is a victim of whoever
+/// equivocated, not the culprit. If the coordinator's own view fails Sig3's
+/// checks (it was sent different messages than the signers), the first
+/// valid signer view is used instead.
+///
+/// Every attempt must be a new session with fresh Sig1 nonces: the partial
+/// signatures depend on SS, and reusing a_i under two different challenges
+/// reveals s(i). A combined signature that still fails `verify` cannot be
+/// attributed to a single share and yields Error::InvalidSignature.
+///
+/// Returns the signature with the SS it was produced by, which `verify`
+/// needs.
+#[cfg(feature = "std")]
+pub fn combine_resilient<F>(
+    par: &Params,
+    pk_joint: &RistrettoPoint,
+    pk_shares: &[(u32, RistrettoPoint)],
+    message: &[u8],
+    ss_candidates: &[u32],
+    max_retries: usize,
+    mut run_session: F,
+) -> Result<(Signature, Vec<u32>), Error>
+where
+    F: FnMut(&[u32]) -> SessionTranscript,
+{
+    let required = par.t + 1;
+    let mut excluded: Vec<u32> = Vec::new();
+
+    for _ in 0..=max_retries {
+        let ss: Vec<u32> = ss_candidates
+            .iter()
+            .copied()
+            .filter(|i| !excluded.contains(i))
+            .take(required)
+            .collect();
+        if ss.len() < required {
+            return Err(Error::QuorumUnavailable { available: ss.len(), required });
+        }
+        let table = LagrangeTable::new(&ss).ok_or_else(|| invalid_signing_set(&ss))?;
+
+        let (commitments, openings, partials) = run_session(&ss);
+        let mut session = SigningSession::new(par, message, &ss, pk_joint, pk_shares);
+        let openings = match resilient_view(&mut session, &table, &commitments, &openings, &partials) {
+            Ok(openings) => openings,
+            Err(blamed) => {
+                excluded.extend(blamed);
+                continue;
+            }
+        };
+
+        let sigshares: Vec<PartialSignature> = partials
+            .into_iter()
+            .filter(|vp| ss.contains(&vp.partial.i))
+            .map(|vp| vp.partial)
+            .collect();
+        let sig = combine_with_table(&table, &openings, &sigshares)?;
+        if !verify(par, pk_joint, &ss, message, &sig) {
+            return Err(Error::InvalidSignature);
+        }
+        return Ok((sig, ss));
+    }
+
+    Err(Error::RetriesExhausted { excluded })
+}
+
+/// The openings `combine_resilient` combines (the coordinator's if its view
+/// is valid, else the first valid signer view), or the signers to exclude.
+#[cfg(feature = "std")]
+fn resilient_view(
+    session: &mut SigningSession,
+    table: &LagrangeTable,
+    commitments: &[(u32, [u8; 32])],
+    openings: &[OpeningMessage],
+    partials: &[ViewedPartial],
+) -> Result<Vec<OpeningMessage>, Vec<u32>> {
+    let ss = table.ids();
+    let mut blamed: Vec<u32> = ss
+        .iter()
+        .copied()
+        .filter(|&j| partials.iter().filter(|vp| vp.partial.i == j).count() != 1)
+        .collect();
+
+    // Views already checked, so signers sharing a view cost one check.
+    let mut valid: Vec<(&ViewedPartial, [u8; 32])> = Vec::new();
+    for vp in partials.iter().filter(|vp| ss.contains(&vp.partial.i)) {
+        let known = valid.iter().find(|(v, _)| same_view(v, vp)).map(|v| v.1);
+        let digest = match known {
+            Some(d) => Some(d),
+            None => view_a_hat(session, table, &vp.commitments, &vp.openings).map(|a| {
+                let d = hahat(&a);
+                valid.push((vp, d));
+                d
+            }),
+        };
+        if !digest.is_some_and(|d| bool::from(d.ct_eq(&vp.partial.a_hat_digest))) {
+            blamed.push(vp.partial.i);
+        }
+    }
+    if !blamed.is_empty() {
+        blamed.sort_unstable();
+        blamed.dedup();
+        return Err(blamed);
+    }
+
+    let (reference, digest) = match view_a_hat(session, table, commitments, openings) {
+        Some(a) => (openings, hahat(&a)),
+        None => {
+            let &(vp, d) = valid.first().expect("every signer in SS has a partial over a valid view");
+            (&vp.openings[..], d)
+        }
+    };
+
+    // Both views passed Sig3's checks, so every opening in either is proven
+    // under its sender's pk_j: a sender with two different ones made both.
+    let mut equivocators: Vec<u32> = Vec::new();
+    for &(vp, _) in valid.iter().filter(|(_, d)| !bool::from(d.ct_eq(&digest))) {
+        for om in reference {
+            let differs = vp.openings.iter().find(|o| o.i == om.i).is_some_and(|o| !same_opening(o, om));
+            if differs && !equivocators.contains(&om.i) {
+                equivocators.push(om.i);
+            }
+        }
+    }
+    if !equivocators.is_empty() {
+        equivocators.sort_unstable();
+        return Err(equivocators);
+    }
+    Ok(reference.to_vec())
+}
+
+/// A_hat of a session view a signer may sign over: one opening per signer
+/// in SS, each passing Sig3's checks against `commitments`.
+#[cfg(feature = "std")]
+fn view_a_hat(
+    session: &mut SigningSession,
+    table: &LagrangeTable,
+    commitments: &[(u32, [u8; 32])],
+    openings: &[OpeningMessage],
+) -> Option<RistrettoPoint> {
+    if !is_permutation(table.ids(), openings.iter().map(|om| om.i)) {
+        return None;
+    }
+    session.check_openings(commitments, openings).ok()?;
+    a_hat_from(table, openings).ok()
+}
+
+#[cfg(feature = "std")]
+fn same_view(a: &ViewedPartial, b: &ViewedPartial) -> bool {
+    normalize_mu_vec(a.commitments.clone()) == normalize_mu_vec(b.commitments.clone())
+        && a.openings.len() == b.openings.len()
+        && a.openings.iter().all(|x| b.openings.iter().any(|y| same_opening(x, y)))
+}
+
+#[cfg(feature = "std")]
+fn same_opening(a: &OpeningMessage, b: &OpeningMessage) -> bool {
+    a.i == b.i
+        && a.a_point == b.a_point
+        && a.rho_i == b.rho_i
+        && a.b_point == b.b_point
+        && a.proof.to_bytes() == b.proof.to_bytes()
+}
+
+/// `ids` lists every element of SS exactly once (in any order).
+#[cfg(feature = "std")]
+fn is_permutation(ss: &[u32], ids: impl Iterator<Item = u32>) -> bool {
+    let mut ids: Vec<u32> = ids.collect();
+    let mut ss = ss.to_vec();
+    ids.sort_unstable();
+    ss.sort_unstable();
+    ids == ss
 }
 
 /// Verify Schnorr:
-/// check g*z == A_hat + pk*c, where c = Hsig(A_hat, pk, m)
-pub fn verify(par: &Params, pk_joint: &RistrettoPoint, message: &[u8], sig: &Signature) -> bool {
-    let c = hsig(&sig.A_hat, pk_joint, message);
-    let left = par.g * sig.z;
-    let right = sig.A_hat + (*pk_joint) * c;
-    left == right
+/// check g*z == A_hat + pk*c, where c = Hsig(SS, m, A_hat, pk)
+/// SS is the signing set that produced the signature (in any order); the
+/// same signature does not verify under any other set.
+/// There is no low-s mode: z is unique for a given A_hat (see
+/// `Signature::normalize_low_s`), so rejecting upper-half z would only
+/// reject valid signatures.
+pub fn verify(par: &Params, pk_joint: &RistrettoPoint, ss: &[u32], message: &[u8], sig: &Signature) -> bool {
+    verify_in::<Ristretto>(&par.bases(), pk_joint, ss, message, &sig.A_hat, &sig.z)
+}
+
+/// `verify` over any `Group`: g*z == A_hat + pk*Hsig(SS, m, A_hat, pk).
+pub fn verify_in<G: Group>(
+    bases: &Bases<G>,
+    pk_joint: &G::Point,
+    ss: &[u32],
+    message: &[u8],
+    a_hat: &G::Point,
+    z: &G::Scalar,
+) -> bool {
+    let c = hsig_in::<G>(a_hat, pk_joint, ss, message);
+    G::vartime_multiscalar_mul(
+        &[*z, G::scalar_neg(&G::scalar_one()), G::scalar_neg(&c)],
+        &[bases.g, *a_hat, *pk_joint],
+    ) == G::identity()
+}
+
+/// Verify without a stored pk_joint: rebuild it as Σ_{j∈SS} L_{j,SS} S_j
+/// from the per-signer commitments S_j = g*s(j), then run `verify`. SS must
+/// be t+1 or more distinct nonzero ids, each with a commitment; any t+1
+/// honest S_j give the same key.
+pub fn verify_from_share_commitments(
+    par: &Params,
+    share_commitments: &[(u32, RistrettoPoint)],
+    ss: &[u32],
+    message: &[u8],
+    sig: &Signature,
+) -> bool {
+    if invalid_id(ss).is_some() || ss.len() < par.t + 1 {
+        return false;
+    }
+    let mut points = Vec::with_capacity(ss.len());
+    for &j in ss {
+        match share_for(share_commitments, j) {
+            Some(s_j) => points.push((j, *s_j)),
+            None => return false,
+        }
+    }
+    match interpolate_points(&points) {
+        Some(pk_joint) => verify(par, &pk_joint, ss, message, sig),
+        None => false,
+    }
+}
+
+/// g*z - A_hat - pk*c == 0 as a single multi-exponentiation.
+fn schnorr_check(par: &Params, pk_joint: &RistrettoPoint, sig: &Signature, c: Scalar) -> bool {
+    RistrettoPoint::vartime_multiscalar_mul(
+        [sig.z, -Scalar::ONE, -c],
+        [par.g, sig.A_hat, *pk_joint],
+    )
+    .is_identity()
+}
+
+/// Verify many signatures under one joint key with a single
+/// multi-exponentiation: for random δ_k, check
+///   g*(Σ δ_k z_k) - Σ δ_k A_hat_k - pk*(Σ δ_k c_k) == 0.
+/// Any invalid signature makes this fail except with probability ~1/p.
+/// Each entry is (SS, message, signature), as for `verify`.
+/// An empty batch is rejected: it vouches for nothing. Variable-time; all
+/// inputs are public.
+#[cfg(feature = "std")]
+pub fn verify_batch(par: &Params, pk_joint: &RistrettoPoint, batch: &[(&[u32], &[u8], &Signature)]) -> bool {
+    verify_batch_with_rng(par, pk_joint, batch, &mut OsRng.unwrap_err())
+}
+
+/// `verify_batch` drawing the weights δ_k from `rng`.
+pub fn verify_batch_with_rng(
+    par: &Params,
+    pk_joint: &RistrettoPoint,
+    batch: &[(&[u32], &[u8], &Signature)],
+    rng: &mut impl CryptoRng,
+) -> bool {
+    if batch.is_empty() {
+        return false;
+    }
+    let mut scalars = Vec::with_capacity(batch.len() + 2);
+    let mut points = Vec::with_capacity(batch.len() + 2);
+
+    // Signatures over the same (SS, message) share one absorbed state.
+    let mut hashers: Vec<(&[u32], &[u8], ChallengeHasher)> = Vec::new();
+    let mut z_sum = Scalar::ZERO;
+    let mut c_sum = Scalar::ZERO;
+    for &(ss, message, sig) in batch {
+        let delta = crate::randutil::random_scalar_with_rng(rng);
+        let c = match hashers.iter().find(|(s, m, _)| *s == ss && *m == message) {
+            Some((_, _, h)) => h.finalize(&sig.A_hat, pk_joint),
+            None => {
+                let h = ChallengeHasher::for_message(ss, message);
+                let c = h.finalize(&sig.A_hat, pk_joint);
+                hashers.push((ss, message, h));
+                c
+            }
+        };
+        z_sum += delta * sig.z;
+        c_sum += delta * c;
+        scalars.push(-delta);
+        points.push(sig.A_hat);
+    }
+    scalars.push(z_sum);
+    points.push(par.g);
+    scalars.push(-c_sum);
+    points.push(*pk_joint);
+
+    RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity()
+}
+
+/// `verify` for a message streamed in chunks: the challenge hash absorbs
+/// each chunk as it arrives, so the message is never buffered.
+#[derive(Clone)]
+pub struct StreamVerifier {
+    hasher: ChallengeHasher,
+}
+
+impl StreamVerifier {
+    /// A verifier for a signature by signing set `ss`.
+    pub fn new(ss: &[u32]) -> StreamVerifier {
+        StreamVerifier { hasher: ChallengeHasher::new(ss) }
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+    }
+
+    /// Same check as `verify` over the concatenation of all chunks.
+    pub fn finalize(self, par: &Params, pk_joint: &RistrettoPoint, sig: &Signature) -> bool {
+        let c = self.hasher.finalize(&sig.A_hat, pk_joint);
+        schnorr_check(par, pk_joint, sig, c)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::hash::dec_scalar;
+    use crate::testutil::{plus_ell, Session, MSG};
+
+    /// Fresh round-1 states for every signer in `s.ss`.
+    fn round1(s: &Session) -> (Vec<(u32, [u8; 32])>, Vec<SignerState>) {
+        s.ss.iter()
+            .map(|&i| {
+                let (cm, st) = sig1(&s.par, i, s.sk(i));
+                ((cm.i, cm.mu_i), st)
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn sig2_rejects_a_missing_own_commitment() {
+        let s = Session::new(3, 1);
+        let (mut commitments, states) = round1(&s);
+        commitments.remove(0);
+        let i = s.ss[0];
+        assert_eq!(
+            s.signing().sig2(i, &commitments, s.pk(i), s.sk(i), &states[0]).err(),
+            Some(SignError::MissingCommitment(i))
+        );
+    }
+
+    #[test]
+    fn sig2_rejects_a_wrong_own_commitment() {
+        let s = Session::new(3, 1);
+        let (mut commitments, states) = round1(&s);
+        commitments[0].1[0] ^= 1;
+        let i = s.ss[0];
+        assert_eq!(
+            s.signing().sig2(i, &commitments, s.pk(i), s.sk(i), &states[0]).err(),
+            Some(SignError::CommitmentMismatch(i))
+        );
+
+        // Another signer's state under this signer's id.
+        let (commitments, states) = round1(&s);
+        assert_eq!(
+            s.signing().sig2(i, &commitments, s.pk(i), s.sk(i), &states[1]).err(),
+            Some(SignError::CommitmentMismatch(i))
+        );
+    }
+
+    fn sig3_error(s: &Session, openings: &[OpeningMessage]) -> Option<SignError> {
+        let i = s.ss[0];
+        s.signing().sig3(i, s.sk(i), &s.states[0], &s.commitments, openings).err()
+    }
+
+    #[test]
+    fn sig3_names_the_signer_behind_a_bad_opening() {
+        let s = Session::new(4, 2);
+        let j = s.ss[2];
+
+        let mut openings = s.openings.clone();
+        openings[2].b_point = [0xff; 32];
+        assert_eq!(sig3_error(&s, &openings), Some(SignError::PointDecodeFailed(j)));
+
+        let mut openings = s.openings.clone();
+        openings[2].rho_i[0] ^= 1;
+        assert_eq!(sig3_error(&s, &openings), Some(SignError::CommitmentMismatch(j)));
+
+        let mut openings = s.openings.clone();
+        openings[2].a_point = enc_point(&RistrettoPoint::identity());
+        assert_eq!(sig3_error(&s, &openings), Some(SignError::IdentityPoint(j)));
+        assert_eq!(SignError::IdentityPoint(j).signer(), Some(j));
+
+        let mut openings = s.openings.clone();
+        openings[2].proof = s.openings[1].proof.clone();
+        assert_eq!(sig3_error(&s, &openings), Some(SignError::ProofInvalid(j)));
+        assert_eq!(SignError::ProofInvalid(j).signer(), Some(j));
+
+        let mut openings = s.openings.clone();
+        openings[2].i = 4;
+        assert_eq!(sig3_error(&s, &openings), Some(SignError::MissingCommitment(4)));
+        assert_eq!(sig3_error(&s, &s.openings), None);
+    }
+
+    #[test]
+    fn sig3_batch_matches_serial_sig3() {
+        let s = Session::new(9, 5);
+        let serial: Vec<_> = s.ss.iter()
+            .zip(&s.states)
+            .map(|(&i, st)| {
+                s.signing().sig3(i, s.sk(i), st, &s.commitments, &s.openings).unwrap()
+            })
+            .collect();
+        // Signers handed over out of order come back in id order.
+        let signers: Vec<_> = s.ss.iter().zip(&s.states).rev().map(|(&i, st)| (i, s.sk(i), st)).collect();
+        let batch = |openings: &[OpeningMessage]| {
+            s.signing().sig3_batch(&signers, &s.commitments, openings)
+        };
+        for _ in 0..3 {
+            let shares = batch(&s.openings).unwrap();
+            assert_eq!(shares.iter().map(|ps| ps.i).collect::<Vec<_>>(), s.ss);
+            for (a, b) in shares.iter().zip(&serial) {
+                assert_eq!((a.i, a.z_i, a.a_hat_digest), (b.i, b.z_i, b.a_hat_digest));
+            }
+        }
+
+        // The same error as the serial path, even with a later opening bad too.
+        let mut openings = s.openings.clone();
+        openings[2].proof = s.openings[1].proof.clone();
+        openings[4].proof = s.openings[3].proof.clone();
+        assert_eq!(batch(&openings).err(), Some(SignError::ProofInvalid(s.ss[2])));
+        assert_eq!(sig3_error(&s, &openings), batch(&openings).err());
+        openings[5].rho_i[0] ^= 1;
+        assert_eq!(sig3_error(&s, &openings), batch(&openings).err());
+    }
+
+    #[test]
+    fn sig3_logs_every_signer_behind_a_rejected_round() {
+        let s = Session::new(5, 4);
+        let (j, k, l) = (s.ss[1], s.ss[3], s.ss[4]);
+        let mut openings = s.openings.clone();
+        openings[1].rho_i[0] ^= 1;
+        openings[3].proof = s.openings[2].proof.clone();
+        openings[4].a_point = enc_point(&RistrettoPoint::identity());
+        let expected = vec![
+            ProtocolEvent::OpeningRejected { signer: j, reason: SignError::CommitmentMismatch(j) },
+            ProtocolEvent::OpeningRejected { signer: l, reason: SignError::IdentityPoint(l) },
+            ProtocolEvent::ProofFailed { signer: k },
+        ];
+
+        let i = s.ss[0];
+        let mut log = Vec::new();
+        let res = s.signing().with_log(&mut log).sig3(i, s.sk(i), &s.states[0], &s.commitments, &openings);
+        assert_eq!(res.err(), Some(SignError::CommitmentMismatch(j)));
+        assert_eq!(log, expected);
+
+        let signers: Vec<_> = s.ss.iter().zip(&s.states).map(|(&i, st)| (i, s.sk(i), st)).collect();
+        let mut log = Vec::new();
+        let res = s.signing().with_log(&mut log).sig3_batch(&signers, &s.commitments, &openings);
+        assert_eq!(res.err(), Some(SignError::CommitmentMismatch(j)));
+        assert_eq!(log, expected);
+
+        // An accepted round logs nothing.
+        let mut log = Vec::new();
+        s.signing().with_log(&mut log).sig3(i, s.sk(i), &s.states[0], &s.commitments, &s.openings).unwrap();
+        assert!(log.is_empty());
+    }
+
+    /// Every signer's `NonceReveal` for the session's Sig2 states.
+    fn reveals(s: &Session) -> Vec<NonceReveal> {
+        s.states.iter().map(|st| reveal_nonce(&s.par, s.pk(st.i), s.sk(st.i), st)).collect()
+    }
+
+    /// `SigningSession::combine_checked` over the session with the given openings, reveals and partials.
+    fn check_combine(
+        s: &Session,
+        openings: &[OpeningMessage],
+        reveals: &[NonceReveal],
+        partials: &[PartialSignature],
+    ) -> Option<Error> {
+        s.signing().combine_checked(&s.commitments, openings, reveals, partials).err()
+    }
+
+    #[test]
+    fn combine_checked_sums_one_partial_per_signer() {
+        let s = Session::new(5, 4);
+        let reveals = reveals(&s);
+        let check = |partials: &[PartialSignature]| check_combine(&s, &s.openings, &reveals, partials);
+        assert_eq!(check(&s.partials), None);
+
+        // A repeated partial would otherwise be summed twice.
+        let mut partials = s.partials.clone();
+        partials[4] = partials[1].clone();
+        assert_eq!(check(&partials), Some(Error::BadPartials(vec![2, 5])));
+
+        let mut partials = s.partials.clone();
+        partials.push(PartialSignature { i: 9, ..s.partials[0].clone() });
+        assert_eq!(check(&partials), Some(Error::BadPartials(vec![9])));
+
+        let mut openings = s.openings.clone();
+        openings[4] = openings[1].clone();
+        assert_eq!(check_combine(&s, &openings, &reveals, &s.partials), Some(Error::CombineFailed));
+
+        // A signer without a reveal cannot have its share checked.
+        assert_eq!(check_combine(&s, &s.openings, &reveals[1..], &s.partials), Some(Error::BadPartials(vec![1])));
+    }
+
+    #[test]
+    fn combine_checked_rejects_a_bad_share() {
+        let s = Session::new(5, 4);
+        let reveals = reveals(&s);
+        let mut partials = s.partials.clone();
+        let z = dec_scalar(&partials[3].z_i) + Scalar::ONE;
+        partials[3].z_i = enc_scalar(&z);
+        assert!(combine(&s.ss, &s.openings, &partials).is_ok());
+        assert_eq!(check_combine(&s, &s.openings, &reveals, &partials), Some(Error::BadPartials(vec![4])));
+
+        partials[3] = s.partials[3].clone();
+        partials[3].a_hat_digest[0] ^= 1;
+        assert_eq!(
+            check_combine(&s, &s.openings, &reveals, &partials),
+            Some(Error::ChallengeDivergence(vec![4]))
+        );
+    }
+
+    #[test]
+    fn verify_partial_accepts_each_honest_share() {
+        let s = Session::new(5, 3);
+        for (ps, st) in s.partials.iter().zip(&s.states) {
+            let i = ps.i;
+            let reveal = reveal_nonce(&s.par, s.pk(i), s.sk(i), st);
+            assert!(s.signing().verify_partial(&s.commitments, &s.openings, &reveal, ps));
+        }
+    }
+
+    #[test]
+    fn verify_partial_rejects_a_tampered_share() {
+        let s = Session::new(5, 3);
+        let (ps, st) = (&s.partials[2], &s.states[2]);
+        let i = ps.i;
+        let reveal = reveal_nonce(&s.par, s.pk(i), s.sk(i), st);
+        let check = |ps: &PartialSignature, reveal: &NonceReveal| {
+            s.signing().verify_partial(&s.commitments, &s.openings, reveal, ps)
+        };
+        assert!(check(ps, &reveal));
+
+        let mut bad = ps.clone();
+        bad.z_i = enc_scalar(&(dec_scalar(&ps.z_i) + Scalar::ONE));
+        assert!(!check(&bad, &reveal));
+        bad.z_i = [0xff; 32];
+        assert!(!check(&bad, &reveal));
+
+        // Another signer's reveal, or one claiming a different R_i.
+        let other = reveal_nonce(&s.par, s.pk(1), s.sk(1), &s.states[0]);
+        assert!(!check(ps, &other));
+        assert!(!check(ps, &NonceReveal { i, ..other }));
+        let mut moved = reveal.clone();
+        moved.r_point = enc_point(&(dec_point(&reveal.r_point).unwrap() + s.par.g));
+        assert!(!check(ps, &moved));
+
+        // combine_checked names the signer behind the tampered share.
+        let mut partials = s.partials.clone();
+        partials[2].z_i = enc_scalar(&(dec_scalar(&ps.z_i) + Scalar::ONE));
+        assert_eq!(
+            check_combine(&s, &s.openings, &reveals(&s), &partials),
+            Some(Error::BadPartials(vec![i]))
+        );
+    }
+
+    /// An honest session over `ss`, each partial carrying the shared view.
+    fn honest_transcript(s: &Session, ss: &[u32]) -> SessionTranscript {
+        let (commitments, _, openings, partials) = s.rounds(ss, MSG);
+        let viewed = partials
+            .into_iter()
+            .map(|partial| ViewedPartial { partial, commitments: commitments.clone(), openings: openings.clone() })
+            .collect();
+        (commitments, openings, viewed)
+    }
+
+    fn resilient(
+        s: &Session,
+        max_retries: usize,
+        run: impl FnMut(&[u32]) -> SessionTranscript,
+    ) -> Result<(Signature, Vec<u32>), Error> {
+        let candidates: Vec<u32> = (1..=s.par.n as u32).collect();
+        combine_resilient(&s.par, &s.pk_joint, &s.pk_map, MSG, &candidates, max_retries, run)
+    }
+
+    #[test]
+    fn resilient_combine_signs_an_honest_session() {
+        let s = Session::new(5, 2);
+        let (sig, ss) = resilient(&s, 0, |ss| honest_transcript(&s, ss)).unwrap();
+        assert!(verify(&s.par, &s.pk_joint, &ss, MSG, &sig));
+    }
+
+    #[test]
+    fn resilient_combine_excludes_a_signer_lying_about_its_digest() {
+        let s = Session::new(5, 2);
+        let lying = |ss: &[u32]| {
+            let mut tr = honest_transcript(&s, ss);
+            for vp in tr.2.iter_mut().filter(|vp| vp.partial.i == 2) {
+                vp.partial.a_hat_digest[0] ^= 1;
+            }
+            tr
+        };
+        assert_eq!(resilient(&s, 0, lying).err(), Some(Error::RetriesExhausted { excluded: vec![2] }));
+
+        let (sig, ss) = resilient(&s, 1, lying).unwrap();
+        assert_eq!(ss, vec![1, 3, 4]);
+        assert!(verify(&s.par, &s.pk_joint, &ss, MSG, &sig));
+    }
+
+    #[test]
+    fn resilient_combine_excludes_a_missing_partial() {
+        let s = Session::new(5, 2);
+        let run = |ss: &[u32]| {
+            let mut tr = honest_transcript(&s, ss);
+            tr.2.retain(|vp| vp.partial.i != 3);
+            tr
+        };
+        assert_eq!(resilient(&s, 0, run).err(), Some(Error::RetriesExhausted { excluded: vec![3] }));
+    }
+
+    #[test]
+    fn equivocation_toward_the_coordinator_excludes_no_honest_signer() {
+        // Signer 1 shows the coordinator a second commitment and an opening
+        // against it; the signers all see the first one. The coordinator's
+        // view then fails Sig3's checks on the honest openings, which must
+        // not get them excluded.
+        let s = Session::new(5, 2);
+        let run = |ss: &[u32]| {
+            let (mut commitments, mut openings, partials) = honest_transcript(&s, ss);
+            let (cm, st) = sig1(&s.par, 1, s.sk(1));
+            commitments[0] = (1, cm.mu_i);
+            openings[0] = s.signing().sig2(1, &commitments, s.pk(1), s.sk(1), &st).unwrap().0;
+            (commitments, openings, partials)
+        };
+        let (sig, ss) = resilient(&s, 0, run).unwrap();
+        assert!(verify(&s.par, &s.pk_joint, &ss, MSG, &sig));
+    }
+
+    #[test]
+    fn two_valid_views_blame_the_signers_who_opened_twice() {
+        // Signers 1 and 2 ran two complete sessions and hand the
+        // coordinator one and a partial from the other: each opened twice.
+        let s = Session::new(3, 1);
+        let run = |ss: &[u32]| {
+            let (commitments, openings, mut viewed) = honest_transcript(&s, ss);
+            let (_, _, other) = honest_transcript(&s, ss);
+            viewed[1] = other[1].clone();
+            (commitments, openings, viewed)
+        };
+        assert_eq!(resilient(&s, 0, run).err(), Some(Error::RetriesExhausted { excluded: vec![1, 2] }));
+    }
+
+    #[test]
+    fn a_partial_over_a_forged_view_blames_its_sender() {
+        let s = Session::new(5, 2);
+        let run = |ss: &[u32]| {
+            let mut tr = honest_transcript(&s, ss);
+            tr.2[2].openings[0].proof = tr.1[1].proof.clone();
+            tr
+        };
+        assert_eq!(resilient(&s, 0, run).err(), Some(Error::RetriesExhausted { excluded: vec![3] }));
+    }
+
+    #[test]
+    fn combine_needs_one_opening_and_one_partial_per_signer() {
+        let s = Session::new(5, 3);
+        assert!(combine(&s.ss, &s.openings, &s.partials).is_ok());
+        assert_eq!(
+            combine(&s.ss, &s.openings[..3], &s.partials).err(),
+            Some(Error::InconsistentInputLengths { ss: 4, openings: 3, partials: 4 })
+        );
+        assert_eq!(
+            combine(&s.ss, &s.openings, &s.partials[1..]).err(),
+            Some(Error::InconsistentInputLengths { ss: 4, openings: 4, partials: 3 })
+        );
+
+        // Right lengths, wrong ids: signer 3's opening sent twice, 4's missing.
+        let mut openings = s.openings.clone();
+        openings[3] = openings[2].clone();
+        assert_eq!(combine(&s.ss, &openings, &s.partials).err(), Some(Error::CombineFailed));
+        assert_eq!(compute_a_hat(&s.ss, &openings), None);
+
+        let mut partials = s.partials.clone();
+        partials[3] = partials[2].clone();
+        assert_eq!(combine(&s.ss, &s.openings, &partials).err(), Some(Error::BadPartials(vec![3, 4])));
+        partials[3] = PartialSignature { i: 7, ..s.partials[3].clone() };
+        assert_eq!(combine(&s.ss, &s.openings, &partials).err(), Some(Error::BadPartials(vec![4, 7])));
+
+        // Sig3 over the same openings names the first signer at fault.
+        let i = s.ss[0];
+        assert_eq!(
+            s.signing().sig3(i, s.sk(i), &s.states[0], &s.commitments, &openings).err(),
+            Some(SignError::OpeningsMismatch(3))
+        );
+    }
+
+    #[test]
+    fn combine_rejects_a_non_canonical_z_i() {
+        let s = Session::new(5, 3);
+        let mut partials = s.partials.clone();
+        // z_i + ℓ reduces to the same z_i, so the sum would still verify.
+        partials[1].z_i = plus_ell(&s.partials[1].z_i);
+        assert_eq!(dec_scalar(&partials[1].z_i), dec_scalar(&s.partials[1].z_i));
+        assert_eq!(
+            combine(&s.ss, &s.openings, &partials).err(),
+            Some(Error::BadPartials(vec![s.ss[1]]))
+        );
+    }
+
+    #[test]
+    fn sig3_rejects_a_non_canonical_proof_response() {
+        let s = Session::new(5, 3);
+        let j = s.ss[2];
+        let mut openings = s.openings.clone();
+        openings[2].proof.zs = plus_ell(&s.openings[2].proof.zs);
+        let i = s.ss[0];
+        assert_eq!(
+            s.signing().sig3(i, s.sk(i), &s.states[0], &s.commitments, &openings).err(),
+            Some(SignError::ProofInvalid(j))
+        );
+    }
+
+    #[test]
+    fn precomputed_a_hat_gives_the_same_signature() {
+        let s = Session::new(6, 3);
+        let pre = precompute_a_hat(&s.ss, &s.commitments, &s.openings).unwrap();
+        assert_eq!(compute_a_hat(&s.ss, &s.openings), Some(pre.a_hat));
+
+        let partials: Vec<PartialSignature> = s
+            .ss
+            .iter()
+            .zip(&s.states)
+            .map(|(&i, st)| {
+                s.signing().sig3_with_precomputed_a_hat(i, s.sk(i), st, &pre).unwrap()
+            })
+            .collect();
+        for (p, q) in partials.iter().zip(&s.partials) {
+            assert_eq!((p.i, p.z_i, p.a_hat_digest), (q.i, q.z_i, q.a_hat_digest));
+        }
+
+        let sig = combine_with_a_hat(&s.ss, &pre.a_hat, &partials).unwrap();
+        assert_eq!(sig.to_bytes(), s.signature().to_bytes());
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+    }
+
+    #[test]
+    fn a_wrong_precomputed_a_hat_is_detected() {
+        let s = Session::new(4, 2);
+        let honest = precompute_a_hat(&s.ss, &s.commitments, &s.openings).unwrap();
+        let lied_point = honest.a_hat + s.par.g;
+        let lied = PrecomputedAHat {
+            transcript: hahat_session(&s.ss, &s.states[0].mu_vec, &lied_point),
+            a_hat: lied_point,
+            ..honest.clone()
+        };
+
+        let partials: Vec<PartialSignature> = s
+            .ss
+            .iter()
+            .zip(&s.states)
+            .map(|(&i, st)| {
+                s.signing().sig3_with_precomputed_a_hat(i, s.sk(i), st, &lied).unwrap()
+            })
+            .collect();
+        // The partials carry the lie in their digest, and the result fails.
+        assert!(partials.iter().all(|p| p.a_hat_digest == hahat(&lied_point)));
+        assert!(!verify(&s.par, &s.pk_joint, &s.ss, MSG, &combine_with_a_hat(&s.ss, &lied_point, &partials).unwrap()));
+        // A signer that recomputes from the openings sees the mismatch.
+        assert_ne!(honest.a_hat, lied_point);
+
+        let mut openings = s.openings.clone();
+        openings[0].i = 99;
+        // Signer 1's opening is now missing, which is reported first.
+        assert_eq!(precompute_a_hat(&s.ss, &s.commitments, &openings), Err(Error::InvalidSignerId(1)));
+        let mut openings = s.openings.clone();
+        openings[1].a_point[31] |= 0x80;
+        assert_eq!(precompute_a_hat(&s.ss, &s.commitments, &openings), Err(Error::PointDecodeFailed));
+        assert_eq!(precompute_a_hat(&[1, 1, 2], &s.commitments, &s.openings), Err(Error::InvalidSignerId(1)));
+    }
+
+    #[test]
+    fn precomputed_a_hat_is_bound_to_its_session() {
+        let mut s = Session::new(4, 2);
+        let pre = precompute_a_hat(&s.ss, &s.commitments, &s.openings).unwrap();
+        let i = s.ss[0];
+        let sign = |s: &Session, pre: &PrecomputedAHat| {
+            s.signing().sig3_with_precomputed_a_hat(i, s.sk(i), &s.states[0], pre)
+        };
+        assert!(sign(&s, &pre).is_ok());
+
+        // An A_hat swapped in without a matching transcript, or one for another SS.
+        let swapped = PrecomputedAHat { a_hat: pre.a_hat + s.par.g, ..pre.clone() };
+        assert_eq!(sign(&s, &swapped).err(), Some(SignError::SessionMismatch));
+        let other_ss = PrecomputedAHat { ss: vec![1, 2, 4], ..pre.clone() };
+        assert_eq!(sign(&s, &other_ss).err(), Some(SignError::SessionMismatch));
+
+        // The previous session's A_hat is refused in a fresh one.
+        s.run(MSG);
+        assert_eq!(sign(&s, &pre).err(), Some(SignError::SessionMismatch));
+        let fresh = precompute_a_hat(&s.ss, &s.commitments, &s.openings).unwrap();
+        assert!(sign(&s, &fresh).is_ok());
+    }
+
+    #[test]
+    fn share_commitments_verify_like_the_joint_key() {
+        let mut s = Session::new(5, 2);
+        let commitments: Vec<(u32, RistrettoPoint)> =
+            (1..=5).map(|i| (i, s.par.g * s.sk(i).s)).collect();
+        for ss in [vec![1, 2, 3], vec![2, 4, 5], vec![1, 2, 3, 4, 5]] {
+            s.ss = ss;
+            s.run(MSG);
+            let sig = s.signature();
+            assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+            assert!(verify_from_share_commitments(&s.par, &commitments, &s.ss, MSG, &sig));
+            assert!(!verify_from_share_commitments(&s.par, &commitments, &s.ss, b"other", &sig));
+        }
+
+        // A wrong or missing commitment, or too small a set, fails.
+        let sig = s.signature();
+        let mut wrong = commitments.clone();
+        wrong[0].1 += s.par.g;
+        assert!(!verify_from_share_commitments(&s.par, &wrong, &s.ss, MSG, &sig));
+        assert!(!verify_from_share_commitments(&s.par, &commitments[1..], &s.ss, MSG, &sig));
+        assert!(!verify_from_share_commitments(&s.par, &commitments, &[1, 2], MSG, &sig));
+    }
+
+    #[test]
+    fn signature_verifies_only_under_its_signing_set() {
+        let s = Session::new(5, 2);
+        let sig = s.signature();
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+        let reversed: Vec<u32> = s.ss.iter().rev().copied().collect();
+        assert!(verify(&s.par, &s.pk_joint, &reversed, MSG, &sig));
+
+        for other in [&[1, 2, 4][..], &[1, 2], &[1, 2, 3, 4], &[]] {
+            assert!(!verify(&s.par, &s.pk_joint, other, MSG, &sig), "ss={:?}", other);
+        }
+    }
+
+    #[test]
+    fn streamed_message_signs_like_the_whole_message() {
+        let s = Session::new(4, 2);
+        let message: Vec<u8> = (0..1u32 << 20).map(|k| (k % 251) as u8).collect();
+        let mut h = MessageHasher::new(&s.ss);
+        for chunk in message.chunks(4093) {
+            h.update(chunk);
+        }
+
+        let (commitments, states) = round1(&s);
+        let mut streamed = SigningSession::hashed(&s.par, &h, &s.pk_joint, &s.pk_map);
+        let (openings, states2): (Vec<_>, Vec<_>) = s.ss.iter()
+            .zip(&states)
+            .map(|(&i, st)| streamed.sig2(i, &commitments, s.pk(i), s.sk(i), st).unwrap())
+            .unzip();
+        let partials: Vec<_> = s.ss.iter()
+            .zip(&states2)
+            .map(|(&i, st)| streamed.sig3(i, s.sk(i), st, &commitments, &openings).unwrap())
+            .collect();
+        let sig = combine(&s.ss, &openings, &partials).unwrap();
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, &message, &sig));
+
+        // A signer holding the whole message accepts the streamed openings
+        // and computes the same z_i.
+        let i = s.ss[0];
+        let whole = SigningSession::new(&s.par, &message, &s.ss, &s.pk_joint, &s.pk_map)
+            .sig3(i, s.sk(i), &states2[0], &commitments, &openings)
+            .unwrap();
+        assert_eq!(whole.z_i, partials[0].z_i);
+    }
+
+    #[test]
+    fn streamed_message_verifies_like_the_whole_message() {
+        let s = Session::new(4, 2);
+        let sig = s.signature();
+        let (a, rest) = MSG.split_at(5);
+        let (b, c) = rest.split_at(11);
+
+        let mut sv = StreamVerifier::new(&s.ss);
+        for chunk in [a, b, c] {
+            sv.update(chunk);
+        }
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+        assert!(sv.finalize(&s.par, &s.pk_joint, &sig));
+
+        let mut sv = StreamVerifier::new(&s.ss);
+        sv.update(a);
+        sv.update(c);
+        assert!(!sv.finalize(&s.par, &s.pk_joint, &sig));
+    }
+
+    #[test]
+    fn verify_batch_accepts_signatures_sharing_a_message() {
+        let mut s = Session::new(4, 2);
+        let mut sigs = Vec::new();
+        for m in [MSG, MSG, b"other"] {
+            s.run(m);
+            sigs.push((m, s.signature()));
+        }
+        let ss = &s.ss[..];
+        let batch: Vec<(&[u32], &[u8], &Signature)> = sigs.iter().map(|(m, sig)| (ss, *m, sig)).collect();
+        assert!(verify_batch(&s.par, &s.pk_joint, &batch));
+
+        // The same signatures under swapped messages.
+        let swapped: Vec<(&[u32], &[u8], &Signature)> =
+            vec![(ss, MSG, &sigs[0].1), (ss, b"other", &sigs[1].1), (ss, MSG, &sigs[2].1)];
+        assert!(!verify_batch(&s.par, &s.pk_joint, &swapped));
+    }
+
+    #[test]
+    fn verify_batch_rejects_a_forgery_and_an_empty_batch() {
+        let mut s = Session::new(4, 2);
+        let mut sigs = Vec::new();
+        for m in [&b"a"[..], b"b", b"c", b"d"] {
+            s.run(m);
+            sigs.push((m, s.signature()));
+        }
+        let ss = &s.ss[..];
+        let mut batch: Vec<(&[u32], &[u8], &Signature)> = sigs.iter().map(|(m, sig)| (ss, *m, sig)).collect();
+        assert!(verify_batch(&s.par, &s.pk_joint, &batch));
+
+        let forged = Signature { A_hat: sigs[2].1.A_hat, z: sigs[2].1.z + Scalar::ONE };
+        batch[2].2 = &forged;
+        assert!(!verify_batch(&s.par, &s.pk_joint, &batch));
+
+        // An honest signature attributed to the wrong signing set.
+        batch[2].2 = &sigs[2].1;
+        batch[1].0 = &[1, 2, 4];
+        assert!(!verify_batch(&s.par, &s.pk_joint, &batch));
+        assert!(!verify_batch(&s.par, &s.pk_joint, &[]));
+    }
+
+    #[test]
+    fn multiexp_verify_agrees_with_the_naive_check() {
+        let mut s = Session::new(4, 2);
+        for m in [&b"a"[..], b"b", b"c"] {
+            s.run(m);
+            let sig = s.signature();
+            let tweaked = [
+                sig.clone(),
+                Signature { A_hat: sig.A_hat, z: sig.z + Scalar::ONE },
+                Signature { A_hat: sig.A_hat + s.par.g, z: sig.z },
+            ];
+            for (k, sig) in tweaked.iter().enumerate() {
+                let c = hsig(&sig.A_hat, &s.pk_joint, &s.ss, m);
+                let naive = s.par.g * sig.z == sig.A_hat + s.pk_joint * c;
+                assert_eq!(verify(&s.par, &s.pk_joint, &s.ss, m, sig), naive);
+                assert_eq!(naive, k == 0);
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_ids_do_not_count_toward_the_quorum() {
+        let s = Session::new(4, 2);
+        let sig3 = |ss: &[u32]| {
+            SigningSession::new(&s.par, MSG, ss, &s.pk_joint, &s.pk_map)
+                .sig3(1, s.sk(1), &s.states[0], &s.commitments, &s.openings)
+                .err()
+        };
+        // Three entries, but only two distinct signers.
+        assert_eq!(sig3(&[1, 2, 1]), Some(SignError::InvalidSigningSet(1)));
+        assert_eq!(sig3(&[1, 2]), Some(SignError::QuorumTooSmall));
+    }
+
+    #[test]
+    fn unanimous_minus_one_threshold_needs_every_signer() {
+        let s = Session::new(5, 4);
+        assert_eq!(s.ss, vec![1, 2, 3, 4, 5]);
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &s.signature()));
+        assert_eq!(
+            SigningSession::new(&s.par, MSG, &[1, 2, 3, 4], &s.pk_joint, &s.pk_map)
+                .sig3(1, s.sk(1), &s.states[0], &s.commitments, &s.openings)
+                .err(),
+            Some(SignError::QuorumTooSmall)
+        );
+        assert!(crate::keygen::setup(5, 5).is_err());
+    }
+
+    #[test]
+    fn presigned_states_sign_one_session_each() {
+        let s = Session::new(4, 2);
+        let mut pools: Vec<PresignPool> =
+            s.ss.iter().map(|&i| PresignPool::new(sig1_preprocess(&s.par, i, s.sk(i), 2))).collect();
+
+        let mut nonces = Vec::new();
+        for message in [&b"first"[..], b"second"] {
+            let (commitments, states1): (Vec<_>, Vec<_>) = pools
+                .iter_mut()
+                .map(|pool| {
+                    let (cm, st) = pool.take().unwrap();
+                    ((cm.i, cm.mu_i), st)
+                })
+                .unzip();
+            nonces.extend(states1.iter().map(|st| st.a_i));
+            let (_, _, openings, partials) = s.rounds_from(&s.ss, message, commitments, &states1);
+            let sig = combine(&s.ss, &openings, &partials).unwrap();
+            assert!(verify(&s.par, &s.pk_joint, &s.ss, message, &sig));
+        }
+
+        // Each pool handed out both of its states once and is now empty.
+        assert!(pools.iter_mut().all(|pool| pool.is_empty() && pool.take().is_none()));
+        for (k, a) in nonces.iter().enumerate() {
+            assert!(!nonces[..k].contains(a));
+        }
+    }
+
+    #[test]
+    fn deterministic_nonce_depends_on_message_and_counter_only() {
+        let s = Session::new(3, 1);
+        let (i, sk) = (1, s.sk(1));
+        let (cm, st) = sig1_deterministic(&s.par, i, sk, MSG, 7);
+        let (cm2, st2) = sig1_deterministic(&s.par, i, sk, MSG, 7);
+        assert_eq!(st.a_i, st2.a_i);
+        assert_eq!(st.rho_i, st2.rho_i);
+        assert_eq!(cm.mu_i, cm2.mu_i);
+
+        let (other_cm, other) = sig1_deterministic(&s.par, i, sk, b"another message", 7);
+        assert_ne!(other.a_i, st.a_i);
+        assert_ne!(other_cm.mu_i, cm.mu_i);
+        assert_ne!(sig1_deterministic(&s.par, i, sk, MSG, 8).1.a_i, st.a_i);
+        assert_ne!(sig1_deterministic(&s.par, 2, s.sk(2), MSG, 7).1.a_i, st.a_i);
+    }
+
+    #[test]
+    fn presign_pool_never_hands_out_a_state_twice() {
+        let s = Session::new(3, 1);
+        let mut pool = PresignPool::new(sig1_preprocess(&s.par, 1, s.sk(1), 3));
+        pool.refill(sig1_preprocess(&s.par, 1, s.sk(1), 2));
+        assert_eq!(pool.len(), 5);
+
+        let mut taken: Vec<(CommitmentMessage, SignerState)> = Vec::new();
+        while let Some((cm, st)) = pool.take() {
+            assert!(taken.iter().all(|(_, prev)| prev.a_i != st.a_i && prev.rho_i != st.rho_i));
+            assert_eq!(cm.mu_i, hcom(st.i, &st.rho_i, &st.b_i));
+            taken.push((cm, st));
+            assert_eq!(pool.len(), 5 - taken.len());
+        }
+        assert_eq!(taken.len(), 5);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn deterministic_sessions_repeat_commitments_and_verify() {
+        let s = Session::new(4, 2);
+        let round1 = |message: &[u8], counter| -> (Vec<(u32, [u8; 32])>, Vec<SignerState>) {
+            s.ss.iter()
+                .map(|&i| {
+                    let (cm, st) = sig1_deterministic(&s.par, i, s.sk(i), message, counter);
+                    ((cm.i, cm.mu_i), st)
+                })
+                .unzip()
+        };
+        let (commitments, states1) = round1(MSG, 1);
+        assert_eq!(round1(MSG, 1).0, commitments);
+        let (other, other_states) = round1(b"another message", 1);
+        for k in 0..s.ss.len() {
+            assert_ne!(other[k], commitments[k]);
+            assert_ne!(other_states[k].a_i, states1[k].a_i);
+        }
+
+        let (_, _, openings, partials) = s.rounds_from(&s.ss, MSG, commitments, &states1);
+        let sig = combine(&s.ss, &openings, &partials).unwrap();
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+    }
 }
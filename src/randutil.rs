@@ -1,14 +1,21 @@
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 
+#[cfg(feature = "std")]
 use rand::rngs::OsRng;
+#[cfg(feature = "std")]
 use rand::TryRngCore;
+use rand::CryptoRng;
 
-
+#[cfg(feature = "std")]
 pub fn random_scalar() -> Scalar {
+    random_scalar_with_rng(&mut OsRng.unwrap_err())
+}
+
+/// Uniform scalar from a caller-supplied RNG (e.g. a seeded one in tests).
+pub fn random_scalar_with_rng(rng: &mut impl CryptoRng) -> Scalar {
     let mut bytes = [0u8; 64];
-    let mut rng = OsRng;
-    rng.try_fill_bytes(&mut bytes).unwrap();
+    rng.fill_bytes(&mut bytes);
     Scalar::from_bytes_mod_order_wide(&bytes)
 }
 
@@ -1,6 +1,16 @@
+use alloc::{vec, vec::Vec};
+
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+#[cfg(feature = "std")]
+use rand::rngs::OsRng;
+#[cfg(feature = "std")]
+use rand::TryRngCore;
+use rand::CryptoRng;
 
-use crate::randutil::random_scalar;
+use crate::error::Error;
+use crate::randutil::random_scalar_with_rng;
 
 /// Degree-t polynomial represented by coefficients [c0, c1, ..., ct]
 #[derive(Clone, Debug)]
@@ -18,20 +28,62 @@ impl Poly {
         }
         acc
     }
+
+    /// The zero polynomial with `degree + 1` (zero) coefficients.
+    pub fn zero(degree: usize) -> Poly {
+        Poly { coeffs: vec![Scalar::ZERO; degree + 1] }
+    }
+
+    /// Coefficient-wise sum; the shorter polynomial is padded with zeros.
+    pub fn add(&self, other: &Poly) -> Poly {
+        let (long, short) = if self.coeffs.len() >= other.coeffs.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut coeffs = long.coeffs.clone();
+        for (c, d) in coeffs.iter_mut().zip(&short.coeffs) {
+            *c += d;
+        }
+        Poly { coeffs }
+    }
+
+    /// Every coefficient multiplied by `k`.
+    pub fn scale(&self, k: &Scalar) -> Poly {
+        Poly { coeffs: self.coeffs.iter().map(|c| c * k).collect() }
+    }
 }
 
 /// Sample random degree-t polynomial with chosen constant term.
+#[cfg(feature = "std")]
 pub fn sample_poly_with_constant(t: usize, c0: Scalar) -> Poly {
+    sample_poly_with_rng(t, c0, &mut OsRng.unwrap_err())
+}
+
+/// `sample_poly_with_constant` drawing from a caller-supplied RNG.
+pub fn sample_poly_with_rng(t: usize, c0: Scalar, rng: &mut impl CryptoRng) -> Poly {
     let mut coeffs = Vec::with_capacity(t + 1);
     coeffs.push(c0);
     for _ in 0..t {
-        coeffs.push(random_scalar());
+        coeffs.push(random_scalar_with_rng(rng));
     }
     Poly { coeffs }
 }
 
-/// Lagrange coefficient L_{i,SS} = Π_{k∈SS\{i}} k/(k-i)
-pub fn lagrange_coeff(i: u32, ss: &[u32]) -> Scalar {
+/// The first id in `ss` that is 0 or repeats an earlier one, if any.
+pub fn invalid_id(ss: &[u32]) -> Option<u32> {
+    ss.iter()
+        .enumerate()
+        .find(|&(k, &j)| j == 0 || ss[..k].contains(&j))
+        .map(|(_, &j)| j)
+}
+
+/// Lagrange coefficient L_{i,SS} = Π_{k∈SS\{i}} k/(k-i).
+/// None unless SS has distinct nonzero ids and contains i.
+pub fn lagrange_coeff(i: u32, ss: &[u32]) -> Option<Scalar> {
+    if invalid_id(ss).is_some() || !ss.contains(&i) {
+        return None;
+    }
     let i_s = Scalar::from(i as u64);
     let mut num = Scalar::ONE;
     let mut den = Scalar::ONE;
@@ -43,5 +95,180 @@ pub fn lagrange_coeff(i: u32, ss: &[u32]) -> Scalar {
         num *= k_s;
         den *= k_s - i_s;
     }
-    num * den.invert()
+    Some(num * den.invert())
+}
+
+/// L_{i,SS} for every i in a fixed SS, computed once. With N = Π_{k∈SS} k,
+/// L_{i,SS} = N / (i * Π_{k∈SS\{i}} (k-i)), so the table shares N across
+/// all entries and inverts the |SS| denominators together.
+#[derive(Clone, Debug)]
+pub struct LagrangeTable {
+    ids: Vec<u32>,
+    coeffs: Vec<Scalar>,
+}
+
+impl LagrangeTable {
+    /// None unless SS has distinct nonzero ids (see `invalid_id`).
+    pub fn new(ss: &[u32]) -> Option<LagrangeTable> {
+        if invalid_id(ss).is_some() {
+            return None;
+        }
+        let n: Scalar = ss.iter().map(|&k| Scalar::from(k as u64)).product();
+        let mut dens: Vec<Scalar> = ss
+            .iter()
+            .map(|&i| {
+                let i_s = Scalar::from(i as u64);
+                ss.iter()
+                    .filter(|&&k| k != i)
+                    .fold(i_s, |acc, &k| acc * (Scalar::from(k as u64) - i_s))
+            })
+            .collect();
+        Scalar::batch_invert(&mut dens);
+        Some(LagrangeTable {
+            ids: ss.to_vec(),
+            coeffs: dens.into_iter().map(|d| n * d).collect(),
+        })
+    }
+
+    /// The signing set the table was built for.
+    pub fn ids(&self) -> &[u32] {
+        &self.ids
+    }
+
+    /// L_{i,SS}; None if i is not in SS.
+    pub fn get(&self, i: u32) -> Option<Scalar> {
+        self.ids.iter().position(|&k| k == i).map(|p| self.coeffs[p])
+    }
+}
+
+/// Σ_j L_{j,ids} P_j: the value at 0 of the degree-(|ids|-1) polynomial "in
+/// the exponent" through (j, P_j), e.g. g*s(0) from g*s(j). Ids must be
+/// distinct and nonzero (None otherwise). Variable time, so for public
+/// points only.
+pub fn interpolate_points(points: &[(u32, RistrettoPoint)]) -> Option<RistrettoPoint> {
+    let ids: Vec<u32> = points.iter().map(|(j, _)| *j).collect();
+    let table = LagrangeTable::new(&ids)?;
+    Some(RistrettoPoint::vartime_multiscalar_mul(
+        &table.coeffs,
+        points.iter().map(|(_, p)| p),
+    ))
+}
+
+/// The value at x of the unique degree-(|points|-1) polynomial through the
+/// (id, value) pairs. Fails with Error::InvalidSignerId if an id is 0 or
+/// repeated, and with Error::NotEnoughShares if `points` is empty.
+pub fn interpolate_at(points: &[(u32, Scalar)], x: Scalar) -> Result<Scalar, Error> {
+    if points.is_empty() {
+        return Err(Error::NotEnoughShares { got: 0, required: 1 });
+    }
+    let ids: Vec<u32> = points.iter().map(|(j, _)| *j).collect();
+    if let Some(j) = invalid_id(&ids) {
+        return Err(Error::InvalidSignerId(j));
+    }
+
+    let mut acc = Scalar::ZERO;
+    for &(j, y) in points {
+        let j_s = Scalar::from(j as u64);
+        let mut num = Scalar::ONE;
+        let mut den = Scalar::ONE;
+        for &k in &ids {
+            if k == j {
+                continue;
+            }
+            let k_s = Scalar::from(k as u64);
+            num *= x - k_s;
+            den *= j_s - k_s;
+        }
+        acc += y * num * den.invert();
+    }
+    Ok(acc)
+}
+
+/// s(0) from the shares (j, s(j)) of a degree-t sharing; needs at least
+/// t+1 of them (Error::NotEnoughShares otherwise).
+pub fn recover_secret(points: &[(u32, Scalar)], t: usize) -> Result<Scalar, Error> {
+    if points.len() < t + 1 {
+        return Err(Error::NotEnoughShares { got: points.len(), required: t + 1 });
+    }
+    interpolate_at(points, Scalar::ZERO)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::randutil::random_scalar;
+
+    #[test]
+    fn add_and_scale_commute_with_eval() {
+        let p = sample_poly_with_constant(3, random_scalar());
+        let q = sample_poly_with_constant(1, random_scalar());
+        let k = random_scalar();
+        for _ in 0..8 {
+            let x = random_scalar();
+            assert_eq!(p.add(&q).eval(x), p.eval(x) + q.eval(x));
+            assert_eq!(q.add(&p).eval(x), p.eval(x) + q.eval(x));
+            assert_eq!(p.scale(&k).eval(x), p.eval(x) * k);
+            assert_eq!(Poly::zero(2).eval(x), Scalar::ZERO);
+            assert_eq!(p.add(&Poly::zero(5)).eval(x), p.eval(x));
+        }
+    }
+
+    #[test]
+    fn add_scale_and_zero_at_fixed_points() {
+        let int = |v: &[u64]| Poly { coeffs: v.iter().map(|&c| Scalar::from(c)).collect() };
+        let p = int(&[1, 2, 3]); // 1 + 2x + 3x^2
+        let q = int(&[5, 7]); // 5 + 7x
+        let sum = p.add(&q);
+        assert_eq!(sum.coeffs, int(&[6, 9, 3]).coeffs);
+        assert_eq!(q.add(&p).coeffs, sum.coeffs);
+        assert_eq!(p.scale(&Scalar::from(2u64)).coeffs, int(&[2, 4, 6]).coeffs);
+        assert_eq!(Poly::zero(3).coeffs.len(), 4);
+
+        for (x, p_x, q_x) in [(0u64, 1u64, 5u64), (1, 6, 12), (2, 17, 19), (10, 321, 75)] {
+            let x = Scalar::from(x);
+            assert_eq!(p.eval(x), Scalar::from(p_x));
+            assert_eq!(q.eval(x), Scalar::from(q_x));
+            assert_eq!(sum.eval(x), Scalar::from(p_x + q_x));
+            assert_eq!(p.scale(&Scalar::ZERO).eval(x), Scalar::ZERO);
+            assert_eq!(Poly::zero(0).add(&p).eval(x), p.eval(x));
+        }
+    }
+
+    #[test]
+    fn recover_secret_needs_t_plus_one_distinct_shares() {
+        for (n, t) in [(1, 0), (3, 1), (5, 2), (7, 6)] {
+            let secret = random_scalar();
+            let f = sample_poly_with_constant(t, secret);
+            let shares: Vec<(u32, Scalar)> = (1..=n as u32).map(|j| (j, f.eval(Scalar::from(j as u64)))).collect();
+
+            assert_eq!(recover_secret(&shares[..t + 1], t), Ok(secret));
+            assert_eq!(recover_secret(&shares[n - t - 1..], t), Ok(secret));
+            let x = random_scalar();
+            assert_eq!(interpolate_at(&shares[..t + 1], x), Ok(f.eval(x)));
+
+            assert_eq!(
+                recover_secret(&shares[..t], t),
+                Err(Error::NotEnoughShares { got: t, required: t + 1 })
+            );
+            let mut repeated = shares[..t + 1].to_vec();
+            repeated.push(shares[0]);
+            assert_eq!(recover_secret(&repeated, t), Err(Error::InvalidSignerId(1)));
+        }
+        assert_eq!(interpolate_at(&[], Scalar::ZERO), Err(Error::NotEnoughShares { got: 0, required: 1 }));
+        assert_eq!(interpolate_at(&[(0, Scalar::ONE)], Scalar::ZERO), Err(Error::InvalidSignerId(0)));
+    }
+
+    #[test]
+    fn lagrange_table_matches_lagrange_coeff() {
+        for ss in [&[1u32][..], &[2, 5, 3], &[7, 1, 4, 10, 2]] {
+            let table = LagrangeTable::new(ss).unwrap();
+            assert_eq!(table.ids(), ss);
+            for &i in ss {
+                assert_eq!(table.get(i), lagrange_coeff(i, ss));
+            }
+            assert_eq!(table.get(99), None);
+        }
+        assert!(LagrangeTable::new(&[1, 2, 1]).is_none());
+        assert!(LagrangeTable::new(&[0, 2]).is_none());
+    }
 }
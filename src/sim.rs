@@ -0,0 +1,212 @@
+// src/sim.rs
+//
+// In-memory network simulation (test-support only).
+//
+// Models a fully connected network of n signers in which messages between
+// specific (from, to) pairs can be dropped and delivery order can be
+// shuffled, then drives the full Sig1 -> Sig2 -> Sig3 -> Combine flow.
+// Every signer takes part (SS = 1..=n) and sees only the messages the
+// network delivers to it, so a dropped message surfaces as the SignError
+// the receiving signer's Sig3 reports (ProofInvalid for a missed
+// commitment, which changes its (g0, g1); OpeningsMismatch for a missed
+// opening).
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+
+use crate::error::Error;
+use crate::keygen::{kgen_for_ids, setup};
+use crate::protocol::{combine, sig1, verify, SignError, SigningSession};
+use crate::types::{share_for, OpeningMessage, PartialSignature, Signature};
+
+#[derive(Clone, Debug)]
+pub struct NetworkSim {
+    n: usize,
+    t: usize,
+    dropped: HashSet<(u32, u32)>,
+    dropped_openings: HashSet<(u32, u32)>,
+    reorder: bool,
+}
+
+/// Result of one simulated session.
+#[derive(Clone, Debug)]
+pub struct SimReport {
+    /// Every signer's outcome, by id: the SignError it aborted with in
+    /// Sig2 or Sig3, or its partial signature.
+    pub signers: Vec<(u32, Result<PartialSignature, SignError>)>,
+    /// The verified signature, or Error::SignerAborted naming the signer
+    /// blamed by the first abort (see `SignError::signer`), or why
+    /// combining failed.
+    pub signature: Result<Signature, Error>,
+}
+
+impl SimReport {
+    /// The SignError signer `i` aborted with, if it did.
+    pub fn error_of(&self, i: u32) -> Option<&SignError> {
+        self.signers.iter().find(|(j, _)| *j == i).and_then(|(_, r)| r.as_ref().err())
+    }
+}
+
+impl NetworkSim {
+    pub fn new(n: usize, t: usize) -> Self {
+        NetworkSim { n, t, dropped: HashSet::new(), dropped_openings: HashSet::new(), reorder: false }
+    }
+
+    /// Drop every message sent by `from` to `to` (one direction only).
+    pub fn drop(mut self, from: u32, to: u32) -> Self {
+        self.dropped.insert((from, to));
+        self
+    }
+
+    /// Drop only the round-2 opening `from` sends to `to`.
+    pub fn drop_opening(mut self, from: u32, to: u32) -> Self {
+        self.dropped_openings.insert((from, to));
+        self
+    }
+
+    /// Deliver each inbox in a random order.
+    pub fn reorder(mut self) -> Self {
+        self.reorder = true;
+        self
+    }
+
+    fn delivers(&self, from: u32, to: u32) -> bool {
+        from == to || !self.dropped.contains(&(from, to))
+    }
+
+    /// Messages `to` receives out of `outbox` (sender id, payload).
+    fn inbox<T: Clone>(&self, to: u32, outbox: &[(u32, T)], extra: &HashSet<(u32, u32)>) -> Vec<T> {
+        let mut msgs: Vec<T> = outbox
+            .iter()
+            .filter(|(from, _)| self.delivers(*from, to) && !extra.contains(&(*from, to)))
+            .map(|(_, m)| m.clone())
+            .collect();
+        if self.reorder {
+            msgs.shuffle(&mut rand::rng());
+        }
+        msgs
+    }
+
+    /// Run keygen and one full signing session over `message`; the
+    /// signature, or the error in `SimReport::signature`.
+    pub fn run(&self, message: &[u8]) -> Result<Signature, Error> {
+        self.session(message)?.signature
+    }
+
+    /// Run keygen and one full signing session over `message`, reporting
+    /// every signer's outcome. Fails only if keygen does.
+    pub fn session(&self, message: &[u8]) -> Result<SimReport, Error> {
+        let par = setup(self.n, self.t)?;
+        let ss: Vec<u32> = (1..=self.n as u32).collect();
+        let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ss)?;
+        let pk_map: Vec<(u32, _)> = pk_shares.iter().map(|(i, pk)| (*i, pk.pk_i)).collect();
+        let sk_of = |i| share_for(&sk_shares, i).expect("kgen dealt every id");
+        let pk_of = |i| share_for(&pk_shares, i).expect("kgen dealt every id");
+        let none = HashSet::new();
+        let mut signing = SigningSession::new(&par, message, &ss, &pk_joint, &pk_map);
+
+        // Round 1: every signer broadcasts a commitment.
+        let mut commits = vec![];
+        let mut states = vec![];
+        for &i in &ss {
+            let (cm, st) = sig1(&par, i, sk_of(i));
+            commits.push((i, (cm.i, cm.mu_i)));
+            states.push(st);
+        }
+
+        // Round 2: each signer opens against the commitments it received.
+        let mut outcomes: Vec<(u32, Result<PartialSignature, SignError>)> = vec![];
+        let mut opens: Vec<(u32, OpeningMessage)> = vec![];
+        let mut states2 = vec![];
+        for (&i, st) in ss.iter().zip(&states) {
+            let recv = self.inbox(i, &commits, &none);
+            match signing.sig2(i, &recv, pk_of(i), sk_of(i), st) {
+                Ok((om, st2)) => {
+                    opens.push((i, om));
+                    states2.push((i, st2));
+                }
+                Err(e) => outcomes.push((i, Err(e))),
+            }
+        }
+
+        // Round 3: each signer that opened checks what it received and
+        // signs its share.
+        for (i, st2) in &states2 {
+            let i = *i;
+            let recv_commits = self.inbox(i, &commits, &none);
+            let recv_opens = self.inbox(i, &opens, &self.dropped_openings);
+            let res = signing.sig3(i, sk_of(i), st2, &recv_commits, &recv_opens);
+            outcomes.push((i, res));
+        }
+        outcomes.sort_by_key(|(i, _)| *i);
+
+        let signature = match outcomes.iter().find_map(|(i, r)| r.as_ref().err().map(|e| e.signer().unwrap_or(*i))) {
+            Some(j) => Err(Error::SignerAborted(j)),
+            None => {
+                let all_opens: Vec<OpeningMessage> = opens.into_iter().map(|(_, o)| o).collect();
+                let sigshares: Vec<PartialSignature> =
+                    outcomes.iter().filter_map(|(_, r)| r.as_ref().ok().cloned()).collect();
+                combine(&ss, &all_opens, &sigshares).and_then(|sig| {
+                    if verify(&par, &pk_joint, &ss, message, &sig) {
+                        Ok(sig)
+                    } else {
+                        Err(Error::InvalidSignature)
+                    }
+                })
+            }
+        };
+        Ok(SimReport { signers: outcomes, signature })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connected_network_signs() {
+        let report = NetworkSim::new(5, 2).reorder().session(b"sim").unwrap();
+        assert!(report.signers.iter().all(|(_, r)| r.is_ok()));
+        assert!(report.signature.is_ok());
+    }
+
+    #[test]
+    fn one_way_drop_splits_the_signers_views() {
+        // 1 cannot reach 2, so 2 derives (g0, g1) from one commitment fewer:
+        // 2 rejects the first proof it checks, and everyone else rejects
+        // the proof 2 made under its view. The coordinator blames 2.
+        let report = NetworkSim::new(5, 2).drop(1, 2).session(b"sim").unwrap();
+        assert_eq!(report.error_of(2), Some(&SignError::ProofInvalid(3)));
+        for i in [1, 3, 4, 5] {
+            assert_eq!(report.error_of(i), Some(&SignError::ProofInvalid(2)));
+        }
+        assert_eq!(report.signature.err(), Some(Error::SignerAborted(2)));
+    }
+
+    #[test]
+    fn a_dropped_opening_is_reported_as_missing() {
+        let report = NetworkSim::new(4, 1).drop_opening(3, 1).session(b"sim").unwrap();
+        assert_eq!(report.error_of(1), Some(&SignError::OpeningsMismatch(3)));
+        assert_eq!(report.error_of(3), None);
+        assert_eq!(report.signature.err(), Some(Error::SignerAborted(3)));
+    }
+
+    #[test]
+    fn partition_aborts_every_signer() {
+        // {1, 2} | {3, 4}: each side misses the other's messages.
+        let mut sim = NetworkSim::new(4, 2);
+        for a in [1, 2] {
+            for b in [3, 4] {
+                sim = sim.drop(a, b).drop(b, a);
+            }
+        }
+        // Each side agrees on its own view, so the proofs check out and
+        // the missing openings are what every signer reports.
+        let report = sim.session(b"sim").unwrap();
+        for (i, first_missing) in [(1, 3), (2, 3), (3, 1), (4, 1)] {
+            assert_eq!(report.error_of(i), Some(&SignError::OpeningsMismatch(first_missing)));
+        }
+        assert_eq!(sim.run(b"sim").err(), Some(Error::SignerAborted(3)));
+    }
+}
@@ -0,0 +1,174 @@
+// src/testutil.rs
+//
+// Fixtures shared by the unit tests and, behind `test-support`, the
+// benches: one honest signing session run up to (and including) Sig3, with
+// every intermediate message kept so a test can tamper with one and re-run
+// a later round. Not every test uses every field.
+#![allow(dead_code)]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use num_bigint::BigUint;
+
+use crate::keygen::{kgen_for_ids, setup};
+use crate::protocol::{combine, sig1, SigningSession};
+use crate::timed::TimedParamsWithTrapdoor;
+use crate::types::{
+    share_for, OpeningMessage, Params, PartialSignature, PublicKeyShare, SecretKeyShare, ShareCommitments,
+    Signature, SignerState,
+};
+
+pub const MSG: &[u8] = b"hello gargos threshold schnorr";
+
+/// Two fixed 256-bit safe primes, so the timed tests need not search for
+/// fresh ones; N = P*Q is exactly MIN_MODULUS_BITS long.
+pub(crate) const P: &str = "e68a3b962e0ba721c676166e93985e5645d905eb9df512fe32c2330d42b8036b";
+pub(crate) const Q: &str = "fe939af00004125d9762a1edf0b9b5159341b09443e3565d01d3efb27fa4adb7";
+
+/// Timed parameters over N = P*Q with g = 4 and T = `t` squarings.
+pub fn timed_params(t: u64) -> TimedParamsWithTrapdoor {
+    let p = BigUint::parse_bytes(P.as_bytes(), 16).unwrap();
+    let q = BigUint::parse_bytes(Q.as_bytes(), 16).unwrap();
+    TimedParamsWithTrapdoor::from_primes(p, q, BigUint::from(4u32), t).unwrap()
+}
+
+/// ℓ, the order of the Ristretto group, little-endian.
+const ELL: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+];
+
+/// The non-canonical encoding s + ℓ of the scalar encoded by `s` (which
+/// must be canonical, so the sum fits in 32 bytes).
+pub(crate) fn plus_ell(s: &[u8; 32]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let mut carry = 0u16;
+    for k in 0..32 {
+        let sum = s[k] as u16 + ELL[k] as u16 + carry;
+        out[k] = sum as u8;
+        carry = sum >> 8;
+    }
+    out
+}
+
+/// Run `value`'s destructor in place, then hand its (still owned, still
+/// initialized) storage to `read`, so a test can check what drop left
+/// behind without touching freed memory.
+pub(crate) fn after_drop<T, R>(value: T, read: impl FnOnce(*const T) -> R) -> R {
+    let mut slot = core::mem::MaybeUninit::new(value);
+    // SAFETY: `slot` holds a live T, dropped exactly once here; `read` only
+    // reads plain bytes from the storage, which outlives the call.
+    unsafe { core::ptr::drop_in_place(slot.as_mut_ptr()) };
+    read(slot.as_ptr())
+}
+
+/// (commitments, Sig2 states, openings, partial signatures) of one session.
+pub type Rounds =
+    (Vec<(u32, [u8; 32])>, Vec<SignerState>, Vec<OpeningMessage>, Vec<PartialSignature>);
+
+pub struct Session {
+    pub par: Params,
+    pub pk_joint: RistrettoPoint,
+    pub pk_shares: Vec<(u32, PublicKeyShare)>,
+    pub sk_shares: Vec<(u32, SecretKeyShare)>,
+    pub share_commitments: ShareCommitments,
+    pub pk_map: Vec<(u32, RistrettoPoint)>,
+    pub ss: Vec<u32>,
+    pub commitments: Vec<(u32, [u8; 32])>,
+    pub states: Vec<SignerState>,
+    pub openings: Vec<OpeningMessage>,
+    pub partials: Vec<PartialSignature>,
+}
+
+impl Session {
+    /// Keys for ids 1..=n, signed by the first t+1 of them.
+    pub fn new(n: usize, t: usize) -> Session {
+        let ids: Vec<u32> = (1..=n as u32).collect();
+        Session::with_ids(n, t, &ids, &ids[..t + 1])
+    }
+
+    /// Keys for `ids`, signed by `ss`.
+    pub fn with_ids(n: usize, t: usize, ids: &[u32], ss: &[u32]) -> Session {
+        let par = setup(n, t).unwrap();
+        let (pk_joint, pk_shares, sk_shares, share_commitments) = kgen_for_ids(&par, ids).unwrap();
+        let pk_map = pk_shares.iter().map(|(i, pk)| (*i, pk.pk_i)).collect();
+        let mut s = Session {
+            par,
+            pk_joint,
+            pk_shares,
+            sk_shares,
+            share_commitments,
+            pk_map,
+            ss: ss.to_vec(),
+            commitments: Vec::new(),
+            states: Vec::new(),
+            openings: Vec::new(),
+            partials: Vec::new(),
+        };
+        s.run(MSG);
+        s
+    }
+
+    pub fn sk(&self, i: u32) -> &SecretKeyShare {
+        share_for(&self.sk_shares, i).unwrap()
+    }
+
+    pub fn pk(&self, i: u32) -> &PublicKeyShare {
+        share_for(&self.pk_shares, i).unwrap()
+    }
+
+    /// The `SigningSession` of these keys and SS over `MSG`, drawing from
+    /// the OS rng.
+    pub fn signing(&self) -> SigningSession<'_> {
+        SigningSession::new(&self.par, MSG, &self.ss, &self.pk_joint, &self.pk_map)
+    }
+
+    /// A fresh session over `message` with the same keys and SS.
+    pub fn run(&mut self, message: &[u8]) {
+        let (commitments, states, openings, partials) = self.rounds(&self.ss, message);
+        self.commitments = commitments;
+        self.states = states;
+        self.openings = openings;
+        self.partials = partials;
+    }
+
+    /// All three rounds of an honest session over `ss`, with these keys.
+    pub fn rounds(&self, ss: &[u32], message: &[u8]) -> Rounds {
+        let mut commitments = Vec::new();
+        let mut states1 = Vec::new();
+        for &i in ss {
+            let (cm, st) = sig1(&self.par, i, self.sk(i));
+            commitments.push((cm.i, cm.mu_i));
+            states1.push(st);
+        }
+        self.rounds_from(ss, message, commitments, &states1)
+    }
+
+    /// Sig2 and Sig3 over `ss` from given first-round messages and states
+    /// (e.g. presigned ones).
+    pub fn rounds_from(
+        &self,
+        ss: &[u32],
+        message: &[u8],
+        commitments: Vec<(u32, [u8; 32])>,
+        states1: &[SignerState],
+    ) -> Rounds {
+        let mut session = SigningSession::new(&self.par, message, ss, &self.pk_joint, &self.pk_map);
+        let mut openings = Vec::new();
+        let mut states = Vec::new();
+        for (&i, st) in ss.iter().zip(states1) {
+            let (om, st2) = session.sig2(i, &commitments, self.pk(i), self.sk(i), st).unwrap();
+            openings.push(om);
+            states.push(st2);
+        }
+
+        let mut partials = Vec::new();
+        for (&i, st) in ss.iter().zip(&states) {
+            partials.push(session.sig3(i, self.sk(i), st, &commitments, &openings).unwrap());
+        }
+        (commitments, states, openings, partials)
+    }
+
+    pub fn signature(&self) -> Signature {
+        combine(&self.ss, &self.openings, &self.partials).unwrap()
+    }
+}
@@ -1,8 +1,79 @@
+use std::fmt;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use curve25519_dalek::scalar::Scalar;
 use num_bigint::{BigInt, BigUint, Sign};
+use num_integer::Integer;
 use num_traits::{One, Zero};
-use rand::RngCore;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512_256};
+use subtle::ConstantTimeEq;
+
+use crate::prime::{is_probable_prime, random_safe_prime, PrimeError};
+
+/// Smallest modulus `derive_h` accepts. Far below a secure RSA size; it only
+/// rules out toy or mistyped moduli (1, a small prime, ...).
+pub const MIN_MODULUS_BITS: u64 = 512;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimedError {
+    /// N is shorter than MIN_MODULUS_BITS.
+    ModulusTooSmall(u64),
+    /// N = 0 in deserialized parameters.
+    ZeroModulus,
+    /// N is even, so it cannot be a product of two odd primes.
+    EvenModulus,
+    /// gcd(g mod N, N) != 1: g is not a unit (or N is already factored by g).
+    BaseNotUnit,
+    /// Plaintext, read as a big-endian integer, is not below N.
+    PlaintextTooLarge,
+    /// Ciphertext AAD differs from the expected one.
+    AadMismatch,
+    /// The mask w^N is not invertible mod N^2 (u shares a factor with N).
+    NotInvertible,
+    /// Decrypted value does not fit the 32-byte output.
+    OutputTooLarge,
+    /// v with the mask removed is not of the form (1 + N)^s mod N^2, so v
+    /// was not produced by `timed_encrypt` under these parameters.
+    MalformedCiphertext,
+    /// A length-prefixed or hybrid payload did not decode (bad framing,
+    /// or the AEAD body failed to open).
+    MalformedPayload,
+    /// A claimed factor of N failed the primality test.
+    FactorNotPrime,
+    /// p == q, so N = p^2 and φ(N) is not (p-1)(q-1).
+    RepeatedFactor,
+    /// Prime generation for `setup_timed` failed.
+    Prime(PrimeError),
+}
+
+impl fmt::Display for TimedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimedError::ModulusTooSmall(bits) => {
+                write!(f, "modulus has {} bits, need at least {}", bits, MIN_MODULUS_BITS)
+            }
+            TimedError::ZeroModulus => write!(f, "modulus is zero"),
+            TimedError::EvenModulus => write!(f, "modulus is even"),
+            TimedError::BaseNotUnit => write!(f, "base is not a unit mod N"),
+            TimedError::PlaintextTooLarge => write!(f, "plaintext must be < N"),
+            TimedError::AadMismatch => write!(f, "AAD mismatch"),
+            TimedError::NotInvertible => write!(f, "mask not invertible mod N^2"),
+            TimedError::OutputTooLarge => write!(f, "decrypted value exceeds 32 bytes"),
+            TimedError::MalformedCiphertext => write!(f, "malformed timed ciphertext"),
+            TimedError::MalformedPayload => write!(f, "malformed timed payload"),
+            TimedError::FactorNotPrime => write!(f, "factor of N is not prime"),
+            TimedError::RepeatedFactor => write!(f, "p and q are equal"),
+            TimedError::Prime(e) => write!(f, "prime generation failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for TimedError {}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "TimedParamsWire", into = "TimedParamsWire")]
 pub struct TimedParams {
     pub n: BigUint,   // RSA modulus N = p*q
     pub g: BigUint,   // base in Z*_N
@@ -10,6 +81,167 @@ pub struct TimedParams {
     pub t: u64,       // number of squarings
 }
 
+/// Serde form of TimedParams: big-endian bytes of n, g, h.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct TimedParamsWire {
+    n: Vec<u8>,
+    g: Vec<u8>,
+    h: Vec<u8>,
+    t: u64,
+}
+
+impl From<TimedParams> for TimedParamsWire {
+    fn from(pp: TimedParams) -> Self {
+        TimedParamsWire {
+            n: pp.n.to_bytes_be(),
+            g: pp.g.to_bytes_be(),
+            h: pp.h.to_bytes_be(),
+            t: pp.t,
+        }
+    }
+}
+
+impl TryFrom<TimedParamsWire> for TimedParams {
+    type Error = TimedError;
+
+    /// Rejects N = 0; the fuller (O(T)) check is `validate`.
+    fn try_from(w: TimedParamsWire) -> Result<Self, TimedError> {
+        let n = BigUint::from_bytes_be(&w.n);
+        if n.is_zero() {
+            return Err(TimedError::ZeroModulus);
+        }
+        Ok(TimedParams {
+            n,
+            g: BigUint::from_bytes_be(&w.g),
+            h: BigUint::from_bytes_be(&w.h),
+            t: w.t,
+        })
+    }
+}
+
+impl TimedParams {
+    /// True iff 1 < g < N and h == g^{2^T} mod N, with N passing the
+    /// `derive_h` checks. Redoes all T squarings: meant for auditing
+    /// parameters once at setup, not for every encryption.
+    pub fn validate(&self) -> bool {
+        if self.g <= BigUint::one() || self.g >= self.n {
+            return false;
+        }
+        derive_h(&self.n, &self.g, self.t).is_ok_and(|h| h == self.h)
+    }
+}
+
+/// Public parameters plus the factorization N = p*q. Whoever holds it can
+/// compute u^{2^T} mod N without the sequential squarings. Debug output
+/// leaves p and q out.
+#[derive(Clone)]
+pub struct TimedParamsWithTrapdoor {
+    pub params: TimedParams,
+    pub p: BigUint,
+    pub q: BigUint,
+}
+
+impl fmt::Debug for TimedParamsWithTrapdoor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedParamsWithTrapdoor")
+            .field("params", &self.params)
+            .field("p", &"<redacted>")
+            .field("q", &"<redacted>")
+            .finish()
+    }
+}
+
+impl TimedParamsWithTrapdoor {
+    /// Build N = p*q and h = g^{2^T} mod N, using the trapdoor for h.
+    /// p and q must be distinct primes (40 Miller–Rabin rounds each).
+    pub fn from_primes(p: BigUint, q: BigUint, g: BigUint, t: u64) -> Result<Self, TimedError> {
+        if p == q {
+            return Err(TimedError::RepeatedFactor);
+        }
+        let mut rng = rand::rng();
+        if !is_probable_prime(&p, 40, &mut rng) || !is_probable_prime(&q, 40, &mut rng) {
+            return Err(TimedError::FactorNotPrime);
+        }
+        let n = &p * &q;
+        // Same checks as derive_h, without its T squarings.
+        if n.bits() < MIN_MODULUS_BITS {
+            return Err(TimedError::ModulusTooSmall(n.bits()));
+        }
+        if n.is_even() {
+            return Err(TimedError::EvenModulus);
+        }
+        if !(&g % &n).gcd(&n).is_one() {
+            return Err(TimedError::BaseNotUnit);
+        }
+        let mut td = TimedParamsWithTrapdoor {
+            params: TimedParams { n, g, h: BigUint::zero(), t },
+            p,
+            q,
+        };
+        td.params.h = td.pow_2t(&td.params.g);
+        Ok(td)
+    }
+
+    /// The public parameters without p and q, safe to publish.
+    pub fn public_view(&self) -> TimedParams {
+        self.params.clone()
+    }
+
+    /// x^{2^T} mod N via the exponent 2^T mod λ(N), λ(N) = lcm(p-1, q-1).
+    /// Costs one exponentiation whatever T is; x must be a unit mod N.
+    fn pow_2t(&self, x: &BigUint) -> BigUint {
+        let n = &self.params.n;
+        let lambda = (&self.p - 1u32).lcm(&(&self.q - 1u32));
+        let e = BigUint::from(2u32).modpow(&BigUint::from(self.params.t), &lambda);
+        (x % n).modpow(&e, n)
+    }
+}
+
+/// Fresh timed parameters over N = p*q with safe primes p = 2p'+1,
+/// q = 2q'+1 of `bits`/2 bits each. g = x^2 for random x with g != 1 mod p
+/// and mod q, so g generates the whole group of squares, of order p'q'.
+/// The factorization is kept; `.params` is the public part.
+pub fn setup_timed(bits: u64, t: u64, rng: &mut impl CryptoRng) -> Result<TimedParamsWithTrapdoor, TimedError> {
+    if bits < MIN_MODULUS_BITS {
+        return Err(TimedError::ModulusTooSmall(bits));
+    }
+    let p = random_safe_prime((bits / 2) as usize, rng).map_err(TimedError::Prime)?;
+    let q = loop {
+        let q = random_safe_prime((bits - bits / 2) as usize, rng).map_err(TimedError::Prime)?;
+        if q != p {
+            break q;
+        }
+    };
+    let n = &p * &q;
+
+    let mut buf = vec![0u8; n.bits().div_ceil(8) as usize];
+    let g = loop {
+        rng.fill_bytes(&mut buf);
+        let x = BigUint::from_bytes_be(&buf) % &n;
+        let g = (&x * &x) % &n;
+        let order_full = !g.is_zero() && g.gcd(&n).is_one() && (&g - 1u32).gcd(&n).is_one();
+        if order_full {
+            break g;
+        }
+    };
+
+    TimedParamsWithTrapdoor::from_primes(p, q, g, t)
+}
+
+/// Wesolowski proof π = u^{⌊2^{T-1}/ℓ⌋} mod N (big-endian) that
+/// w = u^{2^T}; see `prove_squaring`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct WesolowskiProof {
+    pub pi: Vec<u8>,
+}
+
+/// Pietrzak proof that w = u^{2^T}: one midpoint per halving, big-endian,
+/// each as the smaller of ±μ mod N; see `prove_squaring_pietrzak`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PietrzakProof {
+    pub mu: Vec<Vec<u8>>,
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct TimedCiphertext {
     pub u: Vec<u8>,   // mod N
@@ -17,14 +249,67 @@ pub struct TimedCiphertext {
     pub aad: Vec<u8>,
 }
 
-// x <- x^{2^t} mod N  via t sequential squarings
-fn pow_2t_mod(mut x: BigUint, t: u64, n: &BigUint) -> BigUint {
-    for _ in 0..t {
-        x = (&x * &x) % n;
+// x <- x^{2^t} mod N  via t sequential squarings.
+//
+// Same result as `for _ in 0..t { x = x*x % N }`, but each chunk of up to
+// 2^16 squarings is one `modpow` by 2^k: for odd N num-bigint runs it in
+// Montgomery form on reused buffers instead of a fresh multiply and
+// division per step. The chain itself stays sequential.
+fn pow_2t_mod(mut x: BigUint, mut t: u64, n: &BigUint) -> BigUint {
+    const CHUNK: u64 = 1 << 16;
+    x %= n;
+    while t > 0 {
+        let k = t.min(CHUNK);
+        x = x.modpow(&(BigUint::one() << k), n);
+        t -= k;
     }
     x
 }
 
+/// Sequential squarings per second mod a random odd `bits`-bit modulus, as
+/// done by `timed_decrypt` and `derive_h`. Useful to pick T for a target
+/// delay on this machine. Fails with `ModulusTooSmall` for `bits` < 2.
+pub fn benchmark_squaring_rate(bits: u64) -> Result<f64, TimedError> {
+    const SQUARINGS: u64 = 1 << 14;
+    if bits < 2 {
+        return Err(TimedError::ModulusTooSmall(bits));
+    }
+    let mut rng = rand::rng();
+    let mut buf = vec![0u8; bits.div_ceil(8) as usize];
+    rng.fill_bytes(&mut buf);
+    let mut n = BigUint::from_bytes_be(&buf) >> (buf.len() as u64 * 8 - bits);
+    n.set_bit(bits - 1, true);
+    n.set_bit(0, true);
+    rng.fill_bytes(&mut buf);
+    let x = BigUint::from_bytes_be(&buf);
+
+    let start = std::time::Instant::now();
+    std::hint::black_box(pow_2t_mod(x, SQUARINGS, &n));
+    Ok(SQUARINGS as f64 / start.elapsed().as_secs_f64())
+}
+
+// Fiat–Shamir challenge ℓ: the first prime >= 2^127 + H(N, T, u, w) mod 2^127.
+fn hash_to_prime(n: &BigUint, t: u64, u: &BigUint, w: &BigUint) -> BigUint {
+    let mut h = Sha512_256::new();
+    h.update(b"Gargos::WesolowskiL");
+    h.update(t.to_le_bytes());
+    for x in [n, u, w] {
+        let bytes = x.to_bytes_be();
+        h.update((bytes.len() as u64).to_le_bytes());
+        h.update(&bytes);
+    }
+    let digest: [u8; 32] = h.finalize().into();
+
+    let mut l = BigUint::from_bytes_be(&digest[..16]);
+    l.set_bit(127, true);
+    l.set_bit(0, true);
+    let mut rng = rand::rng();
+    while !is_probable_prime(&l, 40, &mut rng) {
+        l += 2u32;
+    }
+    l
+}
+
 fn egcd(a: BigInt, b: BigInt) -> (BigInt, BigInt, BigInt) {
     if b.is_zero() {
         (a, BigInt::one(), BigInt::zero())
@@ -44,11 +329,174 @@ fn modinv(a: &BigUint, m: &BigUint) -> Option<BigUint> {
     Some(x.to_biguint().unwrap())
 }
 
-pub fn derive_h(n: &BigUint, g: &BigUint, t: u64) -> BigUint {
-    pow_2t_mod(g.clone() % n, t, n)
+/// h = g^{2^T} mod N, after checking that N is odd and at least
+/// MIN_MODULUS_BITS long and that g is a unit mod N.
+pub fn derive_h(n: &BigUint, g: &BigUint, t: u64) -> Result<BigUint, TimedError> {
+    if n.bits() < MIN_MODULUS_BITS {
+        return Err(TimedError::ModulusTooSmall(n.bits()));
+    }
+    if n.is_even() {
+        return Err(TimedError::EvenModulus);
+    }
+    let g = g % n;
+    if !g.gcd(n).is_one() {
+        return Err(TimedError::BaseNotUnit);
+    }
+    Ok(pow_2t_mod(g, t, n))
+}
+
+/// w = u^{2^T} mod N together with a Wesolowski proof that lets anyone
+/// check w in two short exponentiations instead of T squarings.
+///
+/// The proof is for y = u^{2^{T-1}}, and the verifier squares y to get w.
+/// In Z*_N a prover can multiply π by -1 and so shift y to -y; squaring
+/// maps both to the same w, so w itself is fixed exactly.
+pub fn prove_squaring(pp: &TimedParams, u: &BigUint) -> (BigUint, WesolowskiProof) {
+    let n = &pp.n;
+    let u = u % n;
+    let w = pow_2t_mod(u.clone(), pp.t, n);
+    let Some(t) = pp.t.checked_sub(1) else {
+        return (w, WesolowskiProof { pi: vec![1] });
+    };
+
+    // π = u^{⌊2^t/ℓ⌋}, the quotient produced bit by bit by long division
+    // of 2^t by ℓ rather than held as a t-bit integer.
+    let l = hash_to_prime(n, pp.t, &u, &w);
+    let mut rem = BigUint::one();
+    let mut pi = BigUint::one();
+    for _ in 0..t {
+        rem <<= 1;
+        pi = &pi * &pi % n;
+        if rem >= l {
+            rem -= &l;
+            pi = pi * &u % n;
+        }
+    }
+
+    (w, WesolowskiProof { pi: pi.to_bytes_be() })
+}
+
+/// Accept iff (π^ℓ · u^{2^{T-1} mod ℓ})² == w (mod N) for the
+/// Fiat–Shamir prime ℓ. For T = 0 the proof is π = 1 and w must equal u.
+pub fn verify_squaring(pp: &TimedParams, u: &BigUint, w: &BigUint, proof: &WesolowskiProof) -> bool {
+    let n = &pp.n;
+    let pi = BigUint::from_bytes_be(&proof.pi);
+    if pi.is_zero() || &pi >= n || w.is_zero() || w >= n {
+        return false;
+    }
+    let u = u % n;
+    let Some(t) = pp.t.checked_sub(1) else {
+        return pi.is_one() && *w == u;
+    };
+
+    let l = hash_to_prime(n, pp.t, &u, w);
+    let r = BigUint::from(2u32).modpow(&BigUint::from(t), &l);
+    let y = pi.modpow(&l, n) * u.modpow(&r, n) % n;
+    &y * &y % n == *w
+}
+
+// Fiat–Shamir challenge r of one Pietrzak halving: 128 bits of
+// H(N, T, τ, x, y, μ²).
+fn pietrzak_r(n: &BigUint, t: u64, tau: u64, x: &BigUint, y: &BigUint, mu_sq: &BigUint) -> BigUint {
+    let mut h = Sha512_256::new();
+    h.update(b"Gargos::PietrzakR");
+    h.update(t.to_le_bytes());
+    h.update(tau.to_le_bytes());
+    for v in [n, x, y, mu_sq] {
+        let bytes = v.to_bytes_be();
+        h.update((bytes.len() as u64).to_le_bytes());
+        h.update(&bytes);
+    }
+    let digest: [u8; 32] = h.finalize().into();
+    BigUint::from_bytes_be(&digest[..16])
+}
+
+/// Pietrzak proof that w = u^{2^T} mod N, an alternative to the Wesolowski
+/// proof of `prove_squaring` with no hash-to-prime step. `w` must be
+/// u^{2^T} mod N, e.g. from `prove_squaring` or the solver's own squarings;
+/// the proof is for that w and does not recompute it.
+///
+/// Like the Wesolowski variant it pins w exactly rather than up to a sign.
+/// The recursion runs on squares: the claim is w = (u²)^{2^{T-1}}, and the
+/// verifier squares each midpoint μ before use, so the elements of order 2
+/// in Z*_N (which would let a prover flip signs) drop out. At each level
+/// with τ squarings left: for odd τ, x is squared and τ decremented; for
+/// even τ, the midpoint μ with μ² = x^{2^{τ/2}} is sent, and with
+/// r = H(…, μ²) the claim becomes x^r μ² ↦ (μ²)^r y over τ/2 squarings,
+/// down to τ = 1, which the verifier checks directly.
+///
+/// About log2(T) midpoints. The prover recomputes each one from a known
+/// square root of x, which costs about T more squarings in total.
+pub fn prove_squaring_pietrzak(pp: &TimedParams, u: &BigUint, w: &BigUint) -> PietrzakProof {
+    let n = &pp.n;
+    let mut mu = Vec::new();
+    let Some(mut tau) = pp.t.checked_sub(1) else {
+        return PietrzakProof { mu };
+    };
+    // Invariant: x = s², and for an honest w, y = x^{2^τ}.
+    let mut s = u % n;
+    let mut x = &s * &s % n;
+    let mut y = w % n;
+    while tau > 1 {
+        if tau % 2 == 1 {
+            s = &s * &s % n;
+            x = &x * &x % n;
+            tau -= 1;
+            continue;
+        }
+        let half = tau / 2;
+        let m = pow_2t_mod(s.clone(), half, n);
+        let neg = n - &m;
+        let m = m.min(neg);
+        let m_sq = &m * &m % n;
+        let r = pietrzak_r(n, pp.t, tau, &x, &y, &m_sq);
+        s = s.modpow(&r, n) * &m % n;
+        x = x.modpow(&r, n) * &m_sq % n;
+        y = m_sq.modpow(&r, n) * &y % n;
+        mu.push(m.to_bytes_be());
+        tau = half;
+    }
+    PietrzakProof { mu }
+}
+
+/// Accept iff `proof` shows w = u^{2^T} mod N (see
+/// `prove_squaring_pietrzak`): O(log T) exponentiations by 128-bit
+/// challenges. Each midpoint must be in [1, (N-1)/2] and there must be
+/// exactly one per halving. For T = 0 the proof is empty and w must equal u.
+pub fn verify_squaring_pietrzak(pp: &TimedParams, u: &BigUint, w: &BigUint, proof: &PietrzakProof) -> bool {
+    let n = &pp.n;
+    if w.is_zero() || w >= n {
+        return false;
+    }
+    let u = u % n;
+    let Some(mut tau) = pp.t.checked_sub(1) else {
+        return proof.mu.is_empty() && *w == u;
+    };
+    let mut x = &u * &u % n;
+    let mut y = w.clone();
+    let mut mus = proof.mu.iter();
+    while tau > 1 {
+        if tau % 2 == 1 {
+            x = &x * &x % n;
+            tau -= 1;
+            continue;
+        }
+        let Some(m) = mus.next().map(|m| BigUint::from_bytes_be(m)) else {
+            return false;
+        };
+        if m.is_zero() || m > (n - 1u32) / 2u32 {
+            return false;
+        }
+        let m_sq = &m * &m % n;
+        let r = pietrzak_r(n, pp.t, tau, &x, &y, &m_sq);
+        x = x.modpow(&r, n) * &m_sq % n;
+        y = m_sq.modpow(&r, n) * &y % n;
+        tau /= 2;
+    }
+    mus.next().is_none() && y == x.modpow(&(BigUint::one() << tau), n)
 }
 
-fn paillier_L(x: &BigUint, n: &BigUint) -> BigUint {
+fn paillier_l(x: &BigUint, n: &BigUint) -> BigUint {
     (x - BigUint::one()) / n
 }
 
@@ -59,7 +507,7 @@ fn sample_r(n: &BigUint) -> BigUint {
     let mut rng = rand::rng();   // <-- this is the new RNG in rand 0.9
 
     loop {
-        let mut buf = vec![0u8; (n2.bits() as usize + 7) / 8];
+        let mut buf = vec![0u8; n2.bits().div_ceil(8) as usize];
         rng.fill_bytes(&mut buf);
         let r = BigUint::from_bytes_be(&buf) % &n2;
         if !r.is_zero() {
@@ -69,9 +517,11 @@ fn sample_r(n: &BigUint) -> BigUint {
 }
 
 
-pub fn timed_encrypt(pp: &TimedParams, plaintext: &[u8], aad: &[u8]) -> TimedCiphertext {
+pub fn timed_encrypt(pp: &TimedParams, plaintext: &[u8], aad: &[u8]) -> Result<TimedCiphertext, TimedError> {
     let s = BigUint::from_bytes_be(plaintext);
-    assert!(s < pp.n, "plaintext must be < N");
+    if s >= pp.n {
+        return Err(TimedError::PlaintextTooLarge);
+    }
 
     let n = &pp.n;
     let n2 = n * n;
@@ -80,46 +530,617 @@ pub fn timed_encrypt(pp: &TimedParams, plaintext: &[u8], aad: &[u8]) -> TimedCip
     let u = pp.g.modpow(&r, n);
 
     let one_plus_n = n + BigUint::one();
-    let rN = &r * n;
-    let term1 = (pp.h.clone() % &n2).modpow(&rN, &n2);
+    let r_n = &r * n;
+    let term1 = (pp.h.clone() % &n2).modpow(&r_n, &n2);
     let term2 = one_plus_n.modpow(&s, &n2);
     let v = (term1 * term2) % &n2;
 
-    TimedCiphertext {
+    Ok(TimedCiphertext {
         u: u.to_bytes_be(),
         v: v.to_bytes_be(),
         aad: aad.to_vec(),
+    })
+}
+
+// Constant-time AAD check, so a mismatch does not reveal how long a prefix
+// of the expected AAD the ciphertext agreed with.
+fn aad_matches(ct: &TimedCiphertext, aad_expected: &[u8]) -> bool {
+    ct.aad.ct_eq(aad_expected).into()
+}
+
+pub fn timed_decrypt(pp: &TimedParams, ct: &TimedCiphertext, aad_expected: &[u8]) -> Result<Vec<u8>, TimedError> {
+    plaintext_bytes(solve_to_int(pp, ct, aad_expected)?)
+}
+
+/// `timed_decrypt` for the holder of the factorization: w = u^{2^T} is
+/// computed through the trapdoor, so the cost does not depend on T.
+pub fn timed_decrypt_trapdoor(
+    td: &TimedParamsWithTrapdoor,
+    ct: &TimedCiphertext,
+    aad_expected: &[u8],
+) -> Result<Vec<u8>, TimedError> {
+    if !aad_matches(ct, aad_expected) { return Err(TimedError::AadMismatch); }
+
+    let w = td.pow_2t(&BigUint::from_bytes_be(&ct.u));
+    plaintext_bytes(open_to_int(&td.params, ct, w)?)
+}
+
+// The plaintext s as the 32 big-endian bytes `timed_encrypt` took.
+fn plaintext_bytes(s: BigUint) -> Result<Vec<u8>, TimedError> {
+    let mut out = s.to_bytes_be();
+    if out.len() > 32 { return Err(TimedError::OutputTooLarge); }
+    if out.len() < 32 {
+        let mut pad = vec![0u8; 32 - out.len()];
+        pad.extend_from_slice(&out);
+        out = pad;
     }
+    Ok(out)
 }
 
-pub fn timed_decrypt(pp: &TimedParams, ct: &TimedCiphertext, aad_expected: &[u8]) -> Option<Vec<u8>> {
-    if ct.aad != aad_expected { return None; }
+// The decryption path without the trapdoor: check the AAD, compute
+// w = u^{2^T} mod N by T sequential squarings and open ct with it.
+fn solve_to_int(pp: &TimedParams, ct: &TimedCiphertext, aad_expected: &[u8]) -> Result<BigUint, TimedError> {
+    if !aad_matches(ct, aad_expected) { return Err(TimedError::AadMismatch); }
 
     let n = &pp.n;
-    let n2 = n * n;
+    let w = pow_2t_mod(BigUint::from_bytes_be(&ct.u) % n, pp.t, n);
+    open_to_int(pp, ct, w)
+}
 
-    let u = BigUint::from_bytes_be(&ct.u);
+// Strip the mask w^N from v and read s off (1 + N)^s.
+fn open_to_int(pp: &TimedParams, ct: &TimedCiphertext, w: BigUint) -> Result<BigUint, TimedError> {
+    let n = &pp.n;
+    let n2 = n * n;
     let v = BigUint::from_bytes_be(&ct.v) % &n2;
 
-    // w = u^{2^T} mod N
-    let w = pow_2t_mod(u % n, pp.t, n);
-
     // w^N mod N^2
-    let wN = (w % &n2).modpow(n, &n2);
-    let inv_wN = modinv(&wN, &n2)?;
+    let w_n = (w % &n2).modpow(n, &n2);
+    let inv_w_n = modinv(&w_n, &n2).ok_or(TimedError::NotInvertible)?;
 
-    // (1 + N)^s mod N^2
-    let x = (v * inv_wN) % &n2;
+    // (1 + N)^s mod N^2, which is 1 mod N. This also rules out x = 0
+    // (e.g. v = 0), where L(x) would underflow.
+    let x = (v * inv_w_n) % &n2;
+    if &x % n != BigUint::one() {
+        return Err(TimedError::MalformedCiphertext);
+    }
 
     // Recover s
-    let s = paillier_L(&x, n) % n;
+    Ok(paillier_l(&x, n) % n)
+}
 
-    let mut out = s.to_bytes_be();
-    if out.len() > 32 { return None; }
-    if out.len() < 32 {
-        let mut pad = vec![0u8; 32 - out.len()];
-        pad.extend_from_slice(&out);
-        out = pad;
+// =============================
+// Arbitrary-length payloads
+// =============================
+// Direct: s = 0x01 || len (u32 BE) || data as one integer below N; the
+// marker byte keeps leading zeros of len and data through the integer.
+// Hybrid: for data beyond that capacity, a fresh AES-256-GCM key is
+// time-locked directly and the body is encrypted under it (same AAD).
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum TimedPayload {
+    Direct(TimedCiphertext),
+    Hybrid {
+        key: TimedCiphertext,
+        nonce: [u8; 12],
+        body: Vec<u8>,
+    },
+}
+
+/// Largest payload `timed_encrypt_bytes` time-locks directly under `pp`.
+pub fn direct_capacity(pp: &TimedParams) -> usize {
+    // marker + length + data must stay below N: (bits - 1) / 8 bytes do.
+    ((pp.n.bits().saturating_sub(1) / 8) as usize).saturating_sub(5)
+}
+
+/// Time-lock `data` of any length: directly if it fits `direct_capacity`,
+/// otherwise in hybrid mode.
+pub fn timed_encrypt_bytes(pp: &TimedParams, data: &[u8], aad: &[u8]) -> Result<TimedPayload, TimedError> {
+    if data.len() <= direct_capacity(pp) {
+        return Ok(TimedPayload::Direct(encrypt_len_prefixed(pp, data, aad)?));
+    }
+
+    let mut key = [0u8; 32];
+    let mut nonce = [0u8; 12];
+    rand::rng().fill_bytes(&mut key);
+    rand::rng().fill_bytes(&mut nonce);
+    let body = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: data, aad })
+        .map_err(|_| TimedError::PlaintextTooLarge)?;
+    let key = encrypt_len_prefixed(pp, &key, aad)?;
+    Ok(TimedPayload::Hybrid { key, nonce, body })
+}
+
+/// Inverse of `timed_encrypt_bytes` (T sequential squarings either way).
+pub fn timed_decrypt_bytes(pp: &TimedParams, payload: &TimedPayload, aad_expected: &[u8]) -> Result<Vec<u8>, TimedError> {
+    match payload {
+        TimedPayload::Direct(ct) => decrypt_len_prefixed(pp, ct, aad_expected),
+        TimedPayload::Hybrid { key, nonce, body } => {
+            let key = decrypt_len_prefixed(pp, key, aad_expected)?;
+            if key.len() != 32 {
+                return Err(TimedError::MalformedPayload);
+            }
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+                .decrypt(Nonce::from_slice(nonce), Payload { msg: body, aad: aad_expected })
+                .map_err(|_| TimedError::MalformedPayload)
+        }
+    }
+}
+
+fn encrypt_len_prefixed(pp: &TimedParams, data: &[u8], aad: &[u8]) -> Result<TimedCiphertext, TimedError> {
+    let mut buf = Vec::with_capacity(5 + data.len());
+    buf.push(1);
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+    timed_encrypt(pp, &buf, aad)
+}
+
+fn decrypt_len_prefixed(pp: &TimedParams, ct: &TimedCiphertext, aad_expected: &[u8]) -> Result<Vec<u8>, TimedError> {
+    let buf = solve_to_int(pp, ct, aad_expected)?.to_bytes_be();
+
+    match buf.split_first() {
+        Some((1, rest)) if rest.len() >= 4 => {
+            let (len, data) = rest.split_at(4);
+            let len = u32::from_be_bytes(len.try_into().expect("4 bytes")) as usize;
+            if data.len() != len {
+                return Err(TimedError::MalformedPayload);
+            }
+            Ok(data.to_vec())
+        }
+        _ => Err(TimedError::MalformedPayload),
+    }
+}
+
+// =============================
+// Additive homomorphism
+// =============================
+// (u1 u2, v1 v2) encrypts s1 + s2 and (u^k, v^k) encrypts k*s (mod N),
+// since (g^r mod N)^N ≡ g^{rN} (mod N^2).
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HomOp {
+    /// Sum of all inputs.
+    Add,
+    /// Single input multiplied by a public constant.
+    ScalarMul(BigUint),
+}
+
+/// Homomorphic addition; both ciphertexts must carry the same AAD.
+pub fn timed_add(pp: &TimedParams, a: &TimedCiphertext, b: &TimedCiphertext) -> Option<TimedCiphertext> {
+    if !aad_matches(a, &b.aad) { return None; }
+    let n = &pp.n;
+    let n2 = n * n;
+
+    let u = (BigUint::from_bytes_be(&a.u) * BigUint::from_bytes_be(&b.u)) % n;
+    let v = (BigUint::from_bytes_be(&a.v) * BigUint::from_bytes_be(&b.v)) % &n2;
+
+    Some(TimedCiphertext {
+        u: u.to_bytes_be(),
+        v: v.to_bytes_be(),
+        aad: a.aad.clone(),
+    })
+}
+
+/// Homomorphic multiplication by a public constant k.
+pub fn timed_scalar_mul(pp: &TimedParams, ct: &TimedCiphertext, k: &BigUint) -> TimedCiphertext {
+    let n = &pp.n;
+    let n2 = n * n;
+
+    let u = BigUint::from_bytes_be(&ct.u).modpow(k, n);
+    let v = BigUint::from_bytes_be(&ct.v).modpow(k, &n2);
+
+    TimedCiphertext {
+        u: u.to_bytes_be(),
+        v: v.to_bytes_be(),
+        aad: ct.aad.clone(),
+    }
+}
+
+/// Homomorphic sum of all of `cts` (u and v components multiplied), one
+/// puzzle that decrypts to Σ s_i mod N. None if `cts` is empty or the AADs
+/// differ.
+pub fn timed_aggregate(pp: &TimedParams, cts: &[TimedCiphertext]) -> Option<TimedCiphertext> {
+    let (first, rest) = cts.split_first()?;
+    rest.iter().try_fold(first.clone(), |acc, ct| timed_add(pp, &acc, ct))
+}
+
+/// One puzzle for the combined signature scalar: each partial z_i (32-byte
+/// little-endian scalar encodings, as in `PartialSignature`) is encrypted
+/// as the integer it encodes and the ciphertexts aggregated, so a
+/// recoverer solves a single puzzle instead of one per signer. A signer
+/// encrypting only its own z_i passes `&[z_i]`; the coordinator then
+/// combines those with `timed_aggregate`. Decrypt with
+/// `timed_decrypt_combined`.
+pub fn timed_encrypt_combined(
+    pp: &TimedParams,
+    z_shares: &[[u8; 32]],
+    aad: &[u8],
+) -> Result<TimedCiphertext, TimedError> {
+    let mut cts = Vec::with_capacity(z_shares.len());
+    for z in z_shares {
+        let mut be = *z;
+        be.reverse();
+        cts.push(timed_encrypt(pp, &be, aad)?);
+    }
+    timed_aggregate(pp, &cts).ok_or(TimedError::MalformedCiphertext)
+}
+
+/// Solve an aggregated puzzle of partial signature scalars and return
+/// z = Σ z_i mod ℓ, the combined `Signature::z`. The plaintext is Σ z_i as
+/// an integer, below k·ℓ for k shares, so it is exact while k·ℓ < N (any
+/// k below 2^259 for N of `MIN_MODULUS_BITS`) and may exceed the 32 bytes
+/// `timed_decrypt` returns; it is reduced mod ℓ here instead.
+pub fn timed_decrypt_combined(pp: &TimedParams, ct: &TimedCiphertext, aad_expected: &[u8]) -> Result<Scalar, TimedError> {
+    let sum = solve_to_int(pp, ct, aad_expected)?;
+    let l = BigUint::from_bytes_le(&(-Scalar::ONE).to_bytes()) + 1u32;
+    let mut bytes = (sum % l).to_bytes_le();
+    bytes.resize(32, 0);
+    Ok(Scalar::from_bytes_mod_order(bytes.try_into().expect("32 bytes")))
+}
+
+/// Check that `claimed` is exactly `op` applied to `inputs`, without
+/// decrypting: the combination is recomputed and compared component-wise.
+pub fn timed_verify_homomorphic(
+    pp: &TimedParams,
+    inputs: &[TimedCiphertext],
+    op: &HomOp,
+    claimed: &TimedCiphertext,
+) -> bool {
+    let expected = match op {
+        HomOp::Add => match timed_aggregate(pp, inputs) {
+            Some(c) => c,
+            None => return false,
+        },
+        HomOp::ScalarMul(k) => {
+            if inputs.len() != 1 { return false; }
+            timed_scalar_mul(pp, &inputs[0], k)
+        }
+    };
+
+    let n = &pp.n;
+    let n2 = n * n;
+    let same = |x: &[u8], y: &[u8], m: &BigUint| {
+        BigUint::from_bytes_be(x) % m == BigUint::from_bytes_be(y) % m
+    };
+    aad_matches(&expected, &claimed.aad)
+        && same(&expected.u, &claimed.u, n)
+        && same(&expected.v, &claimed.v, &n2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::timed_params;
+
+    fn int_ct(pp: &TimedParams, x: u32, aad: &[u8]) -> TimedCiphertext {
+        timed_encrypt(pp, &x.to_be_bytes(), aad).unwrap()
+    }
+
+    fn as_u32(out: &[u8]) -> u32 {
+        u32::from_be_bytes(out[28..].try_into().unwrap())
+    }
+
+    #[test]
+    fn from_primes_needs_two_distinct_primes() {
+        let td = timed_params(8);
+        let (p, q, g) = (td.p.clone(), td.q.clone(), td.params.g.clone());
+        assert_eq!(
+            TimedParamsWithTrapdoor::from_primes(p.clone(), p.clone(), g.clone(), 8).err(),
+            Some(TimedError::RepeatedFactor)
+        );
+        // p + 2 happens to be composite; 4q is even.
+        assert_eq!(
+            TimedParamsWithTrapdoor::from_primes(&p + 2u32, q.clone(), g.clone(), 8).err(),
+            Some(TimedError::FactorNotPrime)
+        );
+        assert_eq!(
+            TimedParamsWithTrapdoor::from_primes(p, &q << 2u32, g, 8).err(),
+            Some(TimedError::FactorNotPrime)
+        );
+    }
+
+    #[test]
+    fn params_round_trip_through_serde() {
+        let td = timed_params(8);
+        let pp = td.public_view();
+        assert_eq!((&pp.n, &pp.g, &pp.h, pp.t), (&td.params.n, &td.params.g, &td.params.h, 8));
+        assert!(!format!("{:?}", pp).contains(&td.p.to_string()));
+
+        let json = serde_json::to_value(&pp).unwrap();
+        assert_eq!(json["n"], serde_json::json!(pp.n.to_bytes_be()));
+        let back: TimedParams = serde_json::from_value(json).unwrap();
+        assert_eq!((&back.n, &back.g, &back.h, back.t), (&pp.n, &pp.g, &pp.h, pp.t));
+        assert!(back.validate());
+
+        let ct = int_ct(&back, 5, b"");
+        assert_eq!(as_u32(&timed_decrypt(&pp, &ct, b"").unwrap()), 5);
+    }
+
+    #[test]
+    fn deserializing_params_rejects_a_zero_modulus() {
+        let mut json = serde_json::to_value(timed_params(8).public_view()).unwrap();
+        for n in [serde_json::json!([]), serde_json::json!([0, 0])] {
+            json["n"] = n;
+            let err = serde_json::from_value::<TimedParams>(json.clone()).unwrap_err();
+            assert_eq!(err.to_string(), TimedError::ZeroModulus.to_string());
+        }
+    }
+
+    #[test]
+    fn pow_2t_mod_matches_the_naive_loop() {
+        let n = timed_params(0).params.n;
+        let x = BigUint::from(123_456_789u32);
+        let mut naive = x.clone();
+        let mut done = 0;
+        // Crosses the 2^16 chunk boundary.
+        for t in [0, 1, 2, 17, 1000, 65_536, 65_537, 70_000] {
+            while done < t {
+                naive = &naive * &naive % &n;
+                done += 1;
+            }
+            assert_eq!(pow_2t_mod(x.clone(), t, &n), naive, "t = {}", t);
+        }
+        // x >= N is reduced first.
+        assert_eq!(pow_2t_mod(&x + &n, 17, &n), pow_2t_mod(x, 17, &n));
+    }
+
+    #[test]
+    fn squaring_rate_needs_a_modulus_of_two_bits() {
+        for bits in [0, 1] {
+            assert_eq!(benchmark_squaring_rate(bits), Err(TimedError::ModulusTooSmall(bits)));
+        }
+        for bits in [2, 64, 512] {
+            assert!(benchmark_squaring_rate(bits).unwrap() > 0.0);
+        }
+    }
+
+    #[test]
+    fn trapdoor_debug_redacts_the_factors() {
+        let td = timed_params(8);
+        let shown = format!("{:?}", td);
+        assert!(shown.contains(&td.params.n.to_string()));
+        assert!(!shown.contains(&td.p.to_string()));
+        assert!(!shown.contains(&td.q.to_string()));
+    }
+
+    #[test]
+    fn trapdoor_decryption_matches_sequential_squaring() {
+        for t in [0, 1, 5, 64] {
+            let td = timed_params(t);
+            let pp = &td.params;
+            for plaintext in [[0u8; 32], [0xab; 32], *b"a 32-byte time-locked plaintext!"] {
+                let ct = timed_encrypt(pp, &plaintext, b"aad").unwrap();
+                let slow = timed_decrypt(pp, &ct, b"aad").unwrap();
+                assert_eq!(slow, plaintext);
+                assert_eq!(timed_decrypt_trapdoor(&td, &ct, b"aad").unwrap(), slow, "t = {}", t);
+            }
+        }
+    }
+
+    #[test]
+    fn wesolowski_proof_verifies_and_pins_w() {
+        for t in [0, 1, 2, 3, 257] {
+            let pp = timed_params(t).params;
+            let u = BigUint::from_bytes_be(&int_ct(&pp, 9, b"").u);
+            let (w, proof) = prove_squaring(&pp, &u);
+            assert_eq!(w, pow_2t_mod(u.clone(), t, &pp.n));
+            assert!(verify_squaring(&pp, &u, &w, &proof));
+
+            // Not w + 1, and not -w even with π negated as well.
+            assert!(!verify_squaring(&pp, &u, &(&w + 1u32), &proof));
+            let neg_pi = WesolowskiProof { pi: (&pp.n - BigUint::from_bytes_be(&proof.pi)).to_bytes_be() };
+            for pi in [&proof, &neg_pi] {
+                assert!(!verify_squaring(&pp, &u, &(&pp.n - &w), pi));
+            }
+            assert!(!verify_squaring(&pp, &(&u + 1u32), &w, &proof));
+        }
+    }
+
+    #[test]
+    fn pietrzak_proof_verifies_and_rejects_a_forged_midpoint() {
+        for t in [0, 1, 2, 3, 4, 5, 6, 7, 8, 64, 257, 1000] {
+            let pp = timed_params(t).params;
+            let n = &pp.n;
+            let u = BigUint::from_bytes_be(&int_ct(&pp, 9, b"").u);
+            let w = pow_2t_mod(u.clone(), t, n);
+            let proof = prove_squaring_pietrzak(&pp, &u, &w);
+            assert!(verify_squaring_pietrzak(&pp, &u, &w, &proof), "t = {}", t);
+            assert!(proof.mu.len() <= 64 - t.leading_zeros() as usize);
+
+            // Not w + 1, not -w, not another u.
+            assert!(!verify_squaring_pietrzak(&pp, &u, &(&w + 1u32), &proof));
+            assert!(!verify_squaring_pietrzak(&pp, &u, &(n - &w), &proof));
+            assert!(!verify_squaring_pietrzak(&pp, &(&u + 1u32), &w, &proof));
+
+            for k in 0..proof.mu.len() {
+                let m = BigUint::from_bytes_be(&proof.mu[k]);
+                // A different midpoint, and the same one negated.
+                for forged in [&m * &u % n, n - &m] {
+                    let mut bad = proof.clone();
+                    bad.mu[k] = forged.to_bytes_be();
+                    assert!(!verify_squaring_pietrzak(&pp, &u, &w, &bad), "t = {}, level {}", t, k);
+                }
+            }
+            let mut longer = proof.clone();
+            longer.mu.push(vec![1]);
+            assert!(!verify_squaring_pietrzak(&pp, &u, &w, &longer));
+            if let Some((_, shorter)) = proof.mu.split_last() {
+                let shorter = PietrzakProof { mu: shorter.to_vec() };
+                assert!(!verify_squaring_pietrzak(&pp, &u, &w, &shorter));
+            }
+        }
+    }
+
+    #[test]
+    fn wesolowski_long_division_matches_the_quotient() {
+        let pp = timed_params(300).params;
+        let u = BigUint::from_bytes_be(&int_ct(&pp, 9, b"").u) % &pp.n;
+        let (w, proof) = prove_squaring(&pp, &u);
+        let l = hash_to_prime(&pp.n, pp.t, &u, &w);
+        let q = (BigUint::one() << (pp.t - 1)) / &l;
+        assert_eq!(BigUint::from_bytes_be(&proof.pi), u.modpow(&q, &pp.n));
+    }
+
+    #[test]
+    fn derive_h_validates_the_modulus_and_base() {
+        let td = timed_params(64);
+        let (n, g) = (&td.params.n, &td.params.g);
+        assert_eq!(derive_h(n, g, 64), Ok(td.params.h.clone()));
+        assert!(td.params.validate());
+
+        // gcd(g, N) = p.
+        assert_eq!(derive_h(n, &td.p, 64), Err(TimedError::BaseNotUnit));
+        assert_eq!(derive_h(n, &(n + &td.q), 64), Err(TimedError::BaseNotUnit));
+        assert_eq!(derive_h(n, &BigUint::zero(), 64), Err(TimedError::BaseNotUnit));
+        assert_eq!(derive_h(&(n << 1u32), g, 64), Err(TimedError::EvenModulus));
+        assert_eq!(derive_h(&BigUint::one(), g, 64), Err(TimedError::ModulusTooSmall(1)));
+        assert_eq!(derive_h(&td.p, g, 64), Err(TimedError::ModulusTooSmall(256)));
+    }
+
+    #[test]
+    fn validate_rejects_a_tampered_h_g_or_t() {
+        let pp = timed_params(64).params;
+        assert!(pp.validate());
+
+        let tampered = |f: &dyn Fn(&mut TimedParams)| {
+            let mut bad = pp.clone();
+            f(&mut bad);
+            bad.validate()
+        };
+        assert!(!tampered(&|p| p.h += 1u32));
+        assert!(!tampered(&|p| p.h = &p.h * &p.h % &p.n));
+        assert!(!tampered(&|p| p.t = 63));
+        assert!(!tampered(&|p| p.g = BigUint::zero()));
+        assert!(!tampered(&|p| p.g = BigUint::one()));
+        assert!(!tampered(&|p| p.g = p.n.clone()));
+        assert!(!tampered(&|p| p.g = &p.g + &p.n));
+        // A different (valid) base with the old h.
+        assert!(!tampered(&|p| p.g = BigUint::from(9u32)));
+    }
+
+    #[test]
+    fn timed_encrypt_and_decrypt_report_each_error() {
+        let td = timed_params(8);
+        let pp = &td.params;
+        let too_large = pp.n.to_bytes_be();
+        assert_eq!(timed_encrypt(pp, &too_large, b"").err(), Some(TimedError::PlaintextTooLarge));
+
+        let ct = int_ct(pp, 42, b"a");
+        assert_eq!(as_u32(&timed_decrypt(pp, &ct, b"a").unwrap()), 42);
+        assert_eq!(timed_decrypt(pp, &ct, b"b"), Err(TimedError::AadMismatch));
+
+        // u = p shares a factor with N, so the mask is not invertible.
+        let bad_u = TimedCiphertext { u: td.p.to_bytes_be(), ..ct.clone() };
+        assert_eq!(timed_decrypt(pp, &bad_u, b"a"), Err(TimedError::NotInvertible));
+
+        // v = 0, v = N^2 and v = 2 are not masked (1 + N)^s.
+        let n2 = &pp.n * &pp.n;
+        for v in [BigUint::zero(), n2, BigUint::from(2u32)] {
+            let bad_v = TimedCiphertext { v: v.to_bytes_be(), ..ct.clone() };
+            assert_eq!(timed_decrypt(pp, &bad_v, b"a"), Err(TimedError::MalformedCiphertext));
+            assert_eq!(timed_decrypt_trapdoor(&td, &bad_v, b"a"), Err(TimedError::MalformedCiphertext));
+        }
+
+        // A 33-byte plaintext is below N but not a 32-byte output.
+        let wide = timed_encrypt(pp, &[1u8; 33], b"a").unwrap();
+        assert_eq!(timed_decrypt(pp, &wide, b"a"), Err(TimedError::OutputTooLarge));
+    }
+
+    #[test]
+    fn payloads_of_any_length_round_trip() {
+        let pp = timed_params(8).params;
+        let cap = direct_capacity(&pp);
+        assert_eq!(cap, 58); // 512-bit N
+        for (len, direct) in [(0, true), (1, true), (cap, true), (cap + 1, false), (200, false), (5 * 1024, false)] {
+            // Leading zeros must survive the trip through an integer.
+            let data: Vec<u8> = (0..len).map(|k| if k < 3 { 0 } else { (k * 7) as u8 }).collect();
+            let payload = timed_encrypt_bytes(&pp, &data, b"aad").unwrap();
+            assert_eq!(matches!(payload, TimedPayload::Direct(_)), direct, "len {}", len);
+            assert_eq!(timed_decrypt_bytes(&pp, &payload, b"aad").unwrap(), data);
+            assert_eq!(timed_decrypt_bytes(&pp, &payload, b"other"), Err(TimedError::AadMismatch));
+        }
+
+        let TimedPayload::Hybrid { key, nonce, mut body } = timed_encrypt_bytes(&pp, &[7; 200], b"").unwrap() else {
+            panic!("200 bytes exceed the direct capacity");
+        };
+        body[0] ^= 1;
+        let tampered = TimedPayload::Hybrid { key, nonce, body };
+        assert_eq!(timed_decrypt_bytes(&pp, &tampered, b""), Err(TimedError::MalformedPayload));
+    }
+
+    #[test]
+    fn homomorphic_aggregation_is_checked() {
+        let pp = timed_params(16).params;
+        let inputs = [int_ct(&pp, 5, b"a"), int_ct(&pp, 7, b"a"), int_ct(&pp, 11, b"a")];
+        let sum = timed_add(&pp, &timed_add(&pp, &inputs[0], &inputs[1]).unwrap(), &inputs[2]).unwrap();
+        assert!(timed_verify_homomorphic(&pp, &inputs, &HomOp::Add, &sum));
+        assert_eq!(as_u32(&timed_decrypt(&pp, &sum, b"a").unwrap()), 23);
+
+        // Dropping an input, or swapping in a fresh encryption of the same
+        // sum, is caught without decrypting.
+        assert!(!timed_verify_homomorphic(&pp, &inputs[..2], &HomOp::Add, &sum));
+        assert!(!timed_verify_homomorphic(&pp, &inputs, &HomOp::Add, &int_ct(&pp, 23, b"a")));
+        let mut tampered = sum.clone();
+        tampered.v = (BigUint::from_bytes_be(&sum.v) + 1u32).to_bytes_be();
+        assert!(!timed_verify_homomorphic(&pp, &inputs, &HomOp::Add, &tampered));
+        let mut relabelled = sum.clone();
+        relabelled.aad = b"b".to_vec();
+        assert!(!timed_verify_homomorphic(&pp, &inputs, &HomOp::Add, &relabelled));
+        assert!(!timed_verify_homomorphic(&pp, &[], &HomOp::Add, &sum));
+    }
+
+    #[test]
+    fn aggregated_puzzle_decrypts_to_the_sum() {
+        let pp = timed_params(16).params;
+        let xs = [5u32, 7, 11, 1 << 30];
+        let cts: Vec<_> = xs.iter().map(|&x| int_ct(&pp, x, b"a")).collect();
+        let agg = timed_aggregate(&pp, &cts).unwrap();
+        let sum: u64 = xs.iter().map(|&x| x as u64).sum();
+        let out = timed_decrypt(&pp, &agg, b"a").unwrap();
+        assert_eq!(u64::from_be_bytes(out[24..].try_into().unwrap()), sum);
+        assert!(timed_verify_homomorphic(&pp, &cts, &HomOp::Add, &agg));
+
+        assert!(timed_aggregate(&pp, &[]).is_none());
+        assert!(timed_aggregate(&pp, &[cts[0].clone(), int_ct(&pp, 1, b"b")]).is_none());
+        assert_eq!(timed_aggregate(&pp, &cts[..1]).unwrap().v, cts[0].v);
+    }
+
+    #[test]
+    fn combined_puzzle_reveals_the_signature_scalar() {
+        use crate::testutil::Session;
+
+        let pp = timed_params(16).params;
+        let s = Session::new(7, 4);
+        let sig = s.signature();
+        let z_shares: Vec<[u8; 32]> = s.partials.iter().map(|ps| ps.z_i).collect();
+
+        let ct = timed_encrypt_combined(&pp, &z_shares, b"timed-z").unwrap();
+        assert_eq!(timed_decrypt_combined(&pp, &ct, b"timed-z"), Ok(sig.z));
+        assert_eq!(timed_decrypt_combined(&pp, &ct, b"other"), Err(TimedError::AadMismatch));
+
+        // Per-signer ciphertexts aggregated later give the same z.
+        let cts: Vec<_> =
+            z_shares.iter().map(|z| timed_encrypt_combined(&pp, &[*z], b"timed-z").unwrap()).collect();
+        let agg = timed_aggregate(&pp, &cts).unwrap();
+        assert_eq!(timed_decrypt_combined(&pp, &agg, b"timed-z"), Ok(sig.z));
+
+        // 17 shares of ℓ - 1 sum past 2^256, beyond `timed_decrypt`'s 32
+        // bytes; the combined decryption still reduces it mod ℓ.
+        let ct = timed_encrypt_combined(&pp, &[(-Scalar::ONE).to_bytes(); 17], b"").unwrap();
+        assert_eq!(timed_decrypt(&pp, &ct, b""), Err(TimedError::OutputTooLarge));
+        assert_eq!(timed_decrypt_combined(&pp, &ct, b""), Ok(-Scalar::from(17u32)));
+    }
+
+    #[test]
+    fn homomorphic_scalar_mul_is_checked() {
+        let pp = timed_params(16).params;
+        let ct = int_ct(&pp, 6, b"a");
+        let k = BigUint::from(7u32);
+        let prod = timed_scalar_mul(&pp, &ct, &k);
+        assert!(timed_verify_homomorphic(&pp, core::slice::from_ref(&ct), &HomOp::ScalarMul(k.clone()), &prod));
+        assert_eq!(as_u32(&timed_decrypt(&pp, &prod, b"a").unwrap()), 42);
+
+        let other = HomOp::ScalarMul(BigUint::from(8u32));
+        assert!(!timed_verify_homomorphic(&pp, core::slice::from_ref(&ct), &other, &prod));
+        assert!(!timed_verify_homomorphic(&pp, &[ct.clone(), ct], &HomOp::ScalarMul(k), &prod));
     }
-    Some(out)
 }
@@ -2,93 +2,732 @@
 //
 // Message-dependent authorized tracing (paper-faithful structure)
 
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
-use sha2::{Digest, Sha256};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-use rand::rand_core::{OsRng, RngCore};
+use crate::hash::{enc_point, hash_to_point, hash_to_scalar};
+use crate::randutil::random_scalar;
+use crate::shamir::{sample_poly_with_constant, LagrangeTable};
 
+type HmacSha256 = Hmac<Sha256>;
 
-#[derive(Clone, Debug)]
+/// sk is wiped on drop and left out of Debug output.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct AdmitterKey {
     pub sk: Scalar,        // tracing master secret
+    #[zeroize(skip)]
     pub pk: RistrettoPoint,
 }
 
+impl fmt::Debug for AdmitterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdmitterKey")
+            .field("sk", &"<redacted>")
+            .field("pk", &self.pk)
+            .finish()
+    }
+}
+
+impl AdmitterKey {
+    /// Derive an independent tracing key for one application:
+    /// sk' = H(sk || app_id), pk' = base * sk'.
+    /// Tokens issued under one application key do not open ciphertexts
+    /// produced under another, so a single master key can serve several
+    /// isolated tracing domains.
+    pub fn derive_app_key(&self, app_id: &[u8]) -> AdmitterKey {
+        let mut h = Sha512::new();
+        h.update(b"Tracing::AppKey");
+        h.update(self.sk.as_bytes());
+        h.update(app_id);
+        let mut wide = [0u8; 64];
+        wide.copy_from_slice(&h.finalize());
+
+        let sk = Scalar::from_bytes_mod_order_wide(&wide);
+        let pk = RISTRETTO_BASEPOINT_POINT * sk;
+        AdmitterKey { sk, pk }
+    }
+}
+
+/// Authorizes tracing for one message until `expiry` (unix seconds,
+/// inclusive). tau = M * sk for M = H2C(expiry, m), a point whose discrete
+/// log nobody knows, so neither sk nor a token for another message or
+/// expiry can be computed from tau and the public data.
 #[derive(Clone, Debug)]
 pub struct TraceToken {
     pub msg_hash: [u8; 32],
-    pub tau: Scalar,      // authorization scalar
+    pub tau: RistrettoPoint, // authorization point
+    pub expiry: u64,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct TraceCiphertext {
+    pub c1: [u8; 32],     // compressed ephemeral point
+    pub nonce: [u8; 12],
+    pub c2: Vec<u8>,      // AEAD ciphertext || tag
+    pub msg_hash: [u8; 32],
+    pub expiry: u64,
+}
+
+/// Variable-length traced ciphertext: c2 is exactly the requested output
+/// length and `tag` authenticates (len(c2) || c2).
+#[derive(Clone, Debug)]
+pub struct TraceCiphertextLen {
     pub c1: RistrettoPoint,
-    pub c2: [u8; 32],
+    pub c2: Vec<u8>,
+    pub tag: [u8; 32],
     pub msg_hash: [u8; 32],
+    pub expiry: u64,
 }
 
 // Setup tracing authority
 pub fn setup_admitter() -> AdmitterKey {
     let buf: [u8; 64] = rand::random();
     let sk = Scalar::from_bytes_mod_order_wide(&buf);
-    let pk = RistrettoPoint::default() * sk;
+    let pk = RISTRETTO_BASEPOINT_POINT * sk;
     AdmitterKey { sk, pk }
 }
 
-// Token issued for a specific message
-pub fn admitter_issue_token(ad: &AdmitterKey, message: &[u8]) -> TraceToken {
-    let h = Sha256::digest(message);
-    let mut mh = [0u8; 32];
-    mh.copy_from_slice(&h);
+// Token issued for a specific message and expiry: tau = H2C(expiry, m) * sk.
+// msg_hash = SHA-256(m) only identifies the message a ciphertext belongs to.
+pub fn admitter_issue_token(ad: &AdmitterKey, message: &[u8], expiry: u64) -> TraceToken {
+    TraceToken {
+        msg_hash: token_msg_hash(message),
+        tau: token_point(message, expiry) * ad.sk,
+        expiry,
+    }
+}
+
+/// Current unix time in seconds; a clock before 1970 counts as expired.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(u64::MAX)
+}
+
+/// Chaum–Pedersen proof that log_G(pk) == log_M(tau) for
+/// M = H2C(expiry, m), i.e. that tau = M * sk for the sk behind pk.
+/// Nobody without sk can compute tau, so an accepted proof shows the
+/// admitter really issued this token.
+/// Compact form: the challenge e and response s.
+#[derive(Clone, Debug)]
+pub struct TokenProof {
+    pub e: Scalar,
+    pub s: Scalar,
+}
+
+/// `admitter_issue_token` plus a proof that the token matches `ad.pk`.
+pub fn admitter_issue_token_with_proof(
+    ad: &AdmitterKey,
+    message: &[u8],
+    expiry: u64,
+) -> (TraceToken, TokenProof) {
+    let token = admitter_issue_token(ad, message, expiry);
+    let m_point = token_point(message, expiry);
+
+    let k = random_scalar();
+    let t1 = RISTRETTO_BASEPOINT_POINT * k;
+    let t2 = m_point * k;
+    let e = token_challenge(&ad.pk, &m_point, &token.tau, &t1, &t2);
+    let proof = TokenProof { e, s: k + e * ad.sk };
+    (token, proof)
+}
+
+/// Check that `token` was issued for `message` under the sk of `pk`:
+/// recompute T1 = s*G - e*pk, T2 = s*M - e*tau for M = H2C(expiry, m)
+/// and compare the challenge.
+pub fn verify_token(pk: &RistrettoPoint, message: &[u8], token: &TraceToken, proof: &TokenProof) -> bool {
+    if token.msg_hash != token_msg_hash(message) {
+        return false;
+    }
+    let m_point = token_point(message, token.expiry);
+    let t1 = RISTRETTO_BASEPOINT_POINT * proof.s - pk * proof.e;
+    let t2 = m_point * proof.s - token.tau * proof.e;
+    token_challenge(pk, &m_point, &token.tau, &t1, &t2) == proof.e
+}
+
+fn token_challenge(
+    pk: &RistrettoPoint,
+    m_point: &RistrettoPoint,
+    tau: &RistrettoPoint,
+    t1: &RistrettoPoint,
+    t2: &RistrettoPoint,
+) -> Scalar {
+    let mut buf = Vec::with_capacity(5 * 32);
+    for p in [pk, m_point, tau, t1, t2] {
+        buf.extend_from_slice(&enc_point(p));
+    }
+    hash_to_scalar(b"Tracing::TokenProof", &buf)
+}
+
+fn token_msg_hash(message: &[u8]) -> [u8; 32] {
+    let mut msg_hash = [0u8; 32];
+    msg_hash.copy_from_slice(&Sha256::digest(message));
+    msg_hash
+}
+
+// M = H2C(expiry, m) of the token derivation. Its discrete log is unknown,
+// which is what keeps tau = M * sk from giving away sk.
+fn token_point(message: &[u8], expiry: u64) -> RistrettoPoint {
+    let mut buf = expiry.to_le_bytes().to_vec();
+    buf.extend_from_slice(message);
+    hash_to_point(b"Tracing::Token::v2", &buf)
+}
+
+// ===== Threshold tracing =====
+// The tracing secret sk is Shamir-shared among m admitters with threshold
+// t_trace (sk_j = f(j), f(0) = sk). tau = M * sk is linear in sk, so
+// admitter j issues tau_j = M * sk_j and any t_trace+1 of them give
+// tau = Σ L_j tau_j in the exponent; t_trace or fewer reveal nothing about it.
+
+/// Public side of a shared tracing authority. `pk` is what `AdmitterKey.pk`
+/// is for a single admitter; pk_j = base * sk_j.
+#[derive(Clone, Debug)]
+pub struct AdmitterGroup {
+    pub m: usize,
+    pub t_trace: usize,
+    pub pk: RistrettoPoint,
+    pub pk_shares: Vec<(u32, RistrettoPoint)>,
+}
+
+/// Admitter j's share of the tracing secret; sk_j is wiped on drop and
+/// left out of Debug output.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct AdmitterShare {
+    #[zeroize(skip)]
+    pub j: u32,
+    pub sk_j: Scalar,
+}
+
+impl fmt::Debug for AdmitterShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdmitterShare")
+            .field("j", &self.j)
+            .field("sk_j", &"<redacted>")
+            .finish()
+    }
+}
+
+/// tau_j = H2C(expiry, m) * sk_j from admitter j.
+#[derive(Clone, Debug)]
+pub struct PartialTraceToken {
+    pub j: u32,
+    pub msg_hash: [u8; 32],
+    pub tau_j: RistrettoPoint,
+    pub expiry: u64,
+}
+
+/// Why `setup_admitters` rejected (m, t_trace).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AdmitterSetupError {
+    /// m = 0: there is nobody to hold a share.
+    NoAdmitters,
+    /// t_trace >= m: a quorum of t_trace+1 admitters never exists.
+    ThresholdTooLarge { m: usize, t_trace: usize },
+}
+
+impl core::fmt::Display for AdmitterSetupError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            AdmitterSetupError::NoAdmitters => write!(f, "setup_admitters needs at least one admitter"),
+            AdmitterSetupError::ThresholdTooLarge { m, t_trace } => {
+                write!(f, "tracing threshold {} must be < m={}", t_trace, m)
+            }
+        }
+    }
+}
+
+impl core::error::Error for AdmitterSetupError {}
 
-    let mut mh = [0u8; 32];
-    mh.copy_from_slice(&h);
-    let tau = Scalar::from_bytes_mod_order(mh) * ad.sk;
+/// Deal a fresh tracing secret to admitters 1..=m, any t_trace+1 of whom
+/// can issue tokens. t_trace must be < m.
+pub fn setup_admitters(m: usize, t_trace: usize) -> Result<(AdmitterGroup, Vec<AdmitterShare>), AdmitterSetupError> {
+    if m == 0 {
+        return Err(AdmitterSetupError::NoAdmitters);
+    }
+    if t_trace >= m {
+        return Err(AdmitterSetupError::ThresholdTooLarge { m, t_trace });
+    }
+    let f = sample_poly_with_constant(t_trace, random_scalar());
+    let shares: Vec<AdmitterShare> = (1..=m as u32)
+        .map(|j| AdmitterShare { j, sk_j: f.eval(Scalar::from(j as u64)) })
+        .collect();
+    let group = AdmitterGroup {
+        m,
+        t_trace,
+        pk: RISTRETTO_BASEPOINT_POINT * f.coeffs[0],
+        pk_shares: shares.iter().map(|s| (s.j, RISTRETTO_BASEPOINT_POINT * s.sk_j)).collect(),
+    };
+    Ok((group, shares))
+}
 
-    TraceToken { msg_hash: mh, tau }
+pub fn admitter_issue_partial_token(share: &AdmitterShare, message: &[u8], expiry: u64) -> PartialTraceToken {
+    PartialTraceToken {
+        j: share.j,
+        msg_hash: token_msg_hash(message),
+        tau_j: token_point(message, expiry) * share.sk_j,
+        expiry,
+    }
 }
 
-// Encrypt a share under tracing
+/// tau = Σ L_j tau_j over the partial tokens. None with fewer than
+/// t_trace+1 partials, repeated or zero admitter ids, or partials for
+/// different messages or expiries.
+pub fn combine_trace_tokens(group: &AdmitterGroup, partials: &[PartialTraceToken]) -> Option<TraceToken> {
+    let first = partials.first()?;
+    if partials.len() < group.t_trace + 1
+        || partials.iter().any(|p| p.msg_hash != first.msg_hash || p.expiry != first.expiry)
+    {
+        return None;
+    }
+    let ids: Vec<u32> = partials.iter().map(|p| p.j).collect();
+    let table = LagrangeTable::new(&ids)?;
+    let tau = partials.iter().map(|p| table.get(p.j).map(|l| p.tau_j * l)).sum::<Option<RistrettoPoint>>()?;
+    Some(TraceToken { msg_hash: first.msg_hash, tau, expiry: first.expiry })
+}
+
+// AEAD key := HKDF-SHA256(c1 || tau)
+fn trace_cipher(c1: &[u8; 32], tau: &RistrettoPoint) -> Aes256Gcm {
+    let mut ikm = Vec::new();
+    ikm.extend_from_slice(c1);
+    ikm.extend_from_slice(&enc_point(tau));
+    let hk = Hkdf::<Sha256>::new(None, &ikm);
+    let mut key = [0u8; 32];
+    hk.expand(b"Tracing::AEAD", &mut key).expect("valid HKDF length");
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+}
+
+fn trace_aad(msg_hash: &[u8; 32], expiry: u64, label: &[u8]) -> Vec<u8> {
+    let mut aad = msg_hash.to_vec();
+    aad.extend_from_slice(&expiry.to_le_bytes());
+    aad.extend_from_slice(label);
+    aad
+}
+
+// Encrypt a share under tracing (AES-256-GCM,
+// AAD = msg_hash || expiry (u64 LE) || label)
 pub fn trace_encrypt(token: &TraceToken, share: &[u8], label: &[u8]) -> TraceCiphertext {
     let r_bytes: [u8; 64] = rand::random();
 
     let r = Scalar::from_bytes_mod_order_wide(&r_bytes);
 
-    let c1 = RistrettoPoint::default() * r;
+    let c1 = (RISTRETTO_BASEPOINT_POINT * r).compress().to_bytes();
 
-    let mut h = Sha256::new();
-    h.update(c1.compress().as_bytes());
-    h.update(token.tau.as_bytes());
-    h.update(label);
-    let key = h.finalize();
-
-    let mut c2 = [0u8; 32];
-    for i in 0..32 {
-        c2[i] = key[i] ^ share[i];
-    }
+    let nonce: [u8; 12] = rand::random();
+    let c2 = trace_cipher(&c1, &token.tau)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload { msg: share, aad: &trace_aad(&token.msg_hash, token.expiry, label) },
+        )
+        .expect("AES-GCM encryption cannot fail for in-memory shares");
 
     TraceCiphertext {
         c1,
+        nonce,
         c2,
         msg_hash: token.msg_hash,
+        expiry: token.expiry,
     }
 }
 
-// Decrypt traced share
-pub fn trace_decrypt(token: &TraceToken, tc: &TraceCiphertext) -> Option<Vec<u8>> {
-    if tc.msg_hash != token.msg_hash {
+// Decrypt traced share; None on a token mismatch, any tampering, or once
+// the token has expired.
+pub fn trace_decrypt(token: &TraceToken, tc: &TraceCiphertext, label: &[u8]) -> Option<Vec<u8>> {
+    trace_decrypt_at(token, tc, label, unix_now())
+}
+
+/// `trace_decrypt` with the current time (unix seconds) supplied.
+pub fn trace_decrypt_at(token: &TraceToken, tc: &TraceCiphertext, label: &[u8], now: u64) -> Option<Vec<u8>> {
+    if tc.msg_hash != token.msg_hash || tc.expiry != token.expiry || now > token.expiry {
         return None;
     }
 
-    let mut h = Sha256::new();
-    h.update(tc.c1.compress().as_bytes());
-    h.update(token.tau.as_bytes());
-    let key = h.finalize();
+    trace_cipher(&tc.c1, &token.tau)
+        .decrypt(
+            Nonce::from_slice(&tc.nonce),
+            Payload { msg: &tc.c2, aad: &trace_aad(&tc.msg_hash, tc.expiry, label) },
+        )
+        .ok()
+}
+
+// (keystream key, MAC key) := HKDF(c1 || tau || label)
+fn trace_len_keys(c1: &RistrettoPoint, tau: &RistrettoPoint, label: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut ikm = Vec::new();
+    ikm.extend_from_slice(c1.compress().as_bytes());
+    ikm.extend_from_slice(&enc_point(tau));
+    ikm.extend_from_slice(label);
+    let hk = Hkdf::<Sha256>::new(None, &ikm);
+
+    let mut ks_key = [0u8; 32];
+    let mut mac_key = [0u8; 32];
+    hk.expand(b"Tracing::KS", &mut ks_key).expect("valid HKDF length");
+    hk.expand(b"Tracing::MAC", &mut mac_key).expect("valid HKDF length");
+    (ks_key, mac_key)
+}
 
-    let mut out = vec![0u8; 32];
-    for i in 0..32 {
-        out[i] = key[i] ^ tc.c2[i];
+// Counter-mode keystream: block_k = HMAC(ks_key, k), truncated to out_len.
+fn trace_keystream(ks_key: &[u8; 32], out_len: usize) -> Vec<u8> {
+    let mut ks = Vec::with_capacity(out_len);
+    let mut ctr = 0u64;
+    while ks.len() < out_len {
+        let mut m = <HmacSha256 as Mac>::new_from_slice(ks_key).expect("HMAC takes any key length");
+        m.update(&ctr.to_le_bytes());
+        let block = m.finalize().into_bytes();
+        let take = (out_len - ks.len()).min(block.len());
+        ks.extend_from_slice(&block[..take]);
+        ctr += 1;
     }
+    ks
+}
+
+fn trace_len_mac(mac_key: &[u8; 32], c2: &[u8]) -> HmacSha256 {
+    let mut m = <HmacSha256 as Mac>::new_from_slice(mac_key).expect("HMAC takes any key length");
+    m.update(&(c2.len() as u64).to_le_bytes());
+    m.update(c2);
+    m
+}
+
+// Length of the share, as u64 LE, at the start of the padded payload.
+const TRACE_LEN_PREFIX: usize = 8;
+
+// Encrypt a share into exactly out_len bytes: payload = len(share) (u64 LE)
+// || share || zero padding, so decryption returns the share without the
+// padding. The ciphertext length is bound into the MAC so truncated or
+// extended ciphertexts are rejected. Returns None if the share and its
+// length prefix do not fit in out_len bytes.
+pub fn trace_encrypt_len(
+    token: &TraceToken,
+    share: &[u8],
+    label: &[u8],
+    out_len: usize,
+) -> Option<TraceCiphertextLen> {
+    if share.len() > out_len.checked_sub(TRACE_LEN_PREFIX)? {
+        return None;
+    }
+    let mut payload = Vec::with_capacity(out_len);
+    payload.extend_from_slice(&(share.len() as u64).to_le_bytes());
+    payload.extend_from_slice(share);
+
+    let r_bytes: [u8; 64] = rand::random();
+    let r = Scalar::from_bytes_mod_order_wide(&r_bytes);
+    let c1 = RISTRETTO_BASEPOINT_POINT * r;
+
+    let (ks_key, mac_key) = trace_len_keys(&c1, &token.tau, label);
+    let mut c2 = trace_keystream(&ks_key, out_len);
+    for (c, p) in c2.iter_mut().zip(&payload) {
+        *c ^= p;
+    }
+
+    let mut tag = [0u8; 32];
+    tag.copy_from_slice(&trace_len_mac(&mac_key, &c2).finalize().into_bytes());
+
+    Some(TraceCiphertextLen {
+        c1,
+        c2,
+        tag,
+        msg_hash: token.msg_hash,
+        expiry: token.expiry,
+    })
+}
+
+// Decrypt a length-bound traced share, stripping the length prefix and
+// padding; None on token or MAC mismatch, a malformed payload, or once the
+// token has expired.
+pub fn trace_decrypt_len(token: &TraceToken, tc: &TraceCiphertextLen, label: &[u8]) -> Option<Vec<u8>> {
+    trace_decrypt_len_at(token, tc, label, unix_now())
+}
+
+/// `trace_decrypt_len` with the current time (unix seconds) supplied.
+pub fn trace_decrypt_len_at(
+    token: &TraceToken,
+    tc: &TraceCiphertextLen,
+    label: &[u8],
+    now: u64,
+) -> Option<Vec<u8>> {
+    if tc.msg_hash != token.msg_hash || tc.expiry != token.expiry || now > token.expiry {
+        return None;
+    }
+
+    let (ks_key, mac_key) = trace_len_keys(&tc.c1, &token.tau, label);
+    trace_len_mac(&mac_key, &tc.c2).verify_slice(&tc.tag).ok()?;
+
+    let mut out = trace_keystream(&ks_key, tc.c2.len());
+    for (o, c) in out.iter_mut().zip(&tc.c2) {
+        *o ^= c;
+    }
+    let prefix: [u8; TRACE_LEN_PREFIX] = out.get(..TRACE_LEN_PREFIX)?.try_into().ok()?;
+    let len = usize::try_from(u64::from_le_bytes(prefix)).ok()?;
+    if len > out.len() - TRACE_LEN_PREFIX {
+        return None;
+    }
+    out.truncate(TRACE_LEN_PREFIX + len);
+    out.drain(..TRACE_LEN_PREFIX);
     Some(out)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MSG: &[u8] = b"traced message";
+
+    #[test]
+    fn admitter_key_is_wiped_on_drop() {
+        let ad = setup_admitter();
+        assert_ne!(ad.sk, Scalar::ZERO);
+        assert!(!format!("{:?}", ad).contains(&format!("{:?}", ad.sk)));
+        // SAFETY: Scalar is a 32-byte array; the pointer comes from after_drop.
+        let left = crate::testutil::after_drop(ad, |p| unsafe {
+            core::ptr::read(core::ptr::addr_of!((*p).sk) as *const [u8; 32])
+        });
+        assert_eq!(left, [0u8; 32]);
+    }
+
+    #[test]
+    fn admitter_share_debug_redacts_the_secret() {
+        let (_, shares) = setup_admitters(3, 1).unwrap();
+        let shown = format!("{:?}", shares[0]);
+        assert!(shown.contains("j: 1"));
+        assert!(!shown.contains(&format!("{:?}", shares[0].sk_j)));
+    }
+
+    #[test]
+    fn app_keys_are_deterministic_and_independent() {
+        let master = setup_admitter();
+        let a = master.derive_app_key(b"app-a");
+        let b = master.derive_app_key(b"app-b");
+
+        assert_eq!(a.sk, master.derive_app_key(b"app-a").sk);
+        assert_ne!(a.sk, b.sk);
+        assert_ne!(a.sk, master.sk);
+        assert_eq!(a.pk, RISTRETTO_BASEPOINT_POINT * a.sk);
+    }
+
+    #[test]
+    fn app_token_does_not_open_other_app() {
+        let master = setup_admitter();
+        let a = master.derive_app_key(b"app-a");
+        let b = master.derive_app_key(b"app-b");
+        let tok_a = admitter_issue_token(&a, MSG, u64::MAX);
+        let tok_b = admitter_issue_token(&b, MSG, u64::MAX);
+
+        let tc = trace_encrypt(&tok_a, b"share", b"label");
+        assert_eq!(trace_decrypt(&tok_a, &tc, b"label").as_deref(), Some(&b"share"[..]));
+        assert!(trace_decrypt(&tok_b, &tc, b"label").is_none());
+    }
+
+    #[test]
+    fn length_bound_ciphertext_has_the_requested_size_and_keeps_the_share_length() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, u64::MAX);
+        for share in [&b""[..], b"z", &[0u8; 32], b"trailing zeros\0\0"] {
+            let tc = trace_encrypt_len(&tok, share, b"label", 64).unwrap();
+            assert_eq!(tc.c2.len(), 64);
+            assert_eq!(trace_decrypt_len(&tok, &tc, b"label").as_deref(), Some(share));
+        }
+        // The length prefix takes 8 bytes of out_len.
+        assert!(trace_encrypt_len(&tok, &[1u8; 57], b"label", 64).is_none());
+        assert!(trace_encrypt_len(&tok, &[1u8; 56], b"label", 64).is_some());
+        assert!(trace_encrypt_len(&tok, b"", b"label", 7).is_none());
+    }
+
+    #[test]
+    fn length_bound_ciphertext_rejects_tampering_and_expiry() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, 1000);
+        let tc = trace_encrypt_len(&tok, b"share", b"label", 40).unwrap();
+        assert!(trace_decrypt_len_at(&tok, &tc, b"label", 1000).is_some());
+        assert!(trace_decrypt_len_at(&tok, &tc, b"label", 1001).is_none());
+        assert!(trace_decrypt_len_at(&tok, &tc, b"other", 1000).is_none());
+
+        let mut cut = tc.clone();
+        cut.c2.pop();
+        assert!(trace_decrypt_len_at(&tok, &cut, b"label", 1000).is_none());
+        let mut flipped = tc.clone();
+        flipped.c2[0] ^= 1;
+        assert!(trace_decrypt_len_at(&tok, &flipped, b"label", 1000).is_none());
+    }
+
+    #[test]
+    fn keys_and_ephemerals_use_the_basepoint() {
+        use curve25519_dalek::traits::IsIdentity;
+
+        let ad = setup_admitter();
+        assert_eq!(ad.pk, RISTRETTO_BASEPOINT_POINT * ad.sk);
+        assert!(!ad.pk.is_identity());
+
+        let tok = admitter_issue_token(&ad, MSG, u64::MAX);
+        let tc = trace_encrypt(&tok, b"share", b"label");
+        assert!(!crate::hash::decode_point(&tc.c1).unwrap().is_identity());
+        assert!(!trace_encrypt_len(&tok, b"share", b"label", 32).unwrap().c1.is_identity());
+    }
+
+    #[test]
+    fn trace_decrypt_is_bound_to_the_label() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, u64::MAX);
+        let tc = trace_encrypt(&tok, b"share", b"label");
+        assert!(trace_decrypt(&tok, &tc, b"label").is_some());
+        assert!(trace_decrypt(&tok, &tc, b"labe").is_none());
+        assert!(trace_decrypt(&tok, &tc, b"").is_none());
+    }
+
+    #[test]
+    fn token_is_deterministic_for_a_message_and_key() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, 10);
+        assert_eq!(tok.msg_hash[..], Sha256::digest(MSG)[..]);
+        assert_eq!(tok.expiry, 10);
+
+        // Same key, message and expiry: the same tau.
+        assert_eq!(admitter_issue_token(&ad, MSG, 10).tau, tok.tau);
+
+        let other = admitter_issue_token(&ad, b"other", 10);
+        assert_ne!(other.msg_hash, tok.msg_hash);
+        assert_ne!(other.tau, tok.tau);
+    }
+
+    #[test]
+    fn aead_ciphertext_rejects_tampering() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, 1000);
+        let tc = trace_encrypt(&tok, b"share", b"label");
+        let json: TraceCiphertext = serde_json::from_str(&serde_json::to_string(&tc).unwrap()).unwrap();
+        assert_eq!(trace_decrypt_at(&tok, &json, b"label", 1000).as_deref(), Some(&b"share"[..]));
+
+        let mut t = tc.clone();
+        t.c2[0] ^= 1;
+        assert!(trace_decrypt_at(&tok, &t, b"label", 1000).is_none());
+        let mut t = tc.clone();
+        t.nonce[0] ^= 1;
+        assert!(trace_decrypt_at(&tok, &t, b"label", 1000).is_none());
+        let mut t = tc.clone();
+        t.c1 = enc_point(&(RISTRETTO_BASEPOINT_POINT * Scalar::from(9u64)));
+        assert!(trace_decrypt_at(&tok, &t, b"label", 1000).is_none());
+        assert!(trace_decrypt_at(&tok, &tc, b"label", 1001).is_none());
+    }
+
+    // H(expiry, m) as a scalar, the derivation tokens used to have.
+    fn scalar_token_hash(message: &[u8], expiry: u64) -> Scalar {
+        let mut buf = expiry.to_le_bytes().to_vec();
+        buf.extend_from_slice(message);
+        hash_to_scalar(b"Tracing::Token", &buf)
+    }
+
+    // sk from all m shares, for checking combined tokens.
+    fn setup_secret(group: &AdmitterGroup, shares: &[AdmitterShare]) -> Scalar {
+        let ids: Vec<u32> = shares.iter().map(|s| s.j).collect();
+        let table = LagrangeTable::new(&ids).unwrap();
+        let sk: Scalar = shares.iter().map(|s| table.get(s.j).unwrap() * s.sk_j).sum();
+        assert_eq!(RISTRETTO_BASEPOINT_POINT * sk, group.pk);
+        sk
+    }
+
+    #[test]
+    fn token_and_public_data_do_not_reveal_the_secret() {
+        let ad = setup_admitter();
+        let tok = admitter_issue_token(&ad, MSG, 1000);
+        let h = scalar_token_hash(MSG, 1000);
+
+        // Dividing out the public hash no longer yields sk ...
+        let guess = Scalar::from_bytes_mod_order(enc_point(&tok.tau)) * h.invert();
+        assert_ne!(RISTRETTO_BASEPOINT_POINT * guess, ad.pk);
+        assert_ne!(tok.tau, ad.pk * h);
+        assert_ne!(token_point(MSG, 1000), RISTRETTO_BASEPOINT_POINT * h);
+
+        // ... and rescaling tau to a later expiry does not open its ciphertexts.
+        let later = admitter_issue_token(&ad, MSG, 2000);
+        let tc = trace_encrypt(&later, b"share", b"label");
+        let stretched = TraceToken {
+            tau: tok.tau * (scalar_token_hash(MSG, 2000) * h.invert()),
+            expiry: 2000,
+            ..tok.clone()
+        };
+        assert_ne!(stretched.tau, later.tau);
+        assert!(trace_decrypt_at(&stretched, &tc, b"label", 1000).is_none());
+        assert!(trace_decrypt_at(&later, &tc, b"label", 1000).is_some());
+    }
+
+    #[test]
+    fn setup_admitters_rejects_a_bad_threshold() {
+        assert_eq!(setup_admitters(0, 0).err(), Some(AdmitterSetupError::NoAdmitters));
+        assert_eq!(
+            setup_admitters(3, 3).err(),
+            Some(AdmitterSetupError::ThresholdTooLarge { m: 3, t_trace: 3 })
+        );
+        assert!(setup_admitters(3, 2).is_ok());
+    }
+
+    #[test]
+    fn threshold_token_needs_t_trace_plus_one_admitters() {
+        let (group, shares) = setup_admitters(5, 2).unwrap();
+        let partials: Vec<PartialTraceToken> =
+            shares.iter().map(|s| admitter_issue_partial_token(s, MSG, 1000)).collect();
+        let expected = token_point(MSG, 1000) * setup_secret(&group, &shares);
+
+        // Any t_trace+1 partials rebuild the token of the shared secret.
+        let tok = combine_trace_tokens(&group, &partials[..3]).unwrap();
+        assert_eq!(tok.tau, expected);
+        let other = combine_trace_tokens(&group, &partials[2..]).unwrap();
+        assert_eq!(other.tau, tok.tau);
+        let tc = trace_encrypt(&tok, b"share", b"label");
+        assert_eq!(trace_decrypt_at(&other, &tc, b"label", 1000).unwrap(), b"share");
+
+        // t_trace are refused, and interpolating them anyway misses tau.
+        assert!(combine_trace_tokens(&group, &partials[..2]).is_none());
+        let table = LagrangeTable::new(&[1, 2]).unwrap();
+        let tau: RistrettoPoint = partials[..2].iter().map(|p| p.tau_j * table.get(p.j).unwrap()).sum();
+        assert_ne!(tau, expected);
+    }
+
+    #[test]
+    fn token_proof_accepts_an_honest_token_and_rejects_a_tampered_one() {
+        let ad = setup_admitter();
+        let (tok, proof) = admitter_issue_token_with_proof(&ad, MSG, 1000);
+        assert!(verify_token(&ad.pk, MSG, &tok, &proof));
+
+        // A junk tau, another message or expiry, or another admitter's pk.
+        let junk = TraceToken { tau: tok.tau + RISTRETTO_BASEPOINT_POINT, ..tok.clone() };
+        assert!(!verify_token(&ad.pk, MSG, &junk, &proof));
+        assert!(!verify_token(&ad.pk, b"other", &tok, &proof));
+        assert!(!verify_token(&ad.pk, MSG, &TraceToken { expiry: 1001, ..tok.clone() }, &proof));
+        assert!(!verify_token(&setup_admitter().pk, MSG, &tok, &proof));
+
+        let forged = TokenProof { e: proof.e, s: proof.s + Scalar::ONE };
+        assert!(!verify_token(&ad.pk, MSG, &tok, &forged));
+    }
+
+    #[test]
+    fn token_proof_rejects_a_forged_token() {
+        let ad = setup_admitter();
+
+        // A forger who knows only pk: the old public formula pk * H(expiry, m)
+        // with a proof made from its own key.
+        let rogue = setup_admitter();
+        let (mut forged, mut proof) = admitter_issue_token_with_proof(&rogue, MSG, 1000);
+        assert!(verify_token(&rogue.pk, MSG, &forged, &proof));
+        assert!(!verify_token(&ad.pk, MSG, &forged, &proof));
+        forged.tau = ad.pk * scalar_token_hash(MSG, 1000);
+        assert!(!verify_token(&ad.pk, MSG, &forged, &proof));
+
+        // Nor does a real token's proof carry over to its rescaled form, or
+        // to a token relabelled for another message.
+        let (tok, real) = admitter_issue_token_with_proof(&ad, MSG, 1000);
+        forged.tau = tok.tau * Scalar::from(2u64);
+        assert!(!verify_token(&ad.pk, MSG, &forged, &real));
+        let relabelled = TraceToken { msg_hash: token_msg_hash(b"other"), ..tok.clone() };
+        assert!(!verify_token(&ad.pk, MSG, &relabelled, &real));
+        proof.e = real.e;
+        assert!(!verify_token(&ad.pk, MSG, &tok, &proof));
+    }
+}
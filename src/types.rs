@@ -1,8 +1,18 @@
+use alloc::vec::Vec;
+use core::fmt;
+
 use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::error::Error;
+use crate::group::{Bases, Ristretto};
+use crate::hash::{decode_point, decode_scalar, enc_point, enc_scalar, is_canonical_point_encoding};
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "ParamsWire", into = "ParamsWire")]
 pub struct Params {
     pub n: usize,
     pub t: usize,
@@ -11,65 +21,702 @@ pub struct Params {
     pub v: RistrettoPoint,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Serialize, Deserialize)]
+struct ParamsWire {
+    n: u64,
+    t: u64,
+    g: [u8; 32],
+    h: [u8; 32],
+    v: [u8; 32],
+}
+
+impl From<Params> for ParamsWire {
+    fn from(p: Params) -> Self {
+        ParamsWire {
+            n: p.n as u64,
+            t: p.t as u64,
+            g: enc_point(&p.g),
+            h: enc_point(&p.h),
+            v: enc_point(&p.v),
+        }
+    }
+}
+
+impl TryFrom<ParamsWire> for Params {
+    type Error = Error;
+
+    fn try_from(w: ParamsWire) -> Result<Self, Error> {
+        let n = usize::try_from(w.n).map_err(|_| Error::MalformedEncoding)?;
+        let t = usize::try_from(w.t).map_err(|_| Error::MalformedEncoding)?;
+        if t >= n {
+            return Err(Error::MalformedEncoding);
+        }
+        Ok(Params {
+            n,
+            t,
+            g: decode_point(&w.g)?,
+            h: decode_point(&w.h)?,
+            v: decode_point(&w.v)?,
+        })
+    }
+}
+
+impl Params {
+    /// The generators (g, h, v) for the group-generic code paths.
+    pub fn bases(&self) -> Bases<Ristretto> {
+        Bases { g: self.g, h: self.h, v: self.v }
+    }
+
+    /// g * k, using the precomputed basepoint table when g is the standard
+    /// generator; see `Bases::mul_g`.
+    pub fn mul_g(&self, k: &Scalar) -> RistrettoPoint {
+        self.bases().mul_g(k)
+    }
+
+    pub const ENCODED_LEN: usize = 16 + 3 * 32;
+
+    /// n (u64 LE) || t (u64 LE) || g || h || v, points compressed.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let w = ParamsWire::from(self.clone());
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[..8].copy_from_slice(&w.n.to_le_bytes());
+        out[8..16].copy_from_slice(&w.t.to_le_bytes());
+        for (k, p) in [w.g, w.h, w.v].iter().enumerate() {
+            out[16 + 32 * k..16 + 32 * (k + 1)].copy_from_slice(p);
+        }
+        out
+    }
+
+    /// Inverse of `to_bytes`; fails if t >= n or a generator does not decode.
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Result<Params, Error> {
+        let mut f = [[0u8; 32]; 3];
+        for (k, chunk) in bytes[16..].chunks_exact(32).enumerate() {
+            f[k].copy_from_slice(chunk);
+        }
+        Params::try_from(ParamsWire {
+            n: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            t: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            g: f[0],
+            h: f[1],
+            v: f[2],
+        })
+    }
+
+    /// Digest of (n, t, g, h, v), used to tie archived transcripts to the
+    /// parameters they were produced under.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut h = Sha512::new();
+        h.update(b"Gargos::ParamsFingerprint");
+        h.update((self.n as u64).to_le_bytes());
+        h.update((self.t as u64).to_le_bytes());
+        h.update(enc_point(&self.g));
+        h.update(enc_point(&self.h));
+        h.update(enc_point(&self.v));
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&h.finalize()[..32]);
+        out
+    }
+}
+
+/// Public verification material: the public parameters and the joint key.
+/// This is everything a verifier-only deployment needs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "VerificationKeyWire", into = "VerificationKeyWire")]
+pub struct VerificationKey {
+    pub params_public: Params,
+    pub pk_joint: RistrettoPoint,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct VerificationKeyWire {
+    n: u64,
+    t: u64,
+    g: [u8; 32],
+    h: [u8; 32],
+    v: [u8; 32],
+    pk_joint: [u8; 32],
+}
+
+impl From<VerificationKey> for VerificationKeyWire {
+    fn from(vk: VerificationKey) -> Self {
+        let p = ParamsWire::from(vk.params_public);
+        VerificationKeyWire {
+            n: p.n,
+            t: p.t,
+            g: p.g,
+            h: p.h,
+            v: p.v,
+            pk_joint: enc_point(&vk.pk_joint),
+        }
+    }
+}
+
+impl TryFrom<VerificationKeyWire> for VerificationKey {
+    type Error = Error;
+
+    fn try_from(w: VerificationKeyWire) -> Result<Self, Error> {
+        Ok(VerificationKey {
+            params_public: Params::try_from(ParamsWire { n: w.n, t: w.t, g: w.g, h: w.h, v: w.v })?,
+            pk_joint: decode_point(&w.pk_joint)?,
+        })
+    }
+}
+
+impl VerificationKey {
+    pub const ENCODED_LEN: usize = Params::ENCODED_LEN + 32;
+
+    /// Params::to_bytes || pk_joint (compressed).
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[..Params::ENCODED_LEN].copy_from_slice(&self.params_public.to_bytes());
+        out[Params::ENCODED_LEN..].copy_from_slice(&enc_point(&self.pk_joint));
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Result<VerificationKey, Error> {
+        let mut p = [0u8; Params::ENCODED_LEN];
+        let mut pk = [0u8; 32];
+        p.copy_from_slice(&bytes[..Params::ENCODED_LEN]);
+        pk.copy_from_slice(&bytes[Params::ENCODED_LEN..]);
+        Ok(VerificationKey {
+            params_public: Params::from_bytes(&p)?,
+            pk_joint: decode_point(&pk)?,
+        })
+    }
+}
+
+/// Wiped on drop and left out of Debug output.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SecretKeyShare {
     pub s: Scalar,
     pub r: Scalar,
     pub u: Scalar,
 }
 
+impl fmt::Debug for SecretKeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKeyShare")
+            .field("s", &"<redacted>")
+            .field("r", &"<redacted>")
+            .field("u", &"<redacted>")
+            .finish()
+    }
+}
+
+impl SecretKeyShare {
+    pub const ENCODED_LEN: usize = 96;
+
+    /// s || r || u, each a canonical 32-byte scalar. The buffer is wiped
+    /// when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<[u8; Self::ENCODED_LEN]> {
+        let mut out = Zeroizing::new([0u8; Self::ENCODED_LEN]);
+        out[..32].copy_from_slice(&enc_scalar(&self.s));
+        out[32..64].copy_from_slice(&enc_scalar(&self.r));
+        out[64..].copy_from_slice(&enc_scalar(&self.u));
+        out
+    }
+
+    /// Inverse of `to_bytes`; rejects scalars that are not reduced mod ℓ.
+    pub fn from_bytes(bytes: &[u8; Self::ENCODED_LEN]) -> Result<SecretKeyShare, Error> {
+        let mut f = Zeroizing::new([[0u8; 32]; 3]);
+        for (k, chunk) in bytes.chunks_exact(32).enumerate() {
+            f[k].copy_from_slice(chunk);
+        }
+        Ok(SecretKeyShare {
+            s: decode_scalar(&f[0])?,
+            r: decode_scalar(&f[1])?,
+            u: decode_scalar(&f[2])?,
+        })
+    }
+}
+
+// Secret shares only (de)serialize with the "serde-secrets" feature, so a
+// stray `serde_json::to_string` on a signer's state cannot leak them by
+// default. The encoding is `to_bytes`, written as a byte string.
+#[cfg(feature = "serde-secrets")]
+impl Serialize for SecretKeyShare {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+#[cfg(feature = "serde-secrets")]
+impl<'de> Deserialize<'de> for SecretKeyShare {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+        let v = Zeroizing::new(Vec::<u8>::deserialize(deserializer)?);
+        let bytes: &[u8; Self::ENCODED_LEN] = v[..]
+            .try_into()
+            .map_err(|_| D::Error::invalid_length(v.len(), &"96 bytes"))?;
+        SecretKeyShare::from_bytes(bytes).map_err(D::Error::custom)
+    }
+}
+
+/// Dealer's coefficient commitments for s(x), r(x), u(x):
+/// `s[k] = g*s_k`, `r[k] = h*r_k`, `u[k] = v*u_k` for k = 0..=t.
+/// `Σ_k (s[k] + r[k] + u[k]) i^k` is the expected pk_i of signer i.
+#[derive(Clone, Debug)]
+pub struct ShareCommitments {
+    pub s: Vec<RistrettoPoint>,
+    pub r: Vec<RistrettoPoint>,
+    pub u: Vec<RistrettoPoint>,
+}
+
 #[derive(Clone, Debug)]
 pub struct PublicKeyShare {
     pub pk_i: RistrettoPoint,
 }
 
+/// id -> share lookup over (id, share) lists such as those from
+/// `kgen_for_ids`, for deployments whose ids are not 1..=n.
+pub fn share_for<T>(shares: &[(u32, T)], id: u32) -> Option<&T> {
+    shares.iter().find(|(j, _)| *j == id).map(|(_, s)| s)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CommitmentMessage {
     pub i: u32,
     pub mu_i: [u8; 32],
 }
 
+/// Sig2 broadcast: (A_i, rho_i, B_i, π_i). Only public values; the nonce
+/// a_i stays in `SignerState`, since a_i and z_i together reveal s(i).
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OpeningMessage {
     pub i: u32,
-    pub a_i: [u8; 32],      // NOT sent; stored in state only (keep layout simple)
     pub a_point: [u8; 32],  // Ai compressed
     pub rho_i: [u8; 32],
     pub b_point: [u8; 32],  // Bi compressed
     pub proof: super::nizk::Proof,
 }
 
+/// The field of an `OpeningMessage` or `NonceReveal` that
+/// `validate_encoding` rejected. The proof fields are shared: an opening's
+/// and a reveal's proof both carry XA, Xpk and za..zu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The serialized opening does not have `OpeningMessage::ENCODED_LEN`
+    /// bytes.
+    Length(usize),
+    /// The serialized reveal does not have `NonceReveal::ENCODED_LEN` bytes.
+    RevealLength(usize),
+    /// Signer id 0, which is reserved.
+    ZeroSignerId,
+    /// A_i is not a canonical Ristretto encoding.
+    APoint,
+    /// B_i is not a canonical Ristretto encoding.
+    BPoint,
+    /// R_i is not a canonical Ristretto encoding.
+    RPoint,
+    /// S_i is not a canonical Ristretto encoding.
+    SPoint,
+    // The proof's commitments, not canonical Ristretto encodings...
+    ProofXa,
+    ProofXb,
+    ProofXpk,
+    ProofXr,
+    ProofXs,
+    // ...and its responses, not canonical scalars.
+    ProofZa,
+    ProofZs,
+    ProofZr,
+    ProofZu,
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Length(len) => {
+                write!(f, "opening is {} bytes, expected {}", len, OpeningMessage::ENCODED_LEN)
+            }
+            DecodeError::RevealLength(len) => {
+                write!(f, "nonce reveal is {} bytes, expected {}", len, NonceReveal::ENCODED_LEN)
+            }
+            DecodeError::ZeroSignerId => write!(f, "message from reserved signer id 0"),
+            DecodeError::APoint => write!(f, "opening has a malformed A_i"),
+            DecodeError::BPoint => write!(f, "opening has a malformed B_i"),
+            DecodeError::RPoint => write!(f, "nonce reveal has a malformed R_i"),
+            DecodeError::SPoint => write!(f, "nonce reveal has a malformed S_i"),
+            DecodeError::ProofXa => write!(f, "proof has a malformed XA"),
+            DecodeError::ProofXb => write!(f, "proof has a malformed XB"),
+            DecodeError::ProofXpk => write!(f, "proof has a malformed Xpk"),
+            DecodeError::ProofXr => write!(f, "proof has a malformed XR"),
+            DecodeError::ProofXs => write!(f, "proof has a malformed XS"),
+            DecodeError::ProofZa => write!(f, "proof has a non-canonical za"),
+            DecodeError::ProofZs => write!(f, "proof has a non-canonical zs"),
+            DecodeError::ProofZr => write!(f, "proof has a non-canonical zr"),
+            DecodeError::ProofZu => write!(f, "proof has a non-canonical zu"),
+        }
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PartialSignature {
     pub i: u32,
-    pub z_i: [u8; 32], // Scalar bytes
+    pub z_i: [u8; 32],          // Scalar bytes
+    pub a_hat_digest: [u8; 32], // HAhat(A_hat) of the A_hat behind c
 }
 
-#[derive(Clone, Debug)]
+/// Optional side message after Sig2 that makes z_i checkable on its own
+/// (see `SigningSession::verify_partial`): R_i = g*a_i and S_i = g*s(i),
+/// with a proof (XR, XA, XS, Xpk; za, zs, zr, zu) that A_i - R_i = G0*r +
+/// G1*u and pk_i - S_i = h*r + v*u for one (a_i, s(i), r, u).
+///
+/// Why a new message: z_i = a_i + c*L_{i,SS}*s(i) is tied to the g-parts
+/// of A_i and pk_i, but the round messages only carry those behind the
+/// G0/G1 and h/v masks, which cancel in the Lagrange sum over SS and
+/// nowhere else. Stripping them needs r(i) and u(i), so no combination of
+/// the commitments, openings and pk_i yields g*a_i or g*s(i).
+///
+/// Why it is safe to send: S_i is already public through the dealer's
+/// `ShareCommitments`; R_i is the per-signer nonce commitment FROST
+/// publishes. It goes out after every opening is fixed, so it cannot steer
+/// A_hat, and the proof is a Schnorr-style Σ-protocol that shows nothing
+/// about r(i), u(i) beyond the two relations. What it gives up is the
+/// hiding of g*a_i that A_i provides, so a deployment that relies on it
+/// simply does not send reveals. Parse it with `from_bytes` or run
+/// `validate_encoding` after `Deserialize`, as for an `OpeningMessage`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NonceReveal {
+    pub i: u32,
+    pub r_point: [u8; 32], // R_i compressed
+    pub s_point: [u8; 32], // S_i compressed
+    pub xr: [u8; 32],
+    pub xa: [u8; 32],
+    pub xs: [u8; 32],
+    pub xpk: [u8; 32],
+    pub za: [u8; 32],
+    pub zs: [u8; 32],
+    pub zr: [u8; 32],
+    pub zu: [u8; 32],
+}
+
+impl NonceReveal {
+    pub const ENCODED_LEN: usize = 4 + 10 * 32;
+
+    /// Decompress every point and check every scalar is canonical, naming
+    /// the first bad field, as `OpeningMessage::validate_encoding` does.
+    pub fn validate_encoding(&self) -> Result<(), DecodeError> {
+        if self.i == 0 {
+            return Err(DecodeError::ZeroSignerId);
+        }
+        for (bytes, err) in [
+            (&self.r_point, DecodeError::RPoint),
+            (&self.s_point, DecodeError::SPoint),
+            (&self.xr, DecodeError::ProofXr),
+            (&self.xa, DecodeError::ProofXa),
+            (&self.xs, DecodeError::ProofXs),
+            (&self.xpk, DecodeError::ProofXpk),
+        ] {
+            decode_point(bytes).map_err(|_| err)?;
+        }
+        for (bytes, err) in [
+            (&self.za, DecodeError::ProofZa),
+            (&self.zs, DecodeError::ProofZs),
+            (&self.zr, DecodeError::ProofZr),
+            (&self.zu, DecodeError::ProofZu),
+        ] {
+            decode_scalar(bytes).map_err(|_| err)?;
+        }
+        Ok(())
+    }
+
+    /// i (u32 LE) || R_i || S_i || XR || XA || XS || Xpk || za || zs || zr || zu.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[..4].copy_from_slice(&self.i.to_le_bytes());
+        for (k, f) in [
+            &self.r_point,
+            &self.s_point,
+            &self.xr,
+            &self.xa,
+            &self.xs,
+            &self.xpk,
+            &self.za,
+            &self.zs,
+            &self.zr,
+            &self.zu,
+        ]
+        .iter()
+        .enumerate()
+        {
+            out[4 + 32 * k..4 + 32 * (k + 1)].copy_from_slice(*f);
+        }
+        out
+    }
+
+    /// Inverse of `to_bytes`, for input of any length; runs
+    /// `validate_encoding` on the result.
+    pub fn from_bytes(bytes: &[u8]) -> Result<NonceReveal, DecodeError> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(DecodeError::RevealLength(bytes.len()));
+        }
+        let field = |k: usize| -> [u8; 32] { bytes[4 + 32 * k..4 + 32 * (k + 1)].try_into().unwrap() };
+        let reveal = NonceReveal {
+            i: u32::from_le_bytes(bytes[..4].try_into().unwrap()),
+            r_point: field(0),
+            s_point: field(1),
+            xr: field(2),
+            xa: field(3),
+            xs: field(4),
+            xpk: field(5),
+            za: field(6),
+            zs: field(7),
+            zr: field(8),
+            zu: field(9),
+        };
+        reveal.validate_encoding()?;
+        Ok(reveal)
+    }
+}
+
+/// A combined signature: g*z == A_hat + c*pk_joint with
+/// c = Hsig(SS, m, A_hat, pk_joint).
+///
+/// It cannot be re-randomized by whoever holds it. c hashes A_hat, so
+/// moving A_hat to A_hat + g*δ changes c to an unrelated c', and the
+/// matching z' = z + δ + (c' - c)*s(0) needs the joint secret. Computing z'
+/// from (A_hat, z) alone would be a forgery of a new signature on the same
+/// message, which the strong unforgeability of Schnorr signatures rules
+/// out. A second, unlinkable signature on the same message comes from a
+/// fresh signing session: its nonces are independent of the first one's.
+// A_hat keeps the paper's notation.
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "SignatureWire", into = "SignatureWire")]
 pub struct Signature {
     pub A_hat: RistrettoPoint,
     pub z: Scalar,
 }
 
-/// Local signer state across rounds
-#[derive(Clone, Debug)]
+/// Serde form of a Signature: the two halves of `to_bytes`.
+#[derive(Clone, Serialize, Deserialize)]
+struct SignatureWire {
+    a_hat: [u8; 32],
+    z: [u8; 32],
+}
+
+impl From<Signature> for SignatureWire {
+    fn from(sig: Signature) -> Self {
+        SignatureWire { a_hat: enc_point(&sig.A_hat), z: enc_scalar(&sig.z) }
+    }
+}
+
+impl TryFrom<SignatureWire> for Signature {
+    type Error = Error;
+
+    fn try_from(w: SignatureWire) -> Result<Self, Error> {
+        Ok(Signature { A_hat: decode_point(&w.a_hat)?, z: decode_scalar(&w.z)? })
+    }
+}
+
+impl Signature {
+    /// Compact encoding: compressed A_hat || z.
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut out = [0u8; 64];
+        out[..32].copy_from_slice(&enc_point(&self.A_hat));
+        out[32..].copy_from_slice(&enc_scalar(&self.z));
+        out
+    }
+
+    /// Inverse of `to_bytes`. A_hat that is not a canonical encoding is
+    /// Error::NonCanonicalPoint, a canonical one that is not a point
+    /// Error::PointDecodeFailed, and z not reduced mod ℓ
+    /// Error::NonCanonicalScalar.
+    pub fn try_from_bytes(bytes: &[u8; 64]) -> Result<Signature, Error> {
+        let mut a_hat = [0u8; 32];
+        let mut z = [0u8; 32];
+        a_hat.copy_from_slice(&bytes[..32]);
+        z.copy_from_slice(&bytes[32..]);
+        Signature::try_from(SignatureWire { a_hat, z })
+    }
+
+    /// `try_from_bytes` without the reason: `None` for a non-canonical
+    /// point or scalar encoding.
+    pub fn from_bytes(bytes: &[u8; 64]) -> Option<Signature> {
+        Signature::try_from_bytes(bytes).ok()
+    }
+
+    /// Low-s normalization, for callers ported from ECDSA-style APIs. This
+    /// is deliberately a no-op. Unlike ECDSA, these signatures are not
+    /// malleable in z: given A_hat, z is the unique scalar with
+    /// g*z = A_hat + c*pk. Negating z breaks the equation, and the
+    /// canonical-scalar check in `from_bytes` rules out z + l. An upper-half
+    /// z is as valid as a lower-half one (about half of all honest
+    /// signatures have one), so no low-s rule exists to enforce either.
+    pub fn normalize_low_s(&self) -> Signature {
+        self.clone()
+    }
+
+    /// The signature as a plain Schnorr pair `(R, s)` = (compressed A_hat, z),
+    /// for verifiers outside this crate. It is not an Ed25519 or schnorrkel
+    /// signature: the group is ristretto255 and the challenge is
+    ///
+    ///   c = SHA-512("Gargos::Hsig::v3" || SS || message || R || PK) mod ℓ
+    ///
+    /// read as a little-endian 512-bit integer, where R and PK are 32-byte
+    /// compressed Ristretto encodings, PK is the joint key and SS is the
+    /// signing set as `hash::encode_signing_set` writes it: the number of
+    /// ids, then the ids in ascending order, each a u32 LE. The verifier
+    /// rejects a non-canonical R, PK or s, then accepts iff
+    ///
+    ///   `[s]B == R + [c]PK`
+    ///
+    /// with B the Ristretto basepoint. This is `verify` for `setup`
+    /// parameters, whose g is B; for any other g, B must be replaced by it.
+    pub fn to_standard_schnorr(&self) -> ([u8; 32], [u8; 32]) {
+        (enc_point(&self.A_hat), enc_scalar(&self.z))
+    }
+}
+
+impl OpeningMessage {
+    pub const ENCODED_LEN: usize = 4 + 3 * 32 + 224;
+
+    /// `validate_encoding` with the reason as a crate `Error`: a zero id
+    /// is `InvalidSignerId(0)`, a bad point `NonCanonicalPoint` or
+    /// `PointDecodeFailed`, a bad response `NonCanonicalScalar`.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.validate_encoding().map_err(|e| {
+            let p = &self.proof;
+            let point = match e {
+                DecodeError::Length(_) => return Error::MalformedEncoding,
+                DecodeError::ZeroSignerId => return Error::InvalidSignerId(0),
+                DecodeError::APoint => &self.a_point,
+                DecodeError::BPoint => &self.b_point,
+                DecodeError::ProofXa => &p.xa,
+                DecodeError::ProofXb => &p.xb,
+                DecodeError::ProofXpk => &p.xpk,
+                DecodeError::ProofZa | DecodeError::ProofZs | DecodeError::ProofZr | DecodeError::ProofZu => {
+                    return Error::NonCanonicalScalar
+                }
+                DecodeError::RevealLength(_)
+                | DecodeError::RPoint
+                | DecodeError::SPoint
+                | DecodeError::ProofXr
+                | DecodeError::ProofXs => unreachable!("not a field of an opening"),
+            };
+            if is_canonical_point_encoding(point) {
+                Error::PointDecodeFailed
+            } else {
+                Error::NonCanonicalPoint
+            }
+        })
+    }
+
+    /// Decompress every point and check every scalar is canonical, naming
+    /// the first bad field. `Deserialize` accepts any 32 bytes per field,
+    /// and Sig3 would otherwise only report the sender
+    /// (`SignError::PointDecodeFailed`, or `ProofInvalid` for a bad proof
+    /// encoding); calling this right after parsing rejects malformed input
+    /// before it reaches the protocol. Identity A_i or B_i decode fine and
+    /// are left to Sig3.
+    pub fn validate_encoding(&self) -> Result<(), DecodeError> {
+        if self.i == 0 {
+            return Err(DecodeError::ZeroSignerId);
+        }
+        let p = &self.proof;
+        for (bytes, err) in [
+            (&self.a_point, DecodeError::APoint),
+            (&self.b_point, DecodeError::BPoint),
+            (&p.xa, DecodeError::ProofXa),
+            (&p.xb, DecodeError::ProofXb),
+            (&p.xpk, DecodeError::ProofXpk),
+        ] {
+            decode_point(bytes).map_err(|_| err)?;
+        }
+        for (bytes, err) in [
+            (&p.za, DecodeError::ProofZa),
+            (&p.zs, DecodeError::ProofZs),
+            (&p.zr, DecodeError::ProofZr),
+            (&p.zu, DecodeError::ProofZu),
+        ] {
+            decode_scalar(bytes).map_err(|_| err)?;
+        }
+        Ok(())
+    }
+
+    /// i (u32 LE) || A_i || rho_i || B_i || proof (`Proof::to_bytes`).
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[..4].copy_from_slice(&self.i.to_le_bytes());
+        out[4..36].copy_from_slice(&self.a_point);
+        out[36..68].copy_from_slice(&self.rho_i);
+        out[68..100].copy_from_slice(&self.b_point);
+        out[100..].copy_from_slice(&self.proof.to_bytes());
+        out
+    }
+
+    /// Inverse of `to_bytes`, for input of any length; runs
+    /// `validate_encoding` on the result.
+    pub fn from_bytes(bytes: &[u8]) -> Result<OpeningMessage, DecodeError> {
+        if bytes.len() != Self::ENCODED_LEN {
+            return Err(DecodeError::Length(bytes.len()));
+        }
+        let field = |k: usize| -> [u8; 32] { bytes[k..k + 32].try_into().unwrap() };
+        let om = OpeningMessage {
+            i: u32::from_le_bytes(bytes[..4].try_into().unwrap()),
+            a_point: field(4),
+            rho_i: field(36),
+            b_point: field(68),
+            proof: super::nizk::Proof {
+                xa: field(100),
+                xb: field(132),
+                xpk: field(164),
+                za: field(196),
+                zs: field(228),
+                zr: field(260),
+                zu: field(292),
+            },
+        };
+        om.validate_encoding()?;
+        Ok(om)
+    }
+}
+
+/// Local signer state across rounds. The nonce a_i and rho_i are wiped on
+/// drop and left out of Debug output; the remaining fields are public values.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
 pub struct SignerState {
+    #[zeroize(skip)]
     pub i: u32,
     pub a_i: Scalar,
     pub rho_i: [u8; 32],
+    #[zeroize(skip)]
     pub b_i: RistrettoPoint,
 
     // after Sig2
+    #[zeroize(skip)]
     pub a_i_point: RistrettoPoint,
+    #[zeroize(skip)]
     pub mu_vec: Vec<(u32, [u8; 32])>,
+    #[zeroize(skip)]
     pub g0: RistrettoPoint,
+    #[zeroize(skip)]
     pub g1: RistrettoPoint,
 }
 
+impl fmt::Debug for SignerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignerState")
+            .field("i", &self.i)
+            .field("a_i", &"<redacted>")
+            .field("rho_i", &"<redacted>")
+            .field("b_i", &self.b_i)
+            .field("a_i_point", &self.a_i_point)
+            .field("mu_vec", &self.mu_vec)
+            .field("g0", &self.g0)
+            .field("g1", &self.g1)
+            .finish()
+    }
+}
+
 // =============================
 // TiMTAPS-style extensions types
 // =============================
 
+#[cfg(feature = "std")]
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct TimedShare {
     pub i: u32,
@@ -84,7 +731,368 @@ pub struct VerifiableCommitmentMsg {
     pub c_i: [u8; 32],
 }
 
+#[cfg(feature = "std")]
 #[derive(Clone, Debug)]
 pub struct TracingBundle {
     pub trace_ct: crate::tracing::TraceCiphertext,
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::testutil::Session;
+
+    #[test]
+    fn debug_output_leaves_out_the_secrets() {
+        let s = Session::new(3, 1);
+        let sk = s.sk(1);
+        let shown = format!("{:?}", sk);
+        for secret in [&sk.s, &sk.r, &sk.u] {
+            assert!(!shown.contains(&format!("{:?}", secret)));
+        }
+
+        let st = &s.states[0];
+        let shown = format!("{:?}", st);
+        assert!(!shown.contains(&format!("{:?}", st.a_i)));
+        assert!(!shown.contains(&format!("{:?}", st.rho_i)));
+        assert!(shown.contains(&format!("{:?}", st.mu_vec)));
+    }
+
+    #[test]
+    fn signature_bytes_round_trip() {
+        let sig = Session::new(3, 1).signature();
+        let back = Signature::from_bytes(&sig.to_bytes()).unwrap();
+        assert_eq!((back.A_hat, back.z), (sig.A_hat, sig.z));
+    }
+
+    #[test]
+    fn signature_from_bytes_rejects_non_canonical_halves() {
+        let sig = Session::new(3, 1).signature();
+        let mut bytes = sig.to_bytes();
+        bytes[31] |= 0x80;
+        assert!(Signature::from_bytes(&bytes).is_none());
+        assert_eq!(Signature::try_from_bytes(&bytes).err(), Some(Error::NonCanonicalPoint));
+
+        // Canonical, but not the encoding of any Ristretto point.
+        let mut bytes = sig.to_bytes();
+        bytes[..32].copy_from_slice(&[0; 32]);
+        bytes[0] = 2;
+        assert!(Signature::from_bytes(&bytes).is_none());
+        assert_eq!(Signature::try_from_bytes(&bytes).err(), Some(Error::PointDecodeFailed));
+
+        let mut bytes = sig.to_bytes();
+        bytes[32..].copy_from_slice(&[0xff; 32]);
+        assert!(Signature::from_bytes(&bytes).is_none());
+        assert_eq!(Signature::try_from_bytes(&bytes).err(), Some(Error::NonCanonicalScalar));
+
+        let back = Signature::try_from_bytes(&sig.to_bytes()).unwrap();
+        assert_eq!((back.A_hat, back.z), (sig.A_hat, sig.z));
+    }
+
+    #[test]
+    fn upper_half_z_verifies_and_is_not_malleable() {
+        use crate::protocol::verify;
+        use crate::testutil::MSG;
+
+        // z is in the upper half iff it exceeds -z as an integer.
+        let upper = |z: &Scalar| z.to_bytes().iter().rev().cmp((-z).to_bytes().iter().rev()).is_gt();
+        let mut s = Session::new(3, 1);
+        let mut sig = s.signature();
+        while !upper(&sig.z) {
+            s.run(MSG);
+            sig = s.signature();
+        }
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &sig));
+
+        let same = sig.normalize_low_s();
+        assert_eq!(same.to_bytes(), sig.to_bytes());
+
+        let negated = Signature { A_hat: sig.A_hat, z: -sig.z };
+        assert!(!verify(&s.par, &s.pk_joint, &s.ss, MSG, &negated));
+    }
+
+    #[test]
+    fn signatures_are_only_rerandomized_with_the_secret() {
+        use crate::hash::hsig;
+        use crate::protocol::verify;
+        use crate::randutil::random_scalar;
+        use crate::shamir::recover_secret;
+        use crate::testutil::MSG;
+
+        let s = Session::new(3, 1);
+        let sig = s.signature();
+        let delta = random_scalar();
+        let a_shifted = sig.A_hat + s.par.g * delta;
+
+        // Shifting both halves by δ is the naive re-randomization; the
+        // challenge moves with A_hat, so it does not verify.
+        let naive = Signature { A_hat: a_shifted, z: sig.z + delta };
+        assert!(!verify(&s.par, &s.pk_joint, &s.ss, MSG, &naive));
+
+        // Fixing up z takes s(0).
+        let shares: Vec<_> = s.ss.iter().map(|&i| (i, s.sk(i).s)).collect();
+        let s0 = recover_secret(&shares, s.par.t).unwrap();
+        let c = hsig(&sig.A_hat, &s.pk_joint, &s.ss, MSG);
+        let c_shifted = hsig(&a_shifted, &s.pk_joint, &s.ss, MSG);
+        let fresh = Signature { A_hat: a_shifted, z: sig.z + delta + (c_shifted - c) * s0 };
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, MSG, &fresh));
+        assert_ne!(fresh.to_bytes(), sig.to_bytes());
+    }
+
+    #[test]
+    fn secrets_are_wiped_on_drop() {
+        use core::ptr::{addr_of, read};
+        use crate::testutil::after_drop;
+
+        let s = Session::new(3, 1);
+        let sk = s.sk(1).clone();
+        assert_ne!(sk.s, Scalar::ZERO);
+        // SAFETY: Scalar is a 32-byte array; the pointers come from after_drop.
+        let left = after_drop(sk, |p| unsafe {
+            [read(addr_of!((*p).s) as *const [u8; 32]), read(addr_of!((*p).r) as *const [u8; 32]),
+             read(addr_of!((*p).u) as *const [u8; 32])]
+        });
+        assert_eq!(left, [[0u8; 32]; 3]);
+
+        let st = s.states[0].clone();
+        assert_ne!(st.rho_i, [0u8; 32]);
+        let left = after_drop(st, |p| unsafe {
+            [read(addr_of!((*p).a_i) as *const [u8; 32]), read(addr_of!((*p).rho_i))]
+        });
+        assert_eq!(left, [[0u8; 32]; 2]);
+    }
+
+    #[test]
+    fn reloaded_secret_shares_still_sign() {
+        use crate::protocol::verify;
+
+        let mut s = Session::new(4, 2);
+        let reloaded: Vec<(u32, SecretKeyShare)> = s
+            .sk_shares
+            .iter()
+            .map(|(i, sk)| (*i, SecretKeyShare::from_bytes(&sk.to_bytes()).unwrap()))
+            .collect();
+        for ((_, a), (_, b)) in s.sk_shares.iter().zip(&reloaded) {
+            assert_eq!((a.s, a.r, a.u), (b.s, b.r, b.u));
+            assert_eq!(&b.to_bytes()[..32], a.s.as_bytes());
+        }
+        s.sk_shares = reloaded;
+        s.run(b"after a restart");
+        assert!(verify(&s.par, &s.pk_joint, &s.ss, b"after a restart", &s.signature()));
+    }
+
+    #[test]
+    fn secret_share_rejects_a_non_canonical_scalar() {
+        let bytes = Session::new(3, 1).sk(1).to_bytes();
+        for k in 0..3 {
+            let mut bad = *bytes;
+            bad[32 * k + 31] = 0xff;
+            assert_eq!(SecretKeyShare::from_bytes(&bad).err(), Some(Error::NonCanonicalScalar));
+        }
+    }
+
+    #[cfg(feature = "serde-secrets")]
+    #[test]
+    fn secret_share_serde_uses_the_byte_encoding() {
+        let sk = Session::new(3, 1).sk(2).clone();
+        let json = serde_json::to_string(&sk).unwrap();
+        assert_eq!(json, serde_json::to_string(&sk.to_bytes()[..]).unwrap());
+        let back: SecretKeyShare = serde_json::from_str(&json).unwrap();
+        assert_eq!((back.s, back.r, back.u), (sk.s, sk.r, sk.u));
+
+        assert!(serde_json::from_str::<SecretKeyShare>("[1,2,3]").is_err());
+        let mut bad = *sk.to_bytes();
+        bad[95] = 0xff;
+        let json = serde_json::to_string(&bad[..]).unwrap();
+        assert!(serde_json::from_str::<SecretKeyShare>(&json).is_err());
+    }
+
+    #[test]
+    fn honest_opening_validates() {
+        let s = Session::new(3, 1);
+        for om in &s.openings {
+            assert_eq!(om.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn opening_validate_reports_the_reason() {
+        let mut om = Session::new(3, 1).openings[0].clone();
+        om.b_point[31] |= 0x80;
+        assert_eq!(om.validate(), Err(Error::NonCanonicalPoint));
+
+        // Canonical, but not the encoding of any Ristretto point.
+        let mut om = Session::new(3, 1).openings[0].clone();
+        om.proof.xa = [0u8; 32];
+        om.proof.xa[0] = 2;
+        assert_eq!(om.validate(), Err(Error::PointDecodeFailed));
+
+        let mut om = Session::new(3, 1).openings[0].clone();
+        om.proof.zr = [0xff; 32];
+        assert_eq!(om.validate(), Err(Error::NonCanonicalScalar));
+
+        om.i = 0;
+        assert_eq!(om.validate(), Err(Error::InvalidSignerId(0)));
+    }
+
+    #[test]
+    fn validate_encoding_names_each_malformed_field() {
+        let honest = Session::new(3, 1).openings[0].clone();
+        assert_eq!(honest.validate_encoding(), Ok(()));
+        let bytes = honest.to_bytes();
+        assert_eq!(OpeningMessage::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+
+        // Byte offset of each field in `to_bytes`, its error, and the field.
+        type Field = fn(&mut OpeningMessage) -> &mut [u8; 32];
+        let fields: [(usize, DecodeError, Field); 9] = [
+            (4, DecodeError::APoint, |om| &mut om.a_point),
+            (68, DecodeError::BPoint, |om| &mut om.b_point),
+            (100, DecodeError::ProofXa, |om| &mut om.proof.xa),
+            (132, DecodeError::ProofXb, |om| &mut om.proof.xb),
+            (164, DecodeError::ProofXpk, |om| &mut om.proof.xpk),
+            (196, DecodeError::ProofZa, |om| &mut om.proof.za),
+            (228, DecodeError::ProofZs, |om| &mut om.proof.zs),
+            (260, DecodeError::ProofZr, |om| &mut om.proof.zr),
+            (292, DecodeError::ProofZu, |om| &mut om.proof.zu),
+        ];
+        for (at, err, field) in fields {
+            // All-ones is neither a canonical point nor a reduced scalar.
+            let mut bad = bytes;
+            bad[at..at + 32].copy_from_slice(&[0xff; 32]);
+            assert_eq!(OpeningMessage::from_bytes(&bad).err(), Some(err));
+
+            // Deserialize takes it; validate_encoding names the field.
+            let mut om = honest.clone();
+            *field(&mut om) = [0xff; 32];
+            let om: OpeningMessage = serde_json::from_str(&serde_json::to_string(&om).unwrap()).unwrap();
+            assert_eq!(om.validate_encoding(), Err(err));
+        }
+
+        // A point encoding with the sign bit set is non-canonical too.
+        let mut bad = bytes;
+        bad[68 + 31] |= 0x80;
+        assert_eq!(OpeningMessage::from_bytes(&bad).err(), Some(DecodeError::BPoint));
+
+        // rho_i is opaque; any value is well formed.
+        let mut other_rho = bytes;
+        other_rho[36..68].copy_from_slice(&[0xff; 32]);
+        assert!(OpeningMessage::from_bytes(&other_rho).is_ok());
+
+        let mut zero_id = bytes;
+        zero_id[..4].copy_from_slice(&[0; 4]);
+        assert_eq!(OpeningMessage::from_bytes(&zero_id).err(), Some(DecodeError::ZeroSignerId));
+        let long = [bytes.as_slice(), &[0]].concat();
+        for len in [0, OpeningMessage::ENCODED_LEN - 1, OpeningMessage::ENCODED_LEN + 1] {
+            assert_eq!(OpeningMessage::from_bytes(&long[..len]).err(), Some(DecodeError::Length(len)));
+        }
+    }
+
+    #[test]
+    fn nonce_reveal_bytes_round_trip_and_name_each_malformed_field() {
+        let s = Session::new(3, 1);
+        let st = &s.states[0];
+        let honest = crate::protocol::reveal_nonce(&s.par, s.pk(st.i), s.sk(st.i), st);
+        assert_eq!(honest.validate_encoding(), Ok(()));
+        let bytes = honest.to_bytes();
+        let back = NonceReveal::from_bytes(&bytes).unwrap();
+        assert_eq!(back.to_bytes(), bytes);
+
+        let errs = [
+            DecodeError::RPoint,
+            DecodeError::SPoint,
+            DecodeError::ProofXr,
+            DecodeError::ProofXa,
+            DecodeError::ProofXs,
+            DecodeError::ProofXpk,
+            DecodeError::ProofZa,
+            DecodeError::ProofZs,
+            DecodeError::ProofZr,
+            DecodeError::ProofZu,
+        ];
+        for (k, err) in errs.into_iter().enumerate() {
+            let mut bad = bytes;
+            bad[4 + 32 * k..4 + 32 * (k + 1)].copy_from_slice(&[0xff; 32]);
+            assert_eq!(NonceReveal::from_bytes(&bad).err(), Some(err));
+        }
+
+        // Deserialize takes any 32 bytes; validate_encoding names the field.
+        let mut reveal = honest.clone();
+        reveal.zr = [0xff; 32];
+        let reveal: NonceReveal = serde_json::from_str(&serde_json::to_string(&reveal).unwrap()).unwrap();
+        assert_eq!(reveal.validate_encoding(), Err(DecodeError::ProofZr));
+
+        let mut zero_id = bytes;
+        zero_id[..4].copy_from_slice(&[0; 4]);
+        assert_eq!(NonceReveal::from_bytes(&zero_id).err(), Some(DecodeError::ZeroSignerId));
+        let long = [bytes.as_slice(), &[0]].concat();
+        for len in [0, NonceReveal::ENCODED_LEN - 1, NonceReveal::ENCODED_LEN + 1] {
+            assert_eq!(NonceReveal::from_bytes(&long[..len]).err(), Some(DecodeError::RevealLength(len)));
+        }
+    }
+
+    #[test]
+    fn verification_key_round_trips_and_verifies() {
+        let s = Session::new(4, 2);
+        let vk = VerificationKey { params_public: s.par.clone(), pk_joint: s.pk_joint };
+        let sig = s.signature();
+
+        let from_bytes = VerificationKey::from_bytes(&vk.to_bytes()).unwrap();
+        let from_json: VerificationKey = serde_json::from_str(&serde_json::to_string(&vk).unwrap()).unwrap();
+        for vk in [from_bytes, from_json] {
+            assert_eq!(vk.to_bytes(), VerificationKey { params_public: s.par.clone(), pk_joint: s.pk_joint }.to_bytes());
+            assert!(crate::protocol::verify_with_key(&vk, &s.ss, crate::testutil::MSG, &sig));
+        }
+    }
+
+    #[test]
+    fn decoded_params_sign_and_verify_like_the_originals() {
+        use crate::protocol::verify;
+        use crate::testutil::MSG;
+
+        let mut s = Session::new(4, 2);
+        let par = s.par.clone();
+        let sig = s.signature();
+        let from_bytes = Params::from_bytes(&par.to_bytes()).unwrap();
+        let from_json: Params = serde_json::from_str(&serde_json::to_string(&par).unwrap()).unwrap();
+        for decoded in [from_bytes, from_json] {
+            assert_eq!(decoded.fingerprint(), par.fingerprint());
+            assert!(verify(&decoded, &s.pk_joint, &s.ss, MSG, &sig));
+
+            // A whole session run under the decoded params.
+            s.par = decoded;
+            s.run(MSG);
+            assert!(verify(&par, &s.pk_joint, &s.ss, MSG, &s.signature()));
+        }
+    }
+
+    #[test]
+    fn params_reject_an_undecodable_generator() {
+        let par = Session::new(4, 2).par;
+        for k in 0..3 {
+            let mut bytes = par.to_bytes();
+            bytes[16 + 32 * k + 31] |= 0x80;
+            assert_eq!(Params::from_bytes(&bytes).err(), Some(Error::NonCanonicalPoint));
+        }
+
+        let mut wire = ParamsWire::from(par);
+        wire.v = [0xff; 32];
+        let json = serde_json::to_string(&wire).unwrap();
+        assert!(serde_json::from_str::<Params>(&json).is_err());
+    }
+
+    #[test]
+    fn verification_key_rejects_bad_fields() {
+        let s = Session::new(4, 2);
+        let vk = VerificationKey { params_public: s.par.clone(), pk_joint: s.pk_joint };
+
+        // pk_joint follows n, t, g, h and v.
+        let mut bytes = vk.to_bytes();
+        bytes[16 + 3 * 32 + 31] |= 0x80;
+        assert_eq!(VerificationKey::from_bytes(&bytes).err(), Some(Error::NonCanonicalPoint));
+
+        // t >= n
+        let mut bytes = vk.to_bytes();
+        bytes[8..16].copy_from_slice(&4u64.to_le_bytes());
+        assert_eq!(VerificationKey::from_bytes(&bytes).err(), Some(Error::MalformedEncoding));
+    }
+}
@@ -0,0 +1,118 @@
+// src/wasm.rs
+//
+// wasm-bindgen wrappers so a browser can check signatures without a native
+// dependency. Only the public verification side is exported; keys, nonces
+// and proofs never reach JavaScript. Every argument is one of the crate's
+// fixed byte encodings (`Params::to_bytes`, a compressed point,
+// `Signature::to_bytes`, a canonical scalar); a malformed one verifies as
+// false rather than throwing.
+
+use alloc::vec::Vec;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use wasm_bindgen::prelude::*;
+
+use crate::commitment::{derive_h_from_g, verify_aggregate as vc_verify_aggregate};
+use crate::hash::{dec_scalar_canonical, decode_point};
+use crate::protocol::verify as protocol_verify;
+use crate::types::{Params, Signature};
+
+fn params(bytes: &[u8]) -> Option<Params> {
+    Params::from_bytes(bytes.try_into().ok()?).ok()
+}
+
+fn point(bytes: &[u8]) -> Option<RistrettoPoint> {
+    decode_point(bytes.try_into().ok()?).ok()
+}
+
+/// `protocol::verify` over byte encodings: `Params::to_bytes`, the
+/// compressed joint key and `Signature::to_bytes`. `ss` is the signing set
+/// (a `Uint32Array` in JavaScript).
+#[wasm_bindgen]
+pub fn verify(params_bytes: &[u8], pk_bytes: &[u8], ss: &[u32], message: &[u8], sig_bytes: &[u8]) -> bool {
+    let (Some(par), Some(pk)) = (params(params_bytes), point(pk_bytes)) else {
+        return false;
+    };
+    let Some(sig) = JsSignature::from_bytes(sig_bytes) else {
+        return false;
+    };
+    protocol_verify(&par, &pk, ss, message, &sig.inner)
+}
+
+/// `commitment::verify_aggregate` for the VC generators of `params_bytes`
+/// (g, and h = `derive_h_from_g(g)`): C == g*z + h*r, with C compressed and
+/// z, r canonical scalars.
+#[wasm_bindgen(js_name = verifyAggregate)]
+pub fn verify_aggregate(params_bytes: &[u8], c_agg: &[u8], z: &[u8], r_agg: &[u8]) -> bool {
+    let Some(par) = params(params_bytes) else {
+        return false;
+    };
+    let (Ok(c_agg), Ok(z), Ok(r_agg)) = (c_agg.try_into(), z.try_into(), r_agg.try_into()) else {
+        return false;
+    };
+    let (Some(z), Some(_)) = (dec_scalar_canonical(z), dec_scalar_canonical(r_agg)) else {
+        return false;
+    };
+    vc_verify_aggregate(&par.g, &derive_h_from_g(&par.g), c_agg, &z, r_agg)
+}
+
+/// A decoded `Signature`, exported to JavaScript as `Signature`.
+#[wasm_bindgen(js_name = Signature)]
+pub struct JsSignature {
+    inner: Signature,
+}
+
+#[wasm_bindgen(js_class = Signature)]
+impl JsSignature {
+    /// `Signature::from_bytes` for a 64-byte slice; undefined for any other
+    /// length or a non-canonical encoding.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(bytes: &[u8]) -> Option<JsSignature> {
+        Signature::from_bytes(bytes.try_into().ok()?).map(|inner| JsSignature { inner })
+    }
+
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes().to_vec()
+    }
+
+    /// `verify` for this signature.
+    pub fn verify(&self, params_bytes: &[u8], pk_bytes: &[u8], ss: &[u32], message: &[u8]) -> bool {
+        verify(params_bytes, pk_bytes, ss, message, &self.to_bytes())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::commitment::{aggregate_commitments, aggregate_openings, commit_z};
+    use crate::hash::{dec_scalar, enc_point, enc_scalar};
+    use crate::testutil::{Session, MSG};
+
+    #[test]
+    fn byte_wrappers_agree_with_the_native_checks() {
+        let s = Session::new(4, 2);
+        let signature = s.signature();
+        let (par, pk, sig) = (s.par.to_bytes(), enc_point(&s.pk_joint), signature.to_bytes());
+        let ss = &s.ss[..];
+        assert!(verify(&par, &pk, ss, MSG, &sig));
+        assert!(!verify(&par, &pk, ss, b"other", &sig));
+        assert!(!verify(&par, &pk, &ss[1..], MSG, &sig));
+        assert!(!verify(&par, &pk, ss, MSG, &sig[..63]));
+        assert!(!verify(&par[1..], &pk, ss, MSG, &sig));
+
+        let js = JsSignature::from_bytes(&sig).unwrap();
+        assert_eq!(js.to_bytes(), sig.to_vec());
+        assert!(js.verify(&par, &pk, ss, MSG));
+        assert!(JsSignature::from_bytes(&[0xff; 64]).is_none());
+
+        let h = derive_h_from_g(&s.par.g);
+        let (cms, ops): (Vec<_>, Vec<_>) =
+            s.partials.iter().map(|ps| commit_z(ps.i, &s.par.g, &h, &dec_scalar(&ps.z_i))).unzip();
+        let c_agg = aggregate_commitments(&cms).unwrap();
+        let r_agg = aggregate_openings(&ops);
+        let z = enc_scalar(&signature.z);
+        assert!(verify_aggregate(&par, &c_agg, &z, &r_agg));
+        assert!(!verify_aggregate(&par, &c_agg, &enc_scalar(&(signature.z + curve25519_dalek::Scalar::ONE)), &r_agg));
+    }
+}
@@ -0,0 +1,169 @@
+// The Fig.4 proof and the Schnorr check through the `Group` trait alone, in
+// Ristretto and in a toy group that shares no code with it.
+//
+// Two signers hold additive shares (s_1 + s_2 = s, r_1 + r_2 = 0,
+// u_1 + u_2 = 0), so every Lagrange weight is 1 and the flow needs no
+// scalar inversion, which the trait does not offer.
+#![cfg(feature = "std")]
+
+use rand::CryptoRng;
+use sha2::{Digest, Sha512};
+use threshold_signature::group::{Bases, Group, Ristretto};
+use threshold_signature::hash::{f0_in, f1_in, hsig_in};
+use threshold_signature::keygen::setup;
+use threshold_signature::nizk::{sig_prove_in, sig_verify_in};
+use threshold_signature::protocol::{verify, verify_in};
+use threshold_signature::types::Signature;
+
+/// Squares mod the safe prime P = 2Q + 1, a group of prime order Q. Every
+/// point has a known log to base 4, so this is only a test double.
+#[derive(Clone, Copy, Debug)]
+struct Toy;
+
+const P: u64 = 4_611_686_018_427_377_339;
+const Q: u64 = 2_305_843_009_213_688_669;
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+fn pow_mod(mut base: u64, mut e: u64) -> u64 {
+    let mut acc = 1;
+    while e > 0 {
+        if e & 1 == 1 {
+            acc = mul_mod(acc, base, P);
+        }
+        base = mul_mod(base, base, P);
+        e >>= 1;
+    }
+    acc
+}
+
+fn hash_mod_q(domain: &[u8], data: &[u8]) -> u64 {
+    let mut h = Sha512::new();
+    h.update((domain.len() as u64).to_le_bytes());
+    h.update(domain);
+    h.update(data);
+    let digest = h.finalize();
+    (u128::from_le_bytes(digest[..16].try_into().unwrap()) % Q as u128) as u64
+}
+
+impl Group for Toy {
+    type Point = u64;
+    type Scalar = u64;
+
+    fn basepoint() -> u64 {
+        4
+    }
+    fn identity() -> u64 {
+        1
+    }
+    fn add(a: &u64, b: &u64) -> u64 {
+        mul_mod(*a, *b, P)
+    }
+    fn mul(p: &u64, k: &u64) -> u64 {
+        pow_mod(*p, *k)
+    }
+    fn scalar_add(a: &u64, b: &u64) -> u64 {
+        ((*a as u128 + *b as u128) % Q as u128) as u64
+    }
+    fn scalar_mul(a: &u64, b: &u64) -> u64 {
+        mul_mod(*a, *b, Q)
+    }
+    fn scalar_neg(a: &u64) -> u64 {
+        (Q - a % Q) % Q
+    }
+    fn scalar_one() -> u64 {
+        1
+    }
+    fn random_scalar(rng: &mut impl CryptoRng) -> u64 {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        Self::scalar_from_uniform_bytes(&bytes)
+    }
+    fn scalar_from_uniform_bytes(bytes: &[u8; 64]) -> u64 {
+        (u128::from_le_bytes(bytes[..16].try_into().unwrap()) % Q as u128) as u64
+    }
+    fn encode_point(p: &u64) -> Vec<u8> {
+        p.to_be_bytes().to_vec()
+    }
+    fn hash_to_point(domain: &[u8], data: &[u8]) -> u64 {
+        pow_mod(4, hash_mod_q(domain, data))
+    }
+    fn hash_to_scalar(domain: &[u8], data: &[u8]) -> u64 {
+        hash_mod_q(domain, data)
+    }
+}
+
+/// Signs `message` with two additive shares over `bases`, checking every
+/// proof and the final signature, plus tampered variants of each. Returns
+/// (pk, A_hat, z).
+fn sign_and_verify<G: Group>(
+    bases: &Bases<G>,
+    message: &[u8],
+    rng: &mut impl CryptoRng,
+) -> (G::Point, G::Point, G::Scalar) {
+    let ss = [1u32, 2];
+    let [s1, s2, r, u] = [(); 4].map(|_| G::random_scalar(rng));
+    let shares = [(s1, r, u), (s2, G::scalar_neg(&r), G::scalar_neg(&u))];
+    let pk_joint = G::mul(&bases.g, &G::scalar_add(&s1, &s2));
+    // Stand-ins for the session-derived g0', g1'.
+    let g0 = G::hash_to_point(b"test g0", message);
+    let g1 = G::hash_to_point(b"test g1", message);
+
+    let mut a_hat = G::identity();
+    // The trait has no zero scalar.
+    let mut z = G::scalar_add(&G::scalar_one(), &G::scalar_neg(&G::scalar_one()));
+    let mut a_sum = z;
+    let mut partials = Vec::new();
+    for (&i, (s, r, u)) in ss.iter().zip(&shares) {
+        let pk_i = G::multiscalar_mul(&[*s, *r, *u], &[bases.g, bases.h, bases.v]);
+        let a = G::random_scalar(rng);
+        let mut rho = [0u8; 32];
+        rng.fill_bytes(&mut rho);
+        let a_i = G::multiscalar_mul(&[a, *r, *u], &[bases.g, g0, g1]);
+        let b_i = G::multiscalar_mul(&[a, *r, *u], &[bases.g, f0_in::<G>(&rho), f1_in::<G>(&rho)]);
+        let proof = sig_prove_in(bases, i, &pk_i, &a_i, &b_i, &g0, &g1, &rho, &a, (s, r, u), rng);
+
+        let mut check = |i: u32, pk_i: &G::Point, a_i: &G::Point| {
+            sig_verify_in(bases, i, pk_i, a_i, &b_i, &g0, &g1, &rho, &proof, rng)
+        };
+        assert!(check(i, &pk_i, &a_i));
+        assert!(!check(i + 1, &pk_i, &a_i));
+        assert!(!check(i, &pk_joint, &a_i));
+        assert!(!check(i, &pk_i, &G::add(&a_i, &bases.g)));
+
+        a_hat = G::add(&a_hat, &a_i);
+        a_sum = G::scalar_add(&a_sum, &a);
+        partials.push((a, *s));
+    }
+    // The r and u terms cancel in the sum of the A_i.
+    assert_eq!(a_hat, G::mul(&bases.g, &a_sum));
+
+    let c = hsig_in::<G>(&a_hat, &pk_joint, &ss, message);
+    for (a, s) in &partials {
+        z = G::scalar_add(&z, &G::scalar_add(a, &G::scalar_mul(&c, s)));
+    }
+    assert!(verify_in(bases, &pk_joint, &ss, message, &a_hat, &z));
+    assert!(!verify_in(bases, &pk_joint, &ss, b"another message", &a_hat, &z));
+    assert!(!verify_in(bases, &pk_joint, &ss[..1], message, &a_hat, &z));
+    assert!(!verify_in(bases, &pk_joint, &ss, message, &a_hat, &G::scalar_add(&z, &G::scalar_one())));
+    (pk_joint, a_hat, z)
+}
+
+#[test]
+fn ristretto_flow_runs_through_the_trait() {
+    let par = setup(2, 1).unwrap();
+    let (pk, a_hat, z) = sign_and_verify::<Ristretto>(&par.bases(), b"generic flow", &mut rand::rng());
+    assert!(verify(&par, &pk, &[1, 2], b"generic flow", &Signature { A_hat: a_hat, z }));
+}
+
+#[test]
+fn toy_group_flow_runs_through_the_trait() {
+    let bases = Bases::<Toy> {
+        g: Toy::basepoint(),
+        h: Toy::hash_to_point(b"test h", b""),
+        v: Toy::hash_to_point(b"test v", b""),
+    };
+    sign_and_verify::<Toy>(&bases, b"generic flow", &mut rand::rng());
+}
@@ -0,0 +1,91 @@
+// A signing session driven only through the `_with_rng` entry points, the
+// API left under `--no-default-features` (no_std + alloc, no OsRng).
+
+use curve25519_dalek::scalar::Scalar;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use threshold_signature::keygen::{kgen_for_ids_with_rng, setup};
+use threshold_signature::protocol::{
+    combine, sig1_with_rng, sign_with_rng, verify, verify_batch_with_rng, SigningSession,
+};
+use threshold_signature::types::{share_for, Signature};
+
+const MSG: &[u8] = b"signed on a constrained device";
+
+#[test]
+fn session_with_an_injected_rng_verifies() {
+    let mut rng = ChaCha20Rng::seed_from_u64(804);
+    let par = setup(5, 2).unwrap();
+    let ids: Vec<u32> = (1..=5).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids_with_rng(&par, &ids, &mut rng).unwrap();
+    let sk = |i| share_for(&sk_shares, i).unwrap();
+    let pk = |i| share_for(&pk_shares, i).unwrap();
+    let pk_map: Vec<_> = pk_shares.iter().map(|(i, p)| (*i, p.pk_i)).collect();
+    let ss = [1u32, 3, 5];
+
+    let (commitments, states): (Vec<_>, Vec<_>) = ss
+        .iter()
+        .map(|&i| {
+            let (cm, st) = sig1_with_rng(&par, i, sk(i), &mut rng);
+            ((cm.i, cm.mu_i), st)
+        })
+        .unzip();
+    let mut session = SigningSession::new_with_rng(&par, MSG, &ss, &pk_joint, &pk_map, &mut rng);
+    let (openings, states2): (Vec<_>, Vec<_>) = ss
+        .iter()
+        .zip(&states)
+        .map(|(&i, st)| session.sig2(i, &commitments, pk(i), sk(i), st).unwrap())
+        .unzip();
+    let partials: Vec<_> = ss
+        .iter()
+        .zip(&states2)
+        .map(|(&i, st)| session.sig3(i, sk(i), st, &commitments, &openings).unwrap())
+        .collect();
+
+    let sig = combine(&ss, &openings, &partials).unwrap();
+    assert!(verify(&par, &pk_joint, &ss, MSG, &sig));
+}
+
+#[test]
+fn sign_with_rng_runs_the_same_session() {
+    let par = setup(5, 2).unwrap();
+    let ids: Vec<u32> = (1..=5).collect();
+    let (pk_joint, pk_shares, sk_shares, _) =
+        kgen_for_ids_with_rng(&par, &ids, &mut ChaCha20Rng::seed_from_u64(804)).unwrap();
+    let ss = [1u32, 3, 5];
+
+    let sig = |seed| {
+        sign_with_rng(&par, &sk_shares, &pk_shares, &pk_joint, &ss, MSG, &mut ChaCha20Rng::seed_from_u64(seed))
+            .unwrap()
+            .to_bytes()
+    };
+    assert_eq!(sig(1), sig(1));
+    assert_ne!(sig(1), sig(2));
+}
+
+#[test]
+fn verify_batch_with_rng_runs_on_seeded_weights() {
+    let mut rng = ChaCha20Rng::seed_from_u64(804);
+    let par = setup(5, 2).unwrap();
+    let ids: Vec<u32> = (1..=5).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids_with_rng(&par, &ids, &mut rng).unwrap();
+    let ss = [1u32, 3, 5];
+    let messages = [&b"a"[..], b"b", b"c"];
+    let sigs: Vec<Signature> = messages
+        .iter()
+        .map(|m| sign_with_rng(&par, &sk_shares, &pk_shares, &pk_joint, &ss, m, &mut rng).unwrap())
+        .collect();
+    let mut batch: Vec<(&[u32], &[u8], &Signature)> =
+        messages.iter().zip(&sigs).map(|(m, sig)| (&ss[..], *m, sig)).collect();
+
+    let check = |batch: &[(&[u32], &[u8], &Signature)], seed| {
+        verify_batch_with_rng(&par, &pk_joint, batch, &mut ChaCha20Rng::seed_from_u64(seed))
+    };
+    assert!(check(&batch, 1));
+    assert!(check(&batch, 2));
+
+    let forged = Signature { A_hat: sigs[1].A_hat, z: sigs[1].z + Scalar::ONE };
+    batch[1].2 = &forged;
+    assert!(!check(&batch, 1));
+    assert!(!check(&batch, 2));
+}
@@ -0,0 +1,58 @@
+// Generate a key set to disk, load every signer's package back and sign
+// with the loaded material.
+#![cfg(feature = "at-rest")]
+
+use std::fs;
+
+use threshold_signature::error::{Error, IoErrorKind};
+use threshold_signature::keygen::{generate_to_dir, load_package};
+use threshold_signature::protocol::{sign, verify, verify_with_key};
+use threshold_signature::types::VerificationKey;
+
+const MSG: &[u8] = b"signed from files";
+const PASSWORDS: [&str; 4] = ["alpha", "bravo", "charlie", "delta"];
+
+#[test]
+fn generated_packages_load_and_sign() {
+    let dir = std::env::temp_dir().join(format!("gargos-key-files-{}", std::process::id()));
+    let vk = generate_to_dir(4, 2, &dir, &PASSWORDS).unwrap();
+    let stored = VerificationKey::from_bytes(&fs::read(dir.join("verification_key.bin")).unwrap().try_into().unwrap())
+        .unwrap();
+    assert_eq!(stored.to_bytes(), vk.to_bytes());
+
+    let signers: Vec<_> = (1..=4u32)
+        .map(|i| load_package(&dir.join(format!("signer_{}.pkg", i)), PASSWORDS[i as usize - 1]).unwrap())
+        .collect();
+    // Each package opens only under its own signer's password.
+    assert_eq!(
+        load_package(&dir.join("signer_1.pkg"), PASSWORDS[1]).err(),
+        Some(Error::UnsealFailed)
+    );
+    fs::remove_dir_all(&dir).unwrap();
+
+    let par = &vk.params_public;
+    let ss = [1u32, 3, 4];
+    for (i, (pkg, _)) in (1..).zip(&signers) {
+        assert_eq!(pkg.i, i);
+    }
+    let sk_shares: Vec<_> = signers.iter().map(|(pkg, sk)| (pkg.i, sk.clone())).collect();
+    let sig = sign(par, &sk_shares, &signers[0].0.pk_shares, &vk.pk_joint, &ss, MSG).unwrap();
+    assert!(verify(par, &vk.pk_joint, &ss, MSG, &sig));
+    assert!(verify_with_key(&stored, &ss, MSG, &sig));
+}
+
+#[test]
+fn generate_to_dir_needs_one_password_per_signer() {
+    let dir = std::env::temp_dir().join(format!("gargos-key-files-short-{}", std::process::id()));
+    assert_eq!(
+        generate_to_dir(4, 2, &dir, &PASSWORDS[..3]).err(),
+        Some(Error::SignerCountMismatch { expected: 4, got: 3 })
+    );
+    assert!(!dir.exists());
+}
+
+#[test]
+fn missing_package_is_an_io_error() {
+    let path = std::env::temp_dir().join(format!("gargos-key-files-missing-{}.pkg", std::process::id()));
+    assert_eq!(load_package(&path, PASSWORDS[0]).err(), Some(Error::Io(IoErrorKind::NotFound)));
+}
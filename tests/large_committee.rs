@@ -0,0 +1,46 @@
+// Full signing at the largest committee the benchmarks sweep: n = 128,
+// t = 64, so every signing set has 65 members.
+#![cfg(feature = "std")]
+
+use threshold_signature::keygen::{kgen_for_ids, setup};
+use threshold_signature::protocol::{sign, verify, verify_batch};
+use threshold_signature::types::Signature;
+
+const N: usize = 128;
+const T: usize = 64;
+
+#[test]
+fn n_128_signatures_verify_singly_and_in_a_batch() {
+    let par = setup(N, T).unwrap();
+    let ids: Vec<u32> = (1..=N as u32).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids).unwrap();
+
+    // Ten different quorums of t+1, each signing its own message. The
+    // strides 1, 3 and 5 are odd, so the 65 ids of a quorum are distinct.
+    let sessions: Vec<(Vec<u32>, Vec<u8>)> = (0..10u32)
+        .map(|k| {
+            let ss: Vec<u32> = (0..=T as u32).map(|j| (j * (2 * (k % 3) + 1) + 6 * k) % N as u32 + 1).collect();
+            (ss, format!("large committee message {}", k).into_bytes())
+        })
+        .collect();
+    let sigs: Vec<Signature> = sessions
+        .iter()
+        .map(|(ss, msg)| {
+            let sig = sign(&par, &sk_shares, &pk_shares, &pk_joint, ss, msg).unwrap();
+            assert!(verify(&par, &pk_joint, ss, msg, &sig));
+            sig
+        })
+        .collect();
+
+    let mut batch: Vec<(&[u32], &[u8], &Signature)> =
+        sessions.iter().zip(&sigs).map(|((ss, msg), sig)| (&ss[..], &msg[..], sig)).collect();
+    assert!(verify_batch(&par, &pk_joint, &batch));
+
+    // Pairing a signature with another session's message breaks the batch,
+    // and so does pairing it with another session's quorum.
+    batch[9].1 = &sessions[0].1;
+    assert!(!verify_batch(&par, &pk_joint, &batch));
+    batch[9].1 = &sessions[9].1;
+    batch[9].0 = &sessions[0].0;
+    assert!(!verify_batch(&par, &pk_joint, &batch));
+}
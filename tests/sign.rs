@@ -0,0 +1,35 @@
+// `protocol::sign` end to end: keys from `kgen_for_ids`, every round run
+// in-process, the result checked with `verify`.
+#![cfg(feature = "std")]
+
+use threshold_signature::keygen::{kgen_for_ids, setup};
+use threshold_signature::protocol::{sign, verify, SignError};
+
+const MSG: &[u8] = b"signed in one call";
+
+#[test]
+fn signatures_verify_for_several_n_t() {
+    for (n, t) in [(1, 0), (3, 1), (4, 2), (7, 3), (5, 4)] {
+        let par = setup(n, t).unwrap();
+        let ids: Vec<u32> = (1..=n as u32).collect();
+        let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids).unwrap();
+
+        // The first and the last t+1 signers, and everyone.
+        for ss in [&ids[..t + 1], &ids[n - t - 1..], &ids[..]] {
+            let sig = sign(&par, &sk_shares, &pk_shares, &pk_joint, ss, MSG).unwrap();
+            assert!(verify(&par, &pk_joint, ss, MSG, &sig), "n={} t={} ss={:?}", n, t, ss);
+            assert!(!verify(&par, &pk_joint, ss, b"another message", &sig));
+        }
+    }
+}
+
+#[test]
+fn sign_needs_a_quorum() {
+    let par = setup(4, 2).unwrap();
+    let ids: Vec<u32> = (1..=4).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids).unwrap();
+    assert_eq!(
+        sign(&par, &sk_shares, &pk_shares, &pk_joint, &[1, 2], MSG).err(),
+        Some(SignError::QuorumTooSmall)
+    );
+}
@@ -0,0 +1,59 @@
+// `Signature::to_standard_schnorr` checked by a verifier written from its
+// doc comment alone: only sha2 and curve25519-dalek, none of the crate's
+// hashing or verification code.
+#![cfg(feature = "std")]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+use threshold_signature::keygen::{kgen_for_ids, setup};
+use threshold_signature::protocol::{sign, verify};
+
+fn reference_verify(pk: &[u8; 32], ss: &[u32], message: &[u8], (r, s): ([u8; 32], [u8; 32])) -> bool {
+    let Some(pk_point) = CompressedRistretto(*pk).decompress() else { return false };
+    let Some(r_point) = CompressedRistretto(r).decompress() else { return false };
+    let Some(s) = Option::<Scalar>::from(Scalar::from_canonical_bytes(s)) else { return false };
+
+    let mut h = Sha512::new();
+    let mut ids = ss.to_vec();
+    ids.sort();
+    h.update(b"Gargos::Hsig::v3");
+    h.update((ids.len() as u32).to_le_bytes());
+    for i in ids {
+        h.update(i.to_le_bytes());
+    }
+    h.update(message);
+    h.update(r);
+    h.update(pk);
+    let c = Scalar::from_bytes_mod_order_wide(&h.finalize().into());
+
+    RISTRETTO_BASEPOINT_POINT * s == r_point + pk_point * c
+}
+
+#[test]
+fn reference_verifier_agrees_with_verify() {
+    let par = setup(5, 2).unwrap();
+    let ids: Vec<u32> = (1..=5).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids(&par, &ids).unwrap();
+    let pk = pk_joint.compress().to_bytes();
+
+    for (k, message) in [&b""[..], b"m", &[0xa5; 300]].into_iter().enumerate() {
+        let ss = &ids[k..k + 3];
+        let sig = sign(&par, &sk_shares, &pk_shares, &pk_joint, ss, message).unwrap();
+        let std_sig = sig.to_standard_schnorr();
+        assert_eq!(std_sig.0, sig.to_bytes()[..32]);
+        assert!(verify(&par, &pk_joint, ss, message, &sig));
+        assert!(reference_verify(&pk, ss, message, std_sig));
+
+        // It rejects the wrong message, a shifted s, the wrong key and the
+        // wrong signing set.
+        assert!(!reference_verify(&pk, ss, b"another message", std_sig));
+        let (r, s) = std_sig;
+        let bumped = (Scalar::from_canonical_bytes(s).unwrap() + Scalar::ONE).to_bytes();
+        assert!(!reference_verify(&pk, ss, message, (r, bumped)));
+        let other_pk = RISTRETTO_BASEPOINT_POINT.compress().to_bytes();
+        assert!(!reference_verify(&other_pk, ss, message, std_sig));
+        assert!(!reference_verify(&pk, &ids[..], message, std_sig));
+    }
+}
@@ -0,0 +1,43 @@
+// Headless browser test of the `wasm` bindings: a signature produced by the
+// Rust signing path (seeded, through `sign_with_rng`, since there is no
+// OsRng here) must verify through the JavaScript-facing wrappers. Run with
+//   wasm-pack test --headless --firefox -- --no-default-features --features wasm --test wasm
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use threshold_signature::hash::enc_point;
+use threshold_signature::keygen::{kgen_for_ids_with_rng, setup};
+use threshold_signature::protocol::sign_with_rng;
+use threshold_signature::wasm::{verify, JsSignature};
+use wasm_bindgen_test::*;
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const MSG: &[u8] = b"checked in the browser";
+
+/// The signing set of `signed`.
+const SS: [u32; 2] = [2, 4];
+
+/// (Params::to_bytes, compressed pk_joint, Signature::to_bytes) of a 2-of-4
+/// session over MSG.
+fn signed() -> (Vec<u8>, [u8; 32], [u8; 64]) {
+    let mut rng = ChaCha20Rng::seed_from_u64(805);
+    let par = setup(4, 1).unwrap();
+    let ids: Vec<u32> = (1..=4).collect();
+    let (pk_joint, pk_shares, sk_shares, _) = kgen_for_ids_with_rng(&par, &ids, &mut rng).unwrap();
+    let sig = sign_with_rng(&par, &sk_shares, &pk_shares, &pk_joint, &SS, MSG, &mut rng).unwrap();
+    (par.to_bytes().to_vec(), enc_point(&pk_joint), sig.to_bytes())
+}
+
+#[wasm_bindgen_test]
+fn native_signature_verifies_in_wasm() {
+    let (par, pk, sig) = signed();
+    assert!(verify(&par, &pk, &SS, MSG, &sig));
+    assert!(!verify(&par, &pk, &SS, b"another message", &sig));
+    assert!(!verify(&par, &pk, &[2, 3], MSG, &sig));
+
+    let js = JsSignature::from_bytes(&sig).unwrap();
+    assert!(js.verify(&par, &pk, &SS, MSG));
+    assert!(JsSignature::from_bytes(&sig[..32]).is_none());
+}